@@ -1,11 +1,11 @@
-use std::env;
-use std::fs;
-use std::path::Path;
-use std::process::Command;
-
 fn main() {
 
     /*
+    use std::env;
+    use std::fs;
+    use std::path::Path;
+    use std::process::Command;
+
     // Trigger rebuild if anything in the frontend source changes.
     println!("cargo:rerun-if-changed=../frontend/src");
 
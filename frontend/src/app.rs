@@ -1,7 +1,13 @@
+use crate::components::audit_log::AuditLog;
 use crate::components::dag_graph::DAGGraph;
+use crate::components::dag_runs::DagRuns;
 use crate::components::nav_bar::NavBar;
 use crate::components::dag_list::DagList;
+use crate::components::login::{Login, Logout};
+use crate::components::users::Users;
 use crate::models::theme::{Theme, ThemeContext};
+use crate::models::user::{AuthContext, AuthStatus};
+use crate::utils::api::fetch_auth_status;
 use yew::prelude::*;
 use yew_hooks::use_location;
 use chrono::Datelike;
@@ -57,13 +63,84 @@ pub fn app() -> Html {
         });
     }
 
+    // Ask the server whether sign-in is required and who is signed in
+    let auth_status = use_state(|| None::<AuthStatus>);
+    let auth_error = use_state(|| None::<String>);
+    {
+        let auth_status = auth_status.clone();
+        let auth_error = auth_error.clone();
+        use_effect_with((), move |_| {
+            wasm_bindgen_futures::spawn_local(async move {
+                match fetch_auth_status().await {
+                    Ok(status) => auth_status.set(Some(status)),
+                    Err(err) => auth_error.set(Some(err)),
+                }
+            });
+            || {}
+        });
+    }
+    let auth_ctx = {
+        let auth_status_clone = auth_status.clone();
+        AuthContext::new(
+            (*auth_status).clone(),
+            Callback::from(move |status| auth_status_clone.set(Some(status))),
+        )
+    };
+
     // Use the location hook to get the current URL
     let location = use_location();
     let pathname = location.pathname.clone();
     
     // Parse the URL to decide which view to show
     let view = {
-        if pathname.starts_with("/dag/") && pathname.contains("/graph") {
+        if let Some(err) = &*auth_error {
+            html! {
+                <div class="p-4 text-red-600">{format!("Could not reach the server: {}", err)}</div>
+            }
+        } else if auth_ctx.status.is_none() {
+            html! {}
+        } else if pathname == "/login" {
+            let next = location.search
+                .trim_start_matches('?')
+                .split('&')
+                .find_map(|pair| pair.strip_prefix("next="))
+                .and_then(|value| js_sys::decode_uri_component(value).ok())
+                .and_then(|value| value.as_string())
+                // Only paths on this server, never another site
+                .filter(|next| next.starts_with('/') && !next.starts_with("//"))
+                .unwrap_or_else(|| "/".to_string());
+            html! { <Login {next} /> }
+        } else if pathname == "/logout" {
+            html! { <Logout /> }
+        } else if auth_ctx.auth_enabled() && auth_ctx.user().is_none() {
+            // Signed out: sign in, then come back here
+            html! { <Login next={format!("{}{}", pathname, location.search)} /> }
+        } else if pathname == "/users" {
+            html! { <Users /> }
+        } else if pathname == "/audit" {
+            let dag_id = location.search
+                .trim_start_matches('?')
+                .split('&')
+                .find_map(|pair| pair.strip_prefix("dag_id="))
+                .and_then(|value| js_sys::decode_uri_component(value).ok())
+                .and_then(|value| value.as_string());
+            html! { <AuditLog {dag_id} /> }
+        } else if pathname.starts_with("/dag/") && pathname.ends_with("/runs") {
+            let dag_id = pathname.strip_prefix("/dag/").unwrap_or("")
+                .strip_suffix("/runs").unwrap_or("");
+            if !dag_id.is_empty() {
+                let decoded_dag_id = match js_sys::decode_uri_component(dag_id) {
+                    Ok(decoded) => decoded,
+                    Err(_) => js_sys::JsString::from(dag_id)
+                }.as_string().unwrap_or_else(|| dag_id.to_string());
+
+                html! {
+                    <DagRuns dag_id={decoded_dag_id} />
+                }
+            } else {
+                html! { <DagList /> }
+            }
+        } else if pathname.starts_with("/dag/") && pathname.contains("/graph") {
             let dag_id = pathname.strip_prefix("/dag/").unwrap_or("")
                 .strip_suffix("/graph").unwrap_or("");
             if !dag_id.is_empty() {
@@ -73,8 +150,16 @@ pub fn app() -> Html {
                     Err(_) => js_sys::JsString::from(dag_id.clone())
                 }.as_string().unwrap_or_else(|| dag_id.to_string());
                 
+                // `?run_id=...` opens the graph on a specific run
+                let run_id = location.search
+                    .trim_start_matches('?')
+                    .split('&')
+                    .find_map(|pair| pair.strip_prefix("run_id="))
+                    .and_then(|value| js_sys::decode_uri_component(value).ok())
+                    .and_then(|value| value.as_string());
+
                 html! {
-                    <DAGGraph dag_id={decoded_dag_id} />
+                    <DAGGraph dag_id={decoded_dag_id} {run_id} />
                 }
             } else {
                 html! { <DagList /> }
@@ -86,6 +171,7 @@ pub fn app() -> Html {
 
     html! {
         <ContextProvider<ThemeContext> context={theme_ctx}>
+        <ContextProvider<AuthContext> context={auth_ctx}>
             <div class="flex flex-col min-h-screen">
                 <NavBar />
                 <main class="flex-grow w-full">
@@ -97,6 +183,7 @@ pub fn app() -> Html {
                     </div>
                 </footer>
             </div>
+        </ContextProvider<AuthContext>>
         </ContextProvider<ThemeContext>>
     }
 }
@@ -0,0 +1,249 @@
+use crate::components::table::{Table, TableBody, TableHead};
+use crate::models::audit::{AuditEntry, AuditQuery, AuditResponse, AUDIT_ACTIONS};
+use crate::utils::api::{fetch_audit, format_datetime};
+use serde_json::Value;
+use web_sys::{HtmlInputElement, HtmlSelectElement};
+use yew::prelude::*;
+use yew_hooks::prelude::*;
+
+const INPUT_CLASS: &str =
+    "border border-gray-300 dark:border-gray-600 rounded px-2 py-1 text-sm dark:bg-gray-800 dark:text-gray-200";
+const TH_CLASS: &str = "px-4 py-2 text-left text-xs font-medium text-gray-500 dark:text-gray-400 uppercase";
+
+#[derive(Properties, PartialEq)]
+pub struct AuditLogProps {
+    /// Start filtered to one DAG
+    #[prop_or_default]
+    pub dag_id: Option<String>,
+}
+
+/// Who paused, triggered, cleared or changed what, newest first
+#[function_component(AuditLog)]
+pub fn audit_log(props: &AuditLogProps) -> Html {
+    let query = use_state(|| AuditQuery {
+        dag_id: props.dag_id.clone(),
+        page: Some(1),
+        limit: Some(50),
+        ..Default::default()
+    });
+
+    let response = use_state(|| AuditResponse {
+        entries: vec![],
+        total_count: 0,
+    });
+
+    let loading = use_state(|| true);
+    let error = use_state(|| None::<String>);
+
+    let fetch_data = {
+        let query = query.clone();
+        let response = response.clone();
+        let loading = loading.clone();
+        let error = error.clone();
+        use_async(async move {
+            loading.set(true);
+            error.set(None);
+
+            match fetch_audit(&query).await {
+                Ok(result) => {
+                    response.set(result);
+                    loading.set(false);
+                    Ok(())
+                }
+                Err(err) => {
+                    error.set(Some(err.clone()));
+                    loading.set(false);
+                    Err(err)
+                }
+            }
+        })
+    };
+
+    {
+        let fetch_data = fetch_data.clone();
+        use_effect_with((*query).clone(), move |_| {
+            fetch_data.run();
+            || {}
+        });
+    }
+
+    // Text filters apply once the field is left or Enter is pressed
+    let text_filter = |set: fn(&mut AuditQuery, Option<String>)| {
+        let query = query.clone();
+        Callback::from(move |e: Event| {
+            let input: HtmlInputElement = e.target_unchecked_into();
+            let value = input.value().trim().to_string();
+            let mut new_query = (*query).clone();
+            set(&mut new_query, Some(value).filter(|value| !value.is_empty()));
+            new_query.page = Some(1);
+            query.set(new_query);
+        })
+    };
+    let on_actor_change = text_filter(|query, value| query.actor = value);
+    let on_dag_change = text_filter(|query, value| query.dag_id = value);
+
+    let on_action_change = {
+        let query = query.clone();
+        Callback::from(move |e: Event| {
+            let select: HtmlSelectElement = e.target_unchecked_into();
+            let value = select.value();
+            let mut new_query = (*query).clone();
+            new_query.action = if value.is_empty() { None } else { Some(value) };
+            new_query.page = Some(1);
+            query.set(new_query);
+        })
+    };
+
+    let page = query.page.unwrap_or(1);
+    let limit = query.limit.unwrap_or(50);
+    let total_pages = response.total_count.div_ceil(limit).max(1);
+
+    let on_prev = {
+        let query = query.clone();
+        Callback::from(move |_: MouseEvent| {
+            let mut new_query = (*query).clone();
+            new_query.page = Some(page.saturating_sub(1).max(1));
+            query.set(new_query);
+        })
+    };
+
+    let on_next = {
+        let query = query.clone();
+        Callback::from(move |_: MouseEvent| {
+            let mut new_query = (*query).clone();
+            new_query.page = Some((page + 1).min(total_pages));
+            query.set(new_query);
+        })
+    };
+
+    let selected_action = query.action.clone().unwrap_or_default();
+
+    html! {
+        <div class="p-4">
+            <div class="flex justify-between items-center mb-4">
+                <div>
+                    <h2 class="text-xl font-semibold text-gray-800 dark:text-gray-100">{"Audit log"}</h2>
+                    <p class="text-sm text-gray-500 dark:text-gray-400">
+                        {format!("{} entries", response.total_count)}
+                    </p>
+                </div>
+                <div class="flex items-center space-x-3">
+                    <input class={INPUT_CLASS} placeholder="Actor" value={query.actor.clone().unwrap_or_default()}
+                        onchange={on_actor_change} />
+                    <input class={INPUT_CLASS} placeholder="DAG" value={query.dag_id.clone().unwrap_or_default()}
+                        onchange={on_dag_change} />
+                    <select class={INPUT_CLASS} onchange={on_action_change}>
+                        <option value="" selected={selected_action.is_empty()}>{"All actions"}</option>
+                        {
+                            AUDIT_ACTIONS.iter().map(|action| html! {
+                                <option value={*action} selected={selected_action == *action}>{*action}</option>
+                            }).collect::<Html>()
+                        }
+                    </select>
+                </div>
+            </div>
+
+            {
+                if let Some(error_message) = &*error {
+                    html! {
+                        <div class="bg-red-50 dark:bg-red-900 border border-red-200 dark:border-red-800 text-red-700 dark:text-red-200 px-4 py-3 rounded mb-4">
+                            <p>{format!("Error loading the audit log: {}", error_message)}</p>
+                        </div>
+                    }
+                } else {
+                    html! {}
+                }
+            }
+
+            <div class="bg-white dark:bg-gray-900 rounded-lg shadow dark:shadow-gray-800">
+                <Table>
+                    <TableHead>
+                        <tr>
+                            <th class={TH_CLASS}>{"Time"}</th>
+                            <th class={TH_CLASS}>{"Actor"}</th>
+                            <th class={TH_CLASS}>{"Action"}</th>
+                            <th class={TH_CLASS}>{"Target"}</th>
+                            <th class={TH_CLASS}>{"Change"}</th>
+                            <th class={TH_CLASS}>{"Reason"}</th>
+                        </tr>
+                    </TableHead>
+                    <TableBody
+                        loading={*loading}
+                        empty={response.entries.is_empty()}
+                        col_span={6}
+                        no_data_message={"Nothing recorded yet".to_string()}
+                    >
+                        { for response.entries.iter().map(entry_row) }
+                    </TableBody>
+                </Table>
+            </div>
+
+            <div class="flex justify-end items-center space-x-3 mt-3 text-sm text-gray-600 dark:text-gray-300">
+                <button
+                    class="px-3 py-1 bg-gray-200 hover:bg-gray-300 rounded disabled:opacity-50"
+                    disabled={page <= 1}
+                    onclick={on_prev}
+                >
+                    {"Previous"}
+                </button>
+                <span>{format!("Page {} of {}", page, total_pages)}</span>
+                <button
+                    class="px-3 py-1 bg-gray-200 hover:bg-gray-300 rounded disabled:opacity-50"
+                    disabled={page >= total_pages}
+                    onclick={on_next}
+                >
+                    {"Next"}
+                </button>
+            </div>
+        </div>
+    }
+}
+
+fn entry_row(entry: &AuditEntry) -> Html {
+    let target = match &entry.run_id {
+        Some(run_id) => html! {
+            <a
+                href={format!("/dag/{}/graph?run_id={}", entry.dag_id, js_sys::encode_uri_component(run_id))}
+                class="text-blue-600 hover:text-blue-800"
+            >
+                {format!("{} / {}", entry.dag_id, run_id)}
+                {entry.task_id.as_ref().map(|task_id| format!(" / {}", task_id)).unwrap_or_default()}
+            </a>
+        },
+        None => html! {
+            <a href={format!("/dag/{}/graph", entry.dag_id)} class="text-blue-600 hover:text-blue-800">
+                {&entry.dag_id}
+            </a>
+        },
+    };
+
+    html! {
+        <tr class="text-sm text-gray-700 dark:text-gray-200 align-top">
+            <td class="px-4 py-2 whitespace-nowrap">{format_datetime(Some(entry.timestamp))}</td>
+            <td class="px-4 py-2">{&entry.actor}</td>
+            <td class="px-4 py-2 font-mono">{&entry.action}</td>
+            <td class="px-4 py-2 font-mono">{target}</td>
+            <td class="px-4 py-2 font-mono text-xs">
+                <div class="text-gray-500 dark:text-gray-400">{summarize(entry.before.as_ref())}</div>
+                <div>{summarize(entry.after.as_ref())}</div>
+            </td>
+            <td class="px-4 py-2">{entry.comment.as_deref().unwrap_or("-")}</td>
+        </tr>
+    }
+}
+
+/// A before or after state on one line: `key=value` pairs for objects, JSON otherwise
+fn summarize(state: Option<&Value>) -> String {
+    match state {
+        None => "-".to_string(),
+        Some(Value::Object(fields)) => fields
+            .iter()
+            .map(|(key, value)| match value {
+                Value::String(text) => format!("{}={}", key, text),
+                other => format!("{}={}", key, other),
+            })
+            .collect::<Vec<_>>()
+            .join(" "),
+        Some(other) => other.to_string(),
+    }
+}
@@ -1,4 +1,5 @@
 use crate::models::dag::DAG;
+use crate::models::user::AuthContext;
 use crate::utils::api::format_datetime;
 use web_sys::MouseEvent;
 use yew::prelude::*;
@@ -7,11 +8,15 @@ use yew::prelude::*;
 pub struct DagCardProps {
     pub dag: DAG,
     pub on_toggle_paused: Callback<(String, bool)>,
+    /// Opens the trigger dialog for this DAG
+    pub on_trigger: Callback<String>,
 }
 
 #[function_component(DagCard)]
 pub fn dag_card(props: &DagCardProps) -> Html {
     let dag = &props.dag;
+    let auth_ctx = use_context::<AuthContext>().expect("No auth context found");
+    let can_operate = auth_ctx.can_operate(&dag.owner, &dag.tags);
 
     let on_toggle_paused = {
         let on_toggle_paused = props.on_toggle_paused.clone();
@@ -24,6 +29,16 @@ pub fn dag_card(props: &DagCardProps) -> Html {
         })
     };
 
+    let on_trigger = {
+        let on_trigger = props.on_trigger.clone();
+        let dag_id = dag.dag_id.clone();
+
+        Callback::from(move |e: MouseEvent| {
+            e.prevent_default();
+            on_trigger.emit(dag_id.clone());
+        })
+    };
+
     let status_class = match dag.status() {
         "paused" => "status-paused",
         "running" => "status-running",
@@ -76,13 +91,18 @@ pub fn dag_card(props: &DagCardProps) -> Html {
                     </div>
                     <div class="flex space-x-2">
                         <button
-                            class="px-3 py-1 bg-gray-200 hover:bg-gray-300 rounded text-sm"
+                            class="px-3 py-1 bg-gray-200 hover:bg-gray-300 rounded text-sm disabled:opacity-50"
                             onclick={on_toggle_paused}
+                            disabled={!can_operate}
                         >
                             <i class={format!("fas {} mr-1", toggle_icon)}></i>
                             {toggle_text}
                         </button>
-                        <button class="px-3 py-1 bg-gray-200 hover:bg-gray-300 rounded text-sm">
+                        <button
+                            class="px-3 py-1 bg-gray-200 hover:bg-gray-300 rounded text-sm disabled:opacity-50"
+                            onclick={on_trigger}
+                            disabled={!can_operate}
+                        >
                             <i class="fas fa-play mr-1"></i>
                             {"Trigger"}
                         </button>
@@ -138,7 +158,7 @@ pub fn dag_card(props: &DagCardProps) -> Html {
                         <i class="fas fa-code mr-1"></i>
                         {"View Code"}
                     </a>
-                    <a href="#" class="text-sm text-blue-600 hover:text-blue-800">
+                    <a href={format!("/dag/{}/runs", dag.dag_id)} class="text-sm text-blue-600 hover:text-blue-800">
                         <i class="fas fa-history mr-1"></i>
                         {"View History"}
                     </a>
@@ -1,9 +1,15 @@
-use crate::models::dag::{DAGGraph as DAGGraphModel, TaskStatus};
+use crate::models::dag::{
+    ClearOptions, ClearResponse, DAG, DAGGraph as DAGGraphModel, DagRun, DagRunsQuery, DagRunsResponse, ServerEvent, Task,
+    TaskAttempt, TaskInstance, TaskStatus,
+};
+use crate::components::log_viewer::LogViewer;
+use crate::models::user::AuthContext;
 use crate::utils::api;
 use crate::utils::cytoscape::{
     Cytoscape, CytoscapeEdge, CytoscapeLayout, CytoscapeNode, CytoscapeStyle, 
-    NodeContextMenuEvent, highlight_connected_edges, reset_highlights
+    NodeContextMenuEvent, highlight_connected_edges, highlight_path, reset_highlights, set_node_data
 };
+use crate::utils::events::{self, EventSubscription};
 use wasm_bindgen::JsValue;
 use wasm_bindgen::JsCast;
 use wasm_bindgen_futures::spawn_local;
@@ -13,27 +19,67 @@ use yew::prelude::*;
 #[derive(Properties, PartialEq)]
 pub struct DAGGraphProps {
     pub dag_id: String,
+    /// Show the task states of this run instead of the latest graph
+    #[prop_or_default]
+    pub run_id: Option<String>,
 }
 
 pub enum Msg {
+    /// The DAG itself, whose owner and tags decide what the user may do here
+    DagLoaded(Result<DAG, String>),
     LoadGraph,
     GraphLoaded(Result<DAGGraphModel, String>),
+    RunLoaded(Result<DagRun, String>),
+    LoadRuns,
+    RunsLoaded(Result<DagRunsResponse, String>),
+    SelectRun(Option<String>),
+    CloseTaskDetails,
     ShowContextMenu(MouseEvent, String),  // event, task_id
     HideContextMenu,
     TaskAction(String, String),  // task_id, action
+    TaskActionDone(Result<DagRun, String>),
+    ToggleApplyUpstream,
+    ToggleApplyDownstream,
+    DismissActionError,
+    /// Preview clearing a task with the given options
+    PreviewClear(String, ClearOptions),
+    ClearPreviewLoaded(Result<ClearResponse, String>),
+    ConfirmClear,
+    CancelClear,
+    ClearDone(Result<ClearResponse, String>),
     HighlightConnections(String), // task_id
     ResetHighlights,
     StoreGraph(JsValue),
+    /// A change pushed by the server for this DAG
+    ServerEvent(ServerEvent),
 }
 
 pub struct DAGGraph {
+    dag: Option<DAG>,
+    /// Tasks with their current states, kept up to date from server events
     graph: Option<DAGGraphModel>,
+    /// The graph as last loaded, which the Cytoscape elements are built from. Live changes are
+    /// patched onto the existing nodes instead, so they do not re-run the layout.
+    rendered_graph: Option<DAGGraphModel>,
+    runs: Vec<DagRun>,
+    selected_run: Option<String>,
+    /// Task instances and attempt history of the selected run
+    run: Option<DagRun>,
+    details_task: Option<String>,
     loading: bool,
     error: Option<String>,
     context_menu_visible: bool,
     context_menu_position: (i32, i32),
     selected_task: Option<String>,
+    /// Apply status changes to the upstream / downstream tasks as well
+    apply_upstream: bool,
+    apply_downstream: bool,
+    /// Why the last status change was rejected
+    action_error: Option<String>,
+    /// Clear being previewed: the task, the options and the ids a dry run reported
+    pending_clear: Option<(String, ClearOptions, Vec<String>)>,
     cy_instance: Option<JsValue>,
+    events: Option<EventSubscription>,
 }
 
 impl Component for DAGGraph {
@@ -41,38 +87,81 @@ impl Component for DAGGraph {
     type Properties = DAGGraphProps;
 
     fn create(ctx: &Context<Self>) -> Self {
-        ctx.link().send_message(Msg::LoadGraph);
+        ctx.link().send_message_batch(vec![Msg::LoadGraph, Msg::LoadRuns]);
+        {
+            let dag_id = ctx.props().dag_id.clone();
+            let link = ctx.link().clone();
+            spawn_local(async move {
+                link.send_message(Msg::DagLoaded(api::fetch_dag(&dag_id).await));
+            });
+        }
         
         Self {
+            dag: None,
             graph: None,
+            rendered_graph: None,
+            runs: Vec::new(),
+            selected_run: ctx.props().run_id.clone(),
+            run: None,
+            details_task: None,
             loading: true,
             error: None,
             context_menu_visible: false,
             context_menu_position: (0, 0),
             selected_task: None,
+            apply_upstream: false,
+            apply_downstream: false,
+            action_error: None,
+            pending_clear: None,
             cy_instance: None,
+            events: Self::subscribe(ctx),
         }
     }
 
     fn update(&mut self, ctx: &Context<Self>, msg: Self::Message) -> bool {
         match msg {
+            Msg::DagLoaded(result) => {
+                match result {
+                    Ok(dag) => self.dag = Some(dag),
+                    Err(err) => log::warn!("Failed to load DAG: {}", err),
+                }
+                
+                true
+            }
             Msg::LoadGraph => {
                 self.loading = true;
+                self.error = None;
+                
+                if self.selected_run.is_none() {
+                    self.run = None;
+                }
                 
                 let dag_id = ctx.props().dag_id.clone();
+                let run_id = self.selected_run.clone();
                 let link = ctx.link().clone();
                 spawn_local(async move {
-                    let result = api::fetch_dag_graph(&dag_id).await;
-                    link.send_message(Msg::GraphLoaded(result));
+                    match run_id {
+                        Some(run_id) => {
+                            let result = api::fetch_dag_run_graph(&dag_id, &run_id).await;
+                            link.send_message(Msg::GraphLoaded(result));
+                            let run = api::fetch_dag_run(&dag_id, &run_id).await;
+                            link.send_message(Msg::RunLoaded(run));
+                        }
+                        None => {
+                            let result = api::fetch_dag_graph(&dag_id).await;
+                            link.send_message(Msg::GraphLoaded(result));
+                        }
+                    }
                 });
                 
-                false
+                true
             }
             Msg::GraphLoaded(result) => {
                 self.loading = false;
                 
                 match result {
                     Ok(graph) => {
+                        self.rendered_graph = Some(graph.clone());
                         self.graph = Some(graph);
                     }
                     Err(err) => {
@@ -82,6 +171,52 @@ impl Component for DAGGraph {
                 
                 true
             }
+            Msg::RunLoaded(result) => {
+                match result {
+                    Ok(run) if self.selected_run.as_ref() == Some(&run.run_id) => self.run = Some(run),
+                    Ok(_) => {}
+                    Err(err) => log::warn!("Failed to load run: {}", err),
+                }
+                
+                true
+            }
+            Msg::CloseTaskDetails => {
+                self.details_task = None;
+                
+                true
+            }
+            Msg::LoadRuns => {
+                let dag_id = ctx.props().dag_id.clone();
+                let link = ctx.link().clone();
+                spawn_local(async move {
+                    let query = DagRunsQuery {
+                        page: Some(1),
+                        limit: Some(50),
+                        state: None,
+                    };
+                    let result = api::fetch_dag_runs(&dag_id, &query).await;
+                    link.send_message(Msg::RunsLoaded(result));
+                });
+                
+                false
+            }
+            Msg::RunsLoaded(result) => {
+                match result {
+                    Ok(response) => self.runs = response.runs,
+                    Err(err) => log::warn!("Failed to load runs: {}", err),
+                }
+                
+                true
+            }
+            Msg::SelectRun(run_id) => {
+                if self.selected_run == run_id {
+                    return false;
+                }
+                self.selected_run = run_id;
+                ctx.link().send_message(Msg::LoadGraph);
+                
+                false
+            }
             Msg::ShowContextMenu(event, task_id) => {
                 event.prevent_default();  // Prevent default context menu
                 
@@ -104,56 +239,150 @@ impl Component for DAGGraph {
             Msg::TaskAction(task_id, action) => {
                 self.context_menu_visible = false;
                 
-                let dag_id = ctx.props().dag_id.clone();
-                let task_id_clone = task_id.clone();
-                
                 // Handle task actions
-                match action.as_str() {
+                let status = match action.as_str() {
                     "view" => {
-                        log::info!("View task details: {}", task_id);
-                        // In a real app, you'd navigate to the task details page
+                        self.details_task = Some(task_id);
+                        return true;
                     }
-                    "force-success" => {
-                        // Update task status
-                        let link = ctx.link().clone();
-                        spawn_local(async move {
-                            let _ = api::update_task_status(&dag_id, &task_id_clone, "SUCCEEDED").await;
-                            link.send_message(Msg::LoadGraph);
-                        });
+                    "clear" => {
+                        ctx.link().send_message(Msg::PreviewClear(task_id, ClearOptions::default()));
+                        return true;
                     }
-                    "retry" => {
-                        let link = ctx.link().clone();
-                        spawn_local(async move {
-                            let _ = api::update_task_status(&dag_id, &task_id_clone, "RUNNING").await;
-                            link.send_message(Msg::LoadGraph);
-                        });
+                    "force-success" => "SUCCEEDED",
+                    "retry" => "QUEUED",
+                    "skip" => "SKIPPED",
+                    "pause" => "PAUSED",
+                    "play" => "PENDING",
+                    _ => return true,
+                };
+                let Some(run_id) = self.selected_run.clone() else {
+                    return true;
+                };
+                let verb = match action.as_str() {
+                    "force-success" => "force success on",
+                    "play" => "resume",
+                    other => other,
+                };
+                let Some(comment) = prompt_reason(&format!("{} {}", verb, task_id)) else {
+                    return true;
+                };
+                
+                let dag_id = ctx.props().dag_id.clone();
+                let (upstream, downstream) = (self.apply_upstream, self.apply_downstream);
+                let link = ctx.link().clone();
+                spawn_local(async move {
+                    let result = api::update_task_status(
+                        &dag_id,
+                        &run_id,
+                        &task_id,
+                        status,
+                        upstream,
+                        downstream,
+                        comment.as_deref(),
+                    )
+                    .await;
+                    link.send_message(Msg::TaskActionDone(result));
+                });
+                
+                true
+            }
+            Msg::TaskActionDone(result) => {
+                match result {
+                    Ok(run) => {
+                        self.action_error = None;
+                        self.apply_run(run);
                     }
-                    "skip" => {
-                        let link = ctx.link().clone();
-                        spawn_local(async move {
-                            let _ = api::update_task_status(&dag_id, &task_id_clone, "SKIPPED").await;
-                            link.send_message(Msg::LoadGraph);
-                        });
+                    Err(err) => self.action_error = Some(err),
+                }
+                
+                true
+            }
+            Msg::PreviewClear(task_id, options) => {
+                let Some(run_id) = self.selected_run.clone() else {
+                    return false;
+                };
+                self.pending_clear = Some((task_id.clone(), options.clone(), Vec::new()));
+                
+                let dag_id = ctx.props().dag_id.clone();
+                let link = ctx.link().clone();
+                spawn_local(async move {
+                    let options = ClearOptions { dry_run: true, ..options };
+                    let result = api::clear_task(&dag_id, &run_id, &task_id, &options).await;
+                    link.send_message(Msg::ClearPreviewLoaded(result));
+                });
+                
+                true
+            }
+            Msg::ClearPreviewLoaded(result) => {
+                match result {
+                    Ok(response) => {
+                        if let Some((_, _, preview)) = &mut self.pending_clear {
+                            *preview = response.task_ids;
+                            self.highlight_clear_set();
+                        }
                     }
-                    "pause" => {
-                        let link = ctx.link().clone();
-                        spawn_local(async move {
-                            let _ = api::update_task_status(&dag_id, &task_id_clone, "PAUSED").await;
-                            link.send_message(Msg::LoadGraph);
-                        });
+                    Err(err) => {
+                        self.pending_clear = None;
+                        self.action_error = Some(err);
                     }
-                    "play" => {
-                        let link = ctx.link().clone();
-                        spawn_local(async move {
-                            let _ = api::update_task_status(&dag_id, &task_id_clone, "RUNNING").await;
-                            link.send_message(Msg::LoadGraph);
-                        });
+                }
+                
+                true
+            }
+            Msg::ConfirmClear => {
+                let (Some((task_id, options, _)), Some(run_id)) = (self.pending_clear.clone(), self.selected_run.clone()) else {
+                    return false;
+                };
+                let Some(comment) = prompt_reason(&format!("clear {}", task_id)) else {
+                    return false;
+                };
+                
+                let dag_id = ctx.props().dag_id.clone();
+                let link = ctx.link().clone();
+                spawn_local(async move {
+                    let options = ClearOptions { dry_run: false, comment, ..options };
+                    let result = api::clear_task(&dag_id, &run_id, &task_id, &options).await;
+                    link.send_message(Msg::ClearDone(result));
+                });
+                
+                false
+            }
+            Msg::CancelClear => {
+                self.pending_clear = None;
+                if let Some(cy) = &self.cy_instance {
+                    let _ = reset_highlights(cy);
+                }
+                
+                true
+            }
+            Msg::ClearDone(result) => {
+                self.pending_clear = None;
+                if let Some(cy) = &self.cy_instance {
+                    let _ = reset_highlights(cy);
+                }
+                match result {
+                    Ok(response) => {
+                        self.action_error = None;
+                        self.apply_run(response.run);
                     }
-                    _ => {}
+                    Err(err) => self.action_error = Some(err),
                 }
                 
                 true
             }
+            Msg::ToggleApplyUpstream => {
+                self.apply_upstream = !self.apply_upstream;
+                true
+            }
+            Msg::ToggleApplyDownstream => {
+                self.apply_downstream = !self.apply_downstream;
+                true
+            }
+            Msg::DismissActionError => {
+                self.action_error = None;
+                true
+            }
             Msg::HighlightConnections(task_id) => {
                 if let Some(cy) = &self.cy_instance {
                     let _ = highlight_connected_edges(cy, &task_id, "#ff0000");
@@ -170,7 +399,23 @@ impl Component for DAGGraph {
                 self.cy_instance = Some(cy);
                 false
             }
+            Msg::ServerEvent(event) => self.apply_event(ctx, event),
+        }
+    }
+
+    fn changed(&mut self, ctx: &Context<Self>, old_props: &Self::Properties) -> bool {
+        let props = ctx.props();
+        if props.dag_id != old_props.dag_id {
+            self.graph = None;
+            self.rendered_graph = None;
+            self.runs.clear();
+            self.events = Self::subscribe(ctx);
+            self.selected_run = props.run_id.clone();
+            ctx.link().send_message_batch(vec![Msg::LoadGraph, Msg::LoadRuns]);
+        } else if props.run_id != old_props.run_id {
+            ctx.link().send_message(Msg::SelectRun(props.run_id.clone()));
         }
+        false
     }
 
     fn view(&self, ctx: &Context<Self>) -> Html {
@@ -202,11 +447,15 @@ impl Component for DAGGraph {
                                                 }
                                             }
                                         </div>
+                                        { self.render_run_picker(ctx) }
                                         <div class="legend-panel">
                                             { self.render_legend() }
                                         </div>
                                 </div>
+                                { self.render_action_error(ctx) }
+                                { self.render_clear_panel(ctx) }
                                 { self.render_graph(ctx) }
+                                { self.render_task_details(ctx) }
                                 { self.render_context_menu(ctx) }
                                 
                                 // Invisible overlay to catch clicks outside the context menu
@@ -233,6 +482,14 @@ impl Component for DAGGraph {
 
 impl DAGGraph {
     // Helper function for handling mouse events
+    /// Whether the signed-in user may change task states of this DAG
+    fn can_operate(&self, ctx: &Context<Self>) -> bool {
+        match (&self.dag, ctx.link().context::<AuthContext>(Callback::noop())) {
+            (Some(dag), Some((auth_ctx, _))) => auth_ctx.can_operate(&dag.owner, &dag.tags),
+            _ => false,
+        }
+    }
+
     fn create_mouse_hover_callback(bg_color: &'static str) -> Callback<MouseEvent> {
         Callback::from(move |e: MouseEvent| {
             if let Some(target) = e.target() {
@@ -245,14 +502,15 @@ impl DAGGraph {
     }
     
     fn render_graph(&self, ctx: &Context<Self>) -> Html {
-        if let Some(graph) = &self.graph {
+        if let Some(graph) = &self.rendered_graph {
             // Create nodes for Cytoscape
             let mut nodes = Vec::new();
             let mut edges = Vec::new();
             
             // Add nodes
             for task in &graph.tasks {
-                let node = CytoscapeNode::new(&task.id, &task.name)
+                let label = self.node_label(task);
+                let node = CytoscapeNode::new(&task.id, &label)
                     .with_data("status", format!("{:?}", task.status))
                     .with_data("taskId", task.id.clone());
                 
@@ -295,7 +553,8 @@ impl DAGGraph {
                 TaskStatus::PENDING,
                 TaskStatus::QUEUED,
                 TaskStatus::SKIPPED,
-                TaskStatus::PAUSED
+                TaskStatus::PAUSED,
+                TaskStatus::UP_FOR_RETRY
             ].iter() {
                 let status_selector = format!("node[status = '{:?}']", status);
                 let status_style = CytoscapeStyle::new(&status_selector)
@@ -410,6 +669,153 @@ impl DAGGraph {
         }
     }
     
+    /// On a run's graph, show how long each task took and which attempt it is on
+    fn node_label(&self, task: &Task) -> String {
+        if self.selected_run.is_none() {
+            return task.name.clone();
+        }
+        let mut details = Vec::new();
+        if let Some(duration) = task.duration {
+            details.push(format!("{:.1}s", duration));
+        }
+        if task.try_number > 1 {
+            details.push(format!("try {}", task.try_number));
+        }
+        if details.is_empty() {
+            task.name.clone()
+        } else {
+            format!("{}\n{}", task.name, details.join(" · "))
+        }
+    }
+
+    /// Listen for changes to this DAG's runs and tasks
+    fn subscribe(ctx: &Context<Self>) -> Option<EventSubscription> {
+        let on_event = ctx.link().callback(Msg::ServerEvent);
+        match events::subscribe(Some(&ctx.props().dag_id), on_event) {
+            Ok(subscription) => Some(subscription),
+            Err(err) => {
+                log::warn!("Live updates unavailable: {}", err);
+                None
+            }
+        }
+    }
+
+    /// Apply a server event to the runs and the selected run's tasks. Returns whether to
+    /// re-render.
+    fn apply_event(&mut self, ctx: &Context<Self>, event: ServerEvent) -> bool {
+        match event {
+            ServerEvent::RunCreated { run } => {
+                if self.runs.iter().any(|known| known.run_id == run.run_id) {
+                    return false;
+                }
+                self.runs.insert(0, run);
+                true
+            }
+            ServerEvent::RunStateChanged { run_id, state, .. } => {
+                for run in self.runs.iter_mut().filter(|run| run.run_id == run_id) {
+                    run.state = state;
+                }
+                if let Some(run) = self.run.as_mut().filter(|run| run.run_id == run_id) {
+                    run.state = state;
+                }
+                true
+            }
+            ServerEvent::TaskStateChanged { run_id, task, .. } => {
+                if self.selected_run.as_ref() != Some(&run_id) {
+                    return false;
+                }
+                self.apply_task_instance(&task);
+                true
+            }
+            ServerEvent::Resync { .. } => {
+                ctx.link().send_message_batch(vec![Msg::LoadGraph, Msg::LoadRuns]);
+                false
+            }
+            // The log viewer tails its own log
+            ServerEvent::DagPaused { .. } | ServerEvent::LogAppended { .. } => false,
+        }
+    }
+
+    /// Take over the task states of the selected run as returned by an action
+    fn apply_run(&mut self, run: DagRun) {
+        if self.selected_run.as_ref() != Some(&run.run_id) {
+            return;
+        }
+        for instance in &run.task_instances {
+            self.apply_task_instance(instance);
+        }
+        for known in self.runs.iter_mut().filter(|known| known.run_id == run.run_id) {
+            known.state = run.state;
+        }
+        self.run = Some(run);
+    }
+
+    /// Record a task instance's new state and recolour its node in place
+    fn apply_task_instance(&mut self, instance: &TaskInstance) {
+        if let Some(ti) = self
+            .run
+            .as_mut()
+            .and_then(|run| run.task_instances.iter_mut().find(|ti| ti.task_id == instance.task_id))
+        {
+            *ti = instance.clone();
+        }
+        let Some(task) = self
+            .graph
+            .as_mut()
+            .and_then(|graph| graph.tasks.iter_mut().find(|task| task.id == instance.task_id))
+        else {
+            return;
+        };
+        task.apply_instance(instance);
+
+        let task = task.clone();
+        if let Some(cy) = &self.cy_instance {
+            let status = JsValue::from_str(&format!("{:?}", task.status));
+            let label = JsValue::from_str(&self.node_label(&task));
+            let _ = set_node_data(cy, &task.id, "status", &status);
+            let _ = set_node_data(cy, &task.id, "label", &label);
+        }
+    }
+
+    fn render_run_picker(&self, ctx: &Context<Self>) -> Html {
+        let on_change = ctx.link().callback(|e: Event| {
+            let select: web_sys::HtmlSelectElement = e.target_unchecked_into();
+            let value = select.value();
+            Msg::SelectRun(if value.is_empty() { None } else { Some(value) })
+        });
+        let selected = self.selected_run.clone().unwrap_or_default();
+
+        // A run linked directly may be older than the ones listed
+        let selected_missing = !selected.is_empty() && !self.runs.iter().any(|run| run.run_id == selected);
+
+        html! {
+            <div class="run-picker-panel">
+                <select class="run-picker" onchange={on_change}>
+                    <option value="" selected={selected.is_empty()}>{ "Latest graph (no run)" }</option>
+                    {
+                        if selected_missing {
+                            html! { <option value={selected.clone()} selected=true>{ &selected }</option> }
+                        } else {
+                            html! {}
+                        }
+                    }
+                    {
+                        self.runs.iter().map(|run| {
+                            let label = format!(
+                                "{} · {}",
+                                run.logical_date.format("%Y-%m-%d %H:%M"),
+                                run.state.label()
+                            );
+                            html! {
+                                <option value={run.run_id.clone()} selected={run.run_id == selected}>{ label }</option>
+                            }
+                        }).collect::<Html>()
+                    }
+                </select>
+            </div>
+        }
+    }
+
     fn render_legend(&self) -> Html {
         html! {
             <ul class="legend-items-horizontal">
@@ -441,10 +847,162 @@ impl DAGGraph {
                     <span class="legend-color" style="background-color: #795548;"></span>
                     <span class="legend-label">{ "Paused" }</span>
                 </li>
+                <li>
+                    <span class="legend-color" style="background-color: #ffc107;"></span>
+                    <span class="legend-label">{ "Up for retry" }</span>
+                </li>
             </ul>
         }
     }
     
+    /// Finished attempts of a task in the selected run, oldest first
+    fn attempts(&self, task_id: &str) -> &[TaskAttempt] {
+        self.run
+            .as_ref()
+            .and_then(|run| run.task_instances.iter().find(|ti| ti.task_id == task_id))
+            .map(|ti| ti.attempts.as_slice())
+            .unwrap_or(&[])
+    }
+
+    fn attempt_summary(attempt: &TaskAttempt) -> String {
+        let mut parts = vec![
+            format!("Try {}", attempt.try_number),
+            attempt.status.label().to_string(),
+        ];
+        if let Some(duration) = attempt.duration {
+            parts.push(format!("{:.1}s", duration));
+        }
+        if let Some(code) = attempt.exit_code {
+            parts.push(format!("exit {}", code));
+        }
+        if let Some(error) = &attempt.error {
+            parts.push(error.clone());
+        }
+        parts.join(" · ")
+    }
+
+    fn render_attempts_menu(&self, task_id: &str) -> Html {
+        let attempts = self.attempts(task_id);
+        if attempts.is_empty() {
+            return html! {};
+        }
+
+        html! {
+            <>
+                <div class="context-menu-divider" style="height: 1px; background-color: #e0e0e0; margin: 4px 0;"></div>
+                <div style="padding: 4px 16px; font-size: 0.75rem; color: #666; text-transform: uppercase;">{ "Attempts" }</div>
+                {
+                    attempts.iter().rev().take(5).map(|attempt| html! {
+                        <div style="padding: 2px 16px; font-size: 0.8rem; color: #333; white-space: nowrap;">
+                            <span class="legend-color" style={format!("background-color: {};", attempt.status.color())}></span>
+                            { Self::attempt_summary(attempt) }
+                        </div>
+                    }).collect::<Html>()
+                }
+            </>
+        }
+    }
+
+    fn render_task_details(&self, ctx: &Context<Self>) -> Html {
+        let Some(task_id) = &self.details_task else {
+            return html! {};
+        };
+        let Some(task) = self.graph.as_ref().and_then(|g| g.tasks.iter().find(|t| t.id == *task_id)) else {
+            return html! {};
+        };
+        let instance = self
+            .run
+            .as_ref()
+            .and_then(|run| run.task_instances.iter().find(|ti| ti.task_id == *task_id));
+        let close = ctx.link().callback(|_: MouseEvent| Msg::CloseTaskDetails);
+
+        html! {
+            <div class="task-details-panel">
+                <div class="task-details-header">
+                    <span>{ format!("Task: {}", task.name) }</span>
+                    <button class="task-details-close" onclick={close} title="Close">
+                        <i class="fas fa-times"></i>
+                    </button>
+                </div>
+                <table class="task-details-table">
+                    <tr><th>{ "Task ID" }</th><td>{ &task.id }</td></tr>
+                    <tr><th>{ "Operator" }</th><td>{ &task.operator }</td></tr>
+                    <tr>
+                        <th>{ "Status" }</th>
+                        <td>
+                            <span class="legend-color" style={format!("background-color: {};", task.status.color())}></span>
+                            { task.status.label() }
+                        </td>
+                    </tr>
+                    <tr><th>{ "Try" }</th><td>{ format!("{} of {}", task.try_number, task.max_retries + 1) }</td></tr>
+                    {
+                        match instance.and_then(|ti| ti.next_retry_at) {
+                            Some(at) => html! {
+                                <tr><th>{ "Next retry" }</th><td>{ api::format_datetime(Some(at)) }</td></tr>
+                            },
+                            None => html! {},
+                        }
+                    }
+                </table>
+                {
+                    if self.selected_run.is_none() {
+                        html! { <p class="task-details-note">{ "Pick a run to see this task's attempts." }</p> }
+                    } else if self.attempts(task_id).is_empty() {
+                        html! { <p class="task-details-note">{ "No finished attempts in this run." }</p> }
+                    } else {
+                        html! {
+                            <table class="task-details-table">
+                                <thead>
+                                    <tr>
+                                        <th>{ "Try" }</th>
+                                        <th>{ "Status" }</th>
+                                        <th>{ "Started" }</th>
+                                        <th>{ "Ended" }</th>
+                                        <th>{ "Duration" }</th>
+                                        <th>{ "Exit code" }</th>
+                                        <th>{ "Error" }</th>
+                                    </tr>
+                                </thead>
+                                <tbody>
+                                {
+                                    self.attempts(task_id).iter().map(|attempt| html! {
+                                        <tr>
+                                            <td>{ attempt.try_number }</td>
+                                            <td>
+                                                <span class="legend-color" style={format!("background-color: {};", attempt.status.color())}></span>
+                                                { attempt.status.label() }
+                                            </td>
+                                            <td>{ api::format_datetime(attempt.start_time) }</td>
+                                            <td>{ api::format_datetime(attempt.end_time) }</td>
+                                            <td>{ attempt.duration.map(|d| format!("{:.1}s", d)).unwrap_or_default() }</td>
+                                            <td>{ attempt.exit_code.map(|c| c.to_string()).unwrap_or_default() }</td>
+                                            <td>{ attempt.error.clone().unwrap_or_default() }</td>
+                                        </tr>
+                                    }).collect::<Html>()
+                                }
+                                </tbody>
+                            </table>
+                        }
+                    }
+                }
+                {
+                    match (&self.selected_run, instance) {
+                        (Some(run_id), Some(ti)) if ti.try_number > 0 => html! {
+                            <LogViewer
+                                dag_id={ctx.props().dag_id.clone()}
+                                run_id={run_id.clone()}
+                                task_id={ti.task_id.clone()}
+                                latest_try={ti.try_number}
+                                running={ti.status == TaskStatus::RUNNING}
+                            />
+                        },
+                        _ => html! {},
+                    }
+                }
+            </div>
+        }
+    }
+
     fn render_context_menu(&self, ctx: &Context<Self>) -> Html {
         if !self.context_menu_visible || self.selected_task.is_none() {
             return html! {};
@@ -491,14 +1049,27 @@ impl DAGGraph {
                     { "View Task Details" }
                 </div>
                 
+                { self.render_attempts_menu(task_id) }
+                
                 {
-                    if let Some(task) = task {
+                    // Task states only exist within a run, and only operators may change them
+                    if let (Some(task), Some(_), true) = (task, &self.selected_run, self.can_operate(ctx)) {
                         html! {
                             <>
                                 <div class="context-menu-divider" style="height: 1px; background-color: #e0e0e0; margin: 4px 0;"></div>
                                 
+                                <div 
+                                    class="context-menu-item"
+                                    style={menu_item_style}
+                                    onmouseover={Self::create_mouse_hover_callback("#f5f5f5")}
+                                    onmouseout={Self::create_mouse_hover_callback("")}
+                                    onclick={on_action("clear")}
+                                >
+                                    { "Clear..." }
+                                </div>
+                                
                                 {
-                                    if task.status == TaskStatus::FAILED {
+                                    if matches!(task.status, TaskStatus::FAILED | TaskStatus::UP_FOR_RETRY) {
                                         html! {
                                             <>
                                                 <div 
@@ -545,7 +1116,7 @@ impl DAGGraph {
                                 }
                                 
                                 {
-                                    if task.status == TaskStatus::RUNNING {
+                                    if task.status == TaskStatus::PENDING || task.status == TaskStatus::QUEUED {
                                         html! {
                                             <div 
                                                 class="context-menu-item"
@@ -573,6 +1144,26 @@ impl DAGGraph {
                                         html! {}
                                     }
                                 }
+                                
+                                <div class="context-menu-divider" style="height: 1px; background-color: #e0e0e0; margin: 4px 0;"></div>
+                                <label class="context-menu-item" style={menu_item_style}>
+                                    <input
+                                        type="checkbox"
+                                        class="mr-2"
+                                        checked={self.apply_upstream}
+                                        onchange={ctx.link().callback(|_: Event| Msg::ToggleApplyUpstream)}
+                                    />
+                                    { "Include upstream" }
+                                </label>
+                                <label class="context-menu-item" style={menu_item_style}>
+                                    <input
+                                        type="checkbox"
+                                        class="mr-2"
+                                        checked={self.apply_downstream}
+                                        onchange={ctx.link().callback(|_: Event| Msg::ToggleApplyDownstream)}
+                                    />
+                                    { "Include downstream" }
+                                </label>
                             </>
                         }
                     } else {
@@ -582,4 +1173,105 @@ impl DAGGraph {
             </div>
         }
     }
-}
\ No newline at end of file
+
+    /// Outline the tasks a pending clear would reset, and the edges between them
+    fn highlight_clear_set(&self) {
+        let (Some(cy), Some(graph), Some((_, _, task_ids))) = (&self.cy_instance, &self.graph, &self.pending_clear) else {
+            return;
+        };
+        // Consecutive ids are highlighted as edges, so list each edge inside the set as a pair;
+        // any other consecutive pair that happens to be an edge is inside the set as well
+        let mut path: Vec<String> = graph
+            .edges
+            .iter()
+            .filter(|edge| task_ids.contains(&edge.source) && task_ids.contains(&edge.target))
+            .flat_map(|edge| [edge.source.clone(), edge.target.clone()])
+            .collect();
+        path.extend(task_ids.iter().filter(|id| !path.contains(id)).cloned().collect::<Vec<_>>());
+        let _ = highlight_path(cy, &path, "#ff9800");
+    }
+
+    /// Confirmation bar for a pending clear, with its scope options and the affected tasks
+    fn render_clear_panel(&self, ctx: &Context<Self>) -> Html {
+        let Some((task_id, options, task_ids)) = &self.pending_clear else {
+            return html! {};
+        };
+
+        let toggle = |label: &'static str, checked: bool, apply: fn(&mut ClearOptions)| {
+            let task_id = task_id.clone();
+            let mut next = options.clone();
+            apply(&mut next);
+            let onchange = ctx.link().callback(move |_: Event| Msg::PreviewClear(task_id.clone(), next.clone()));
+            html! {
+                <label class="mr-3">
+                    <input type="checkbox" class="mr-1" {checked} {onchange} />
+                    { label }
+                </label>
+            }
+        };
+        let confirm = ctx.link().callback(|_: MouseEvent| Msg::ConfirmClear);
+        let cancel = ctx.link().callback(|_: MouseEvent| Msg::CancelClear);
+
+        html! {
+            <div class="task-details-panel clear-panel">
+                <div class="task-details-header">
+                    <span>{ format!("Clear {}", task_id) }</span>
+                </div>
+                <div class="mb-2">
+                    { toggle("Upstream", options.upstream, |o| o.upstream = !o.upstream) }
+                    { toggle("Downstream", options.downstream, |o| o.downstream = !o.downstream) }
+                    { toggle("Recursive", options.recursive, |o| o.recursive = !o.recursive) }
+                    { toggle("Failed only", options.failed_only, |o| o.failed_only = !o.failed_only) }
+                </div>
+                <p class="mb-2">
+                    {
+                        if task_ids.is_empty() {
+                            "Working out which tasks would be cleared...".to_string()
+                        } else {
+                            format!("{} task(s) will be reset to pending: {}", task_ids.len(), task_ids.join(", "))
+                        }
+                    }
+                </p>
+                <div class="flex justify-end space-x-2">
+                    <button class="px-3 py-1 bg-gray-200 hover:bg-gray-300 rounded text-sm" onclick={cancel}>
+                        { "Cancel" }
+                    </button>
+                    <button
+                        class="btn-airflow px-3 py-1 rounded text-sm text-white"
+                        onclick={confirm}
+                        disabled={task_ids.is_empty()}
+                    >
+                        { "Clear" }
+                    </button>
+                </div>
+            </div>
+        }
+    }
+
+    /// Reason the server gave for rejecting the last status change
+    fn render_action_error(&self, ctx: &Context<Self>) -> Html {
+        let Some(error) = &self.action_error else {
+            return html! {};
+        };
+        let dismiss = ctx.link().callback(|_: MouseEvent| Msg::DismissActionError);
+
+        html! {
+            <div class="bg-red-50 border border-red-200 text-red-700 px-4 py-2 rounded mb-2 flex justify-between items-center">
+                <span>{ error }</span>
+                <button class="task-details-close" onclick={dismiss} title="Dismiss">
+                    <i class="fas fa-times"></i>
+                </button>
+            </div>
+        }
+    }
+}
+
+/// Ask for an optional reason to record in the audit log. `None` when the user cancels, so the
+/// action is dropped; an empty answer goes ahead without a reason.
+fn prompt_reason(action: &str) -> Option<Option<String>> {
+    gloo::utils::window()
+        .prompt_with_message(&format!("Reason to {} (optional)", action))
+        .ok()
+        .flatten()
+        .map(|reason| Some(reason.trim().to_string()).filter(|reason| !reason.is_empty()))
+}
@@ -1,54 +1,132 @@
 use crate::components::search_filter::SearchFilter;
-use crate::models::dag::{DAG, DAGsQuery, DAGsResponse};
-use crate::utils::aggrid::{ColumnDef, GridPaginationEvent, SortDirection};
+use crate::components::trigger_dialog::TriggerDialog;
+use crate::models::dag::{DAG, DAGsQuery, DAGsResponse, DagRun, ServerEvent};
+use crate::models::search::QueryError;
+use crate::models::user::AuthContext;
+use crate::utils::aggrid::{ColumnDef, GridSortEvent, SortDirection};
 use crate::utils::aggrid::AgGrid;
 use crate::utils::api::{fetch_dags, toggle_dag_paused};
+use crate::utils::events;
 use serde_wasm_bindgen::to_value;
 use wasm_bindgen::prelude::*;
 use wasm_bindgen::JsValue;
+use wasm_bindgen_futures::spawn_local;
 use js_sys::Object;
+use std::rc::Rc;
 use yew::prelude::*;
 use yew_hooks::prelude::*;
 
+/// The page of DAGs shown, kept current from server events between fetches
+struct DagsState {
+    response: DAGsResponse,
+    /// Bumped when events were missed, to fetch the page again
+    resyncs: usize,
+}
+
+enum DagsAction {
+    Loaded(DAGsResponse),
+    Updated(DAG),
+    Event(ServerEvent),
+}
+
+impl Reducible for DagsState {
+    type Action = DagsAction;
+
+    fn reduce(self: Rc<Self>, action: Self::Action) -> Rc<Self> {
+        let mut response = self.response.clone();
+        let mut resyncs = self.resyncs;
+        match action {
+            DagsAction::Loaded(loaded) => response = loaded,
+            DagsAction::Updated(updated) => {
+                if let Some(dag) = find_dag(&mut response.dags, &updated.dag_id) {
+                    *dag = updated;
+                }
+            }
+            DagsAction::Event(ServerEvent::DagPaused { dag_id, paused }) => {
+                if let Some(dag) = find_dag(&mut response.dags, &dag_id) {
+                    dag.paused = paused;
+                }
+            }
+            DagsAction::Event(ServerEvent::RunCreated { run }) => {
+                if let Some(dag) = find_dag(&mut response.dags, &run.dag_id) {
+                    dag.count_run(None, run.state);
+                    if dag.last_run.is_none_or(|last| run.logical_date > last) {
+                        dag.last_run = Some(run.logical_date);
+                    }
+                }
+            }
+            DagsAction::Event(ServerEvent::RunStateChanged { dag_id, previous, state, .. }) => {
+                if let Some(dag) = find_dag(&mut response.dags, &dag_id) {
+                    dag.count_run(Some(previous), state);
+                }
+            }
+            DagsAction::Event(ServerEvent::Resync { .. }) => resyncs += 1,
+            // Task and log changes do not show in the list
+            DagsAction::Event(_) => return self,
+        }
+        Rc::new(DagsState { response, resyncs })
+    }
+}
+
+fn find_dag<'a>(dags: &'a mut [DAG], dag_id: &str) -> Option<&'a mut DAG> {
+    dags.iter_mut().find(|dag| dag.dag_id == dag_id)
+}
+
+/// Order of the list until a column header is clicked
+const DEFAULT_SORT: &str = "dag_id:asc";
+
 #[function_component(DagList)]
 pub fn dag_list() -> Html {
+    let auth_ctx = use_context::<AuthContext>().expect("No auth context found");
     let query = use_state(|| DAGsQuery {
         page: Some(1),
         limit: Some(10),
-        sort_by: Some("dag_id".to_string()),
-        sort_order: Some("asc".to_string()),
+        sort_by: Some(DEFAULT_SORT.to_string()),
         ..Default::default()
     });
 
-    let dags_response = use_state(|| DAGsResponse {
-        dags: vec![],
-        total_count: 0,
+    let dags = use_reducer(|| DagsState {
+        response: DAGsResponse {
+            dags: vec![],
+            total_count: 0,
+            next_cursor: None,
+        },
+        resyncs: 0,
     });
 
     let loading = use_state(|| true);
     let error = use_state(|| None::<String>);
+    // Where the search query is malformed, shown in the search box rather than as an error
+    let query_error = use_state(|| None::<QueryError>);
     let grid_api = use_state(|| None::<JsValue>);
+    // DAG whose trigger dialog is open
+    let trigger_dag_id = use_state(|| None::<String>);
 
     // Async fetch for DAGs
     let fetch_data = {
         let query = query.clone();
-        let dags_response = dags_response.clone();
+        let dags = dags.clone();
         let loading = loading.clone();
         let error = error.clone();
+        let query_error = query_error.clone();
         use_async(async move {
             loading.set(true);
             error.set(None);
 
             match fetch_dags(&query).await {
                 Ok(response) => {
-                    dags_response.set(response);
+                    dags.dispatch(DagsAction::Loaded(response));
+                    query_error.set(None);
                     loading.set(false);
                     Ok(())
                 }
                 Err(err) => {
-                    error.set(Some(err.clone()));
+                    match err.query_error {
+                        Some(located) => query_error.set(Some(located)),
+                        None => error.set(Some(err.error.clone())),
+                    }
                     loading.set(false);
-                    Err(err)
+                    Err(err.error)
                 }
             }
         })
@@ -63,19 +141,36 @@ pub fn dag_list() -> Html {
         });
     }
 
-    // Refetch data when the query changes
+    // Refetch data when the query changes, or when server events were missed
     {
         let fetch_data = fetch_data.clone();
-        let query_deps = (*query).clone();
+        let query_deps = ((*query).clone(), dags.resyncs);
         use_effect_with(query_deps, move |_| {
             fetch_data.run();
             || {}
         });
     }
 
+    // Keep paused flags and run counters current without refetching
+    {
+        let dags = dags.clone();
+        use_effect_with((), move |_| {
+            let on_event = Callback::from(move |event: ServerEvent| dags.dispatch(DagsAction::Event(event)));
+            let subscription = match events::subscribe(None, on_event) {
+                Ok(subscription) => Some(subscription),
+                Err(err) => {
+                    log::warn!("Live updates unavailable: {}", err);
+                    None
+                }
+            };
+            move || drop(subscription)
+        });
+    }
+
     let on_search = {
         let query = query.clone();
-        Callback::from(move |new_query: DAGsQuery| {
+        Callback::from(move |mut new_query: DAGsQuery| {
+            new_query.page = Some(1);
             query.set(new_query);
         })
     };
@@ -92,48 +187,83 @@ pub fn dag_list() -> Html {
     };
 
     let on_toggle_paused = {
-        let dags_response = dags_response.clone();
-        let fetch_data = fetch_data.clone();
+        let dags = dags.clone();
+        let error = error.clone();
         Callback::from(move |(dag_id, paused): (String, bool)| {
-            let _ = toggle_dag_paused(&dag_id, paused);
-            let mut response = (*dags_response).clone();
-            response.dags.iter_mut().for_each(|dag| {
-                if dag.dag_id == dag_id {
-                    dag.paused = paused;
+            let dags = dags.clone();
+            let error = error.clone();
+            spawn_local(async move {
+                match toggle_dag_paused(&dag_id, paused).await {
+                    Ok(updated) => dags.dispatch(DagsAction::Updated(updated)),
+                    Err(err) => error.set(Some(err)),
                 }
             });
-            dags_response.set(response);
-            let fetch_data_clone = fetch_data.clone();
-            let handle = gloo_timers::callback::Timeout::new(500, move || {
-                fetch_data_clone.run();
-            });
-            handle.forget();
         })
     };
 
-    // Unused sort handler for future use
-    let _on_sort = {
+    let on_trigger = {
+        let trigger_dag_id = trigger_dag_id.clone();
+        Callback::from(move |dag_id: String| trigger_dag_id.set(Some(dag_id)))
+    };
+
+    let on_trigger_close = {
+        let trigger_dag_id = trigger_dag_id.clone();
+        Callback::from(move |_| trigger_dag_id.set(None))
+    };
+
+    // The new run reaches the counters as a server event
+    let on_triggered = {
+        let trigger_dag_id = trigger_dag_id.clone();
+        Callback::from(move |run: DagRun| {
+            log::info!("Triggered run {} of {}", run.run_id, run.dag_id);
+            trigger_dag_id.set(None);
+        })
+    };
+
+    // Sorting happens on the server, over all pages; shift-click sorts on several columns
+    let on_sort_changed = {
         let query = query.clone();
-        Callback::from(move |(field, direction): (String, SortDirection)| {
-            let sort_order = match direction {
-                SortDirection::Ascending => "asc",
-                SortDirection::Descending => "desc",
-            };
+        Callback::from(move |evt: GridSortEvent| {
+            let keys: Vec<String> = evt
+                .columns
+                .iter()
+                .map(|(field, direction)| {
+                    let direction = match direction {
+                        SortDirection::Ascending => "asc",
+                        SortDirection::Descending => "desc",
+                    };
+                    format!("{}:{}", field, direction)
+                })
+                .collect();
+            let sort_by = if keys.is_empty() { DEFAULT_SORT.to_string() } else { keys.join(",") };
+            if query.sort_by.as_deref() != Some(sort_by.as_str()) {
+                let mut new_query = (*query).clone();
+                new_query.sort_by = Some(sort_by);
+                new_query.page = Some(1);
+                query.set(new_query);
+            }
+        })
+    };
+
+    let page = query.page.unwrap_or(1);
+    let limit = query.limit.unwrap_or(10);
+    let total_pages = dags.response.total_count.div_ceil(limit).max(1);
+
+    let on_prev = {
+        let query = query.clone();
+        Callback::from(move |_: MouseEvent| {
             let mut new_query = (*query).clone();
-            new_query.sort_by = Some(field);
-            new_query.sort_order = Some(sort_order.to_string());
+            new_query.page = Some(page.saturating_sub(1).max(1));
             query.set(new_query);
         })
     };
 
-    let on_pagination_changed = {
+    let on_next = {
         let query = query.clone();
-        Callback::from(move |evt: GridPaginationEvent| {
+        Callback::from(move |_: MouseEvent| {
             let mut new_query = (*query).clone();
-            if new_query.page != Some(evt.current_page) {
-                new_query.page = Some(evt.current_page);
-                query.set(new_query);
-            }
+            new_query.page = Some((page + 1).min(total_pages));
+            query.set(new_query);
         })
     };
 
@@ -183,6 +313,7 @@ pub fn dag_list() -> Html {
             .filter(true),
         ColumnDef::new("tags", "Tags")
             .with_width(150)
+            .sortable(false)
             .filter(true)
             .with_extra_prop("cellRenderer", r#"
                 function(params) {
@@ -221,14 +352,12 @@ pub fn dag_list() -> Html {
             "#.to_string()),
         ColumnDef::new("actions", "Actions")
             .with_width(100)
+            .sortable(false)
             .with_extra_prop("cellRenderer", r#"
                 function(params) {
                     const toggleText = params.data.paused ? 'Unpause' : 'Pause';
                     const toggleIcon = params.data.paused ? 'fa-play' : 'fa-pause';
-                    return `<div class="flex space-x-2">
-                        <a href="/dag/${params.data.dag_id}/graph" class="text-gray-500 hover:text-blue-600 dark:text-gray-400 dark:hover:text-blue-400" title="View DAG Graph">
-                            <i class="fas fa-project-diagram"></i>
-                        </a>
+                    const operateButtons = params.data.can_operate ? `
                         <button
                             class="text-gray-500 hover:text-blue-600 dark:text-gray-400 dark:hover:text-blue-400"
                             onclick="window.togglePaused('${params.data.dag_id}', ${!params.data.paused})"
@@ -238,10 +367,19 @@ pub fn dag_list() -> Html {
                         </button>
                         <button
                             class="text-gray-500 hover:text-blue-600 dark:text-gray-400 dark:hover:text-blue-400"
+                            onclick="window.triggerDag('${params.data.dag_id}')"
                             title="Trigger DAG"
                         >
                             <i class="fas fa-play"></i>
-                        </button>
+                        </button>` : '';
+                    return `<div class="flex space-x-2">
+                        <a href="/dag/${params.data.dag_id}/graph" class="text-gray-500 hover:text-blue-600 dark:text-gray-400 dark:hover:text-blue-400" title="View DAG Graph">
+                            <i class="fas fa-project-diagram"></i>
+                        </a>
+                        <a href="/dag/${params.data.dag_id}/runs" class="text-gray-500 hover:text-blue-600 dark:text-gray-400 dark:hover:text-blue-400" title="View Run History">
+                            <i class="fas fa-history"></i>
+                        </a>
+                        ${operateButtons}
                     </div>`;
                 }
             "#.to_string()),
@@ -266,10 +404,32 @@ pub fn dag_list() -> Html {
         });
     }
 
-    // Convert the fetched DAGs to JsValue for the grid
-    let row_data = dags_response.dags.iter()
+    // And one for opening the trigger dialog
+    {
+        let on_trigger = on_trigger.clone();
+        use_effect_with((), move |_| {
+            let window = web_sys::window().expect("no global window exists");
+            let closure = Closure::wrap(Box::new(move |dag_id: String| {
+                on_trigger.emit(dag_id);
+            }) as Box<dyn FnMut(String)>);
+            js_sys::Reflect::set(
+                &window,
+                &JsValue::from_str("triggerDag"),
+                closure.as_ref().unchecked_ref(),
+            ).expect("Failed to set triggerDag function");
+            closure.forget();
+            move || {}
+        });
+    }
+
+    // Convert the fetched DAGs to JsValue for the grid, marking the ones the user may operate
+    let row_data = dags.response.dags.iter()
         .map(|dag| {
             let val = to_value(dag).unwrap_or(JsValue::NULL);
+            if val.is_object() {
+                let can_operate = auth_ctx.can_operate(&dag.owner, &dag.tags);
+                let _ = js_sys::Reflect::set(&val, &JsValue::from_str("can_operate"), &JsValue::from_bool(can_operate));
+            }
             web_sys::console::log_1(&format!("Row data: {:?}", dag).into());
             val
         })
@@ -325,6 +485,7 @@ pub fn dag_list() -> Html {
                 on_search={on_search}
                 on_rows_change={Some(on_limit_change)}
                 current_limit={query.limit}
+                query_error={(*query_error).clone()}
             />
 
             {
@@ -346,16 +507,46 @@ pub fn dag_list() -> Html {
                     row_data={row_data}
                     column_defs={column_defs}
                     height="600px"
-                    pagination={true}
-                    page_size={query.limit}
                     row_selection={false}
                     dark_mode={true}
                     custom_options={Some(custom_options)}
                     on_cell_clicked={Some(on_cell_clicked)}
-                    on_pagination_changed={Some(on_pagination_changed)}
+                    on_sort_changed={Some(on_sort_changed)}
                     on_grid_ready={Some(on_grid_ready)}
                 />
             </div>
+
+            <div class="flex justify-end items-center space-x-3 mb-4 text-sm text-gray-600 dark:text-gray-300">
+                <span>{format!("{} DAGs", dags.response.total_count)}</span>
+                <button
+                    class="px-3 py-1 bg-gray-200 hover:bg-gray-300 rounded disabled:opacity-50"
+                    disabled={page <= 1}
+                    onclick={on_prev}
+                >
+                    {"Previous"}
+                </button>
+                <span>{format!("Page {} of {}", page, total_pages)}</span>
+                <button
+                    class="px-3 py-1 bg-gray-200 hover:bg-gray-300 rounded disabled:opacity-50"
+                    disabled={page >= total_pages}
+                    onclick={on_next}
+                >
+                    {"Next"}
+                </button>
+            </div>
+
+            {
+                match trigger_dag_id.as_ref().and_then(|id| dags.response.dags.iter().find(|dag| &dag.dag_id == id)) {
+                    Some(dag) => html! {
+                        <TriggerDialog
+                            dag={dag.clone()}
+                            on_close={on_trigger_close}
+                            on_triggered={on_triggered}
+                        />
+                    },
+                    None => html! {},
+                }
+            }
         </div>
     }
 }
@@ -0,0 +1,228 @@
+use crate::components::table::{Table, TableBody, TableHead};
+use crate::models::dag::{DagRun, DagRunsQuery, DagRunsResponse, TaskStatus};
+use crate::utils::api::{fetch_dag_runs, format_datetime};
+use yew::prelude::*;
+use yew_hooks::prelude::*;
+
+const RUN_STATES: [&str; 4] = ["QUEUED", "RUNNING", "SUCCEEDED", "FAILED"];
+
+#[derive(Properties, PartialEq)]
+pub struct DagRunsProps {
+    pub dag_id: String,
+}
+
+/// Run history of a single DAG, newest first
+#[function_component(DagRuns)]
+pub fn dag_runs(props: &DagRunsProps) -> Html {
+    let query = use_state(|| DagRunsQuery {
+        page: Some(1),
+        limit: Some(25),
+        state: None,
+    });
+
+    let runs_response = use_state(|| DagRunsResponse {
+        runs: vec![],
+        total_count: 0,
+    });
+
+    let loading = use_state(|| true);
+    let error = use_state(|| None::<String>);
+
+    let fetch_data = {
+        let dag_id = props.dag_id.clone();
+        let query = query.clone();
+        let runs_response = runs_response.clone();
+        let loading = loading.clone();
+        let error = error.clone();
+        use_async(async move {
+            loading.set(true);
+            error.set(None);
+
+            match fetch_dag_runs(&dag_id, &query).await {
+                Ok(response) => {
+                    runs_response.set(response);
+                    loading.set(false);
+                    Ok(())
+                }
+                Err(err) => {
+                    error.set(Some(err.clone()));
+                    loading.set(false);
+                    Err(err)
+                }
+            }
+        })
+    };
+
+    // Refetch whenever the DAG or the query changes
+    {
+        let fetch_data = fetch_data.clone();
+        let deps = (props.dag_id.clone(), (*query).clone());
+        use_effect_with(deps, move |_| {
+            fetch_data.run();
+            || {}
+        });
+    }
+
+    let on_state_change = {
+        let query = query.clone();
+        Callback::from(move |e: Event| {
+            let select: web_sys::HtmlSelectElement = e.target_unchecked_into();
+            let value = select.value();
+            let mut new_query = (*query).clone();
+            new_query.state = if value.is_empty() { None } else { Some(value) };
+            new_query.page = Some(1);
+            query.set(new_query);
+        })
+    };
+
+    let page = query.page.unwrap_or(1);
+    let limit = query.limit.unwrap_or(25);
+    let total_pages = runs_response.total_count.div_ceil(limit).max(1);
+
+    let on_prev = {
+        let query = query.clone();
+        Callback::from(move |_: MouseEvent| {
+            let mut new_query = (*query).clone();
+            new_query.page = Some(page.saturating_sub(1).max(1));
+            query.set(new_query);
+        })
+    };
+
+    let on_next = {
+        let query = query.clone();
+        Callback::from(move |_: MouseEvent| {
+            let mut new_query = (*query).clone();
+            new_query.page = Some((page + 1).min(total_pages));
+            query.set(new_query);
+        })
+    };
+
+    let selected_state = query.state.clone().unwrap_or_default();
+
+    html! {
+        <div class="p-4">
+            <div class="flex justify-between items-center mb-4">
+                <div>
+                    <h2 class="text-xl font-semibold text-gray-800 dark:text-gray-100">
+                        {format!("Run history: {}", props.dag_id)}
+                    </h2>
+                    <p class="text-sm text-gray-500 dark:text-gray-400">
+                        {format!("{} runs", runs_response.total_count)}
+                    </p>
+                </div>
+                <div class="flex items-center space-x-3">
+                    <select
+                        class="border border-gray-300 dark:border-gray-600 rounded px-2 py-1 text-sm dark:bg-gray-800 dark:text-gray-200"
+                        onchange={on_state_change}
+                    >
+                        <option value="" selected={selected_state.is_empty()}>{"All states"}</option>
+                        {
+                            RUN_STATES.iter().map(|state| html! {
+                                <option value={*state} selected={selected_state == *state}>{*state}</option>
+                            }).collect::<Html>()
+                        }
+                    </select>
+                    <a href={format!("/dag/{}/graph", props.dag_id)} class="text-sm text-blue-600 hover:text-blue-800">
+                        <i class="fas fa-project-diagram mr-1"></i>
+                        {"View Graph"}
+                    </a>
+                    <a href={format!("/audit?dag_id={}", js_sys::encode_uri_component(&props.dag_id))} class="text-sm text-blue-600 hover:text-blue-800">
+                        <i class="fas fa-clipboard-list mr-1"></i>
+                        {"Audit Log"}
+                    </a>
+                </div>
+            </div>
+
+            {
+                if let Some(error_message) = &*error {
+                    html! {
+                        <div class="bg-red-50 dark:bg-red-900 border border-red-200 dark:border-red-800 text-red-700 dark:text-red-200 px-4 py-3 rounded mb-4">
+                            <p>{format!("Error loading runs: {}", error_message)}</p>
+                        </div>
+                    }
+                } else {
+                    html! {}
+                }
+            }
+
+            <div class="bg-white dark:bg-gray-900 rounded-lg shadow dark:shadow-gray-800">
+                <Table>
+                    <TableHead>
+                        <tr>
+                            <th class="px-4 py-2 text-left text-xs font-medium text-gray-500 dark:text-gray-400 uppercase">{"Run ID"}</th>
+                            <th class="px-4 py-2 text-left text-xs font-medium text-gray-500 dark:text-gray-400 uppercase">{"State"}</th>
+                            <th class="px-4 py-2 text-left text-xs font-medium text-gray-500 dark:text-gray-400 uppercase">{"Type"}</th>
+                            <th class="px-4 py-2 text-left text-xs font-medium text-gray-500 dark:text-gray-400 uppercase">{"Logical Date"}</th>
+                            <th class="px-4 py-2 text-left text-xs font-medium text-gray-500 dark:text-gray-400 uppercase">{"Started"}</th>
+                            <th class="px-4 py-2 text-left text-xs font-medium text-gray-500 dark:text-gray-400 uppercase">{"Ended"}</th>
+                            <th class="px-4 py-2 text-left text-xs font-medium text-gray-500 dark:text-gray-400 uppercase">{"Triggered By"}</th>
+                            <th class="px-4 py-2 text-left text-xs font-medium text-gray-500 dark:text-gray-400 uppercase">{"Tasks"}</th>
+                        </tr>
+                    </TableHead>
+                    <TableBody
+                        loading={*loading}
+                        empty={runs_response.runs.is_empty()}
+                        col_span={8}
+                        no_data_message={"No runs yet".to_string()}
+                    >
+                        { for runs_response.runs.iter().map(run_row) }
+                    </TableBody>
+                </Table>
+            </div>
+
+            <div class="flex justify-end items-center space-x-3 mt-3 text-sm text-gray-600 dark:text-gray-300">
+                <button
+                    class="px-3 py-1 bg-gray-200 hover:bg-gray-300 rounded disabled:opacity-50"
+                    disabled={page <= 1}
+                    onclick={on_prev}
+                >
+                    {"Previous"}
+                </button>
+                <span>{format!("Page {} of {}", page, total_pages)}</span>
+                <button
+                    class="px-3 py-1 bg-gray-200 hover:bg-gray-300 rounded disabled:opacity-50"
+                    disabled={page >= total_pages}
+                    onclick={on_next}
+                >
+                    {"Next"}
+                </button>
+            </div>
+        </div>
+    }
+}
+
+fn run_row(run: &DagRun) -> Html {
+    let count = |status: TaskStatus| {
+        run.task_instances
+            .iter()
+            .filter(|ti| ti.status == status)
+            .count()
+    };
+
+    html! {
+        <tr class="text-sm text-gray-700 dark:text-gray-200">
+            <td class="px-4 py-2 font-mono">
+                <a
+                    href={format!("/dag/{}/graph?run_id={}", run.dag_id, js_sys::encode_uri_component(&run.run_id))}
+                    class="text-blue-600 hover:text-blue-800"
+                >
+                    {&run.run_id}
+                </a>
+            </td>
+            <td class="px-4 py-2">
+                <span class="inline-block w-2 h-2 rounded-full mr-2" style={format!("background-color: {}", run.state.color())}></span>
+                {run.state.label()}
+            </td>
+            <td class="px-4 py-2">{run.run_type.label()}</td>
+            <td class="px-4 py-2">{format_datetime(Some(run.logical_date))}</td>
+            <td class="px-4 py-2">{format_datetime(run.start_date)}</td>
+            <td class="px-4 py-2">{format_datetime(run.end_date)}</td>
+            <td class="px-4 py-2">{run.triggered_by.as_deref().unwrap_or("-")}</td>
+            <td class="px-4 py-2">
+                <span class="mr-2">{run.task_instances.len()}</span>
+                <span class="text-green-600 mr-2">{format!("✓{}", count(TaskStatus::SUCCEEDED))}</span>
+                <span class="text-red-600">{format!("✗{}", count(TaskStatus::FAILED))}</span>
+            </td>
+        </tr>
+    }
+}
@@ -0,0 +1,215 @@
+use crate::models::dag::LogChunk;
+use crate::utils::api;
+use std::rc::Rc;
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+use wasm_bindgen_futures::spawn_local;
+use web_sys::{EventSource, MessageEvent};
+use yew::prelude::*;
+
+#[derive(Properties, PartialEq)]
+pub struct LogViewerProps {
+    pub dag_id: String,
+    pub run_id: String,
+    pub task_id: String,
+    /// Latest attempt of the task; earlier ones can be picked from the viewer
+    pub latest_try: usize,
+    /// Whether the latest attempt is still running, in which case its log is tailed
+    #[prop_or(false)]
+    pub running: bool,
+}
+
+#[derive(Default, PartialEq)]
+struct LogState {
+    content: String,
+    next_offset: u64,
+    size: u64,
+    streaming: bool,
+    error: Option<String>,
+}
+
+enum LogAction {
+    Reset { streaming: bool },
+    Page(LogChunk),
+    Append(String),
+    Ended,
+    Failed(String),
+}
+
+impl Reducible for LogState {
+    type Action = LogAction;
+
+    fn reduce(self: Rc<Self>, action: Self::Action) -> Rc<Self> {
+        let mut state = LogState {
+            content: self.content.clone(),
+            next_offset: self.next_offset,
+            size: self.size,
+            streaming: self.streaming,
+            error: self.error.clone(),
+        };
+        match action {
+            LogAction::Reset { streaming } => {
+                state = LogState {
+                    streaming,
+                    ..Default::default()
+                };
+            }
+            LogAction::Page(chunk) => {
+                state.content.push_str(&chunk.content);
+                state.next_offset = chunk.next_offset;
+                state.size = chunk.size;
+            }
+            LogAction::Append(text) => state.content.push_str(&text),
+            LogAction::Ended => state.streaming = false,
+            LogAction::Failed(err) => {
+                state.error = Some(err);
+                state.streaming = false;
+            }
+        }
+        Rc::new(state)
+    }
+}
+
+/// Output of one task attempt: finished attempts are paged in, a running one is tailed live
+#[function_component(LogViewer)]
+pub fn log_viewer(props: &LogViewerProps) -> Html {
+    let selected_try = use_state(|| props.latest_try.max(1));
+    let log = use_reducer(LogState::default);
+
+    // Jump back to the newest attempt when the task or its attempts change
+    {
+        let selected_try = selected_try.clone();
+        use_effect_with((props.task_id.clone(), props.latest_try), move |(_, latest_try)| {
+            selected_try.set((*latest_try).max(1));
+            || {}
+        });
+    }
+
+    let follow = props.running && *selected_try == props.latest_try;
+
+    {
+        let log = log.clone();
+        let deps = (
+            props.dag_id.clone(),
+            props.run_id.clone(),
+            props.task_id.clone(),
+            *selected_try,
+            follow,
+        );
+        use_effect_with(deps, move |(dag_id, run_id, task_id, try_number, follow)| {
+            log.dispatch(LogAction::Reset { streaming: *follow });
+
+            let mut source: Option<(EventSource, Vec<Closure<dyn FnMut(MessageEvent)>>)> = None;
+            if *follow {
+                let url = api::task_logs_stream_url(dag_id, run_id, task_id, *try_number);
+                match EventSource::new(&url) {
+                    Ok(events) => {
+                        let on_log = {
+                            let log = log.clone();
+                            Closure::wrap(Box::new(move |e: MessageEvent| {
+                                if let Some(text) = e.data().as_string() {
+                                    log.dispatch(LogAction::Append(text));
+                                }
+                            }) as Box<dyn FnMut(MessageEvent)>)
+                        };
+                        let on_end = {
+                            let log = log.clone();
+                            let events = events.clone();
+                            Closure::wrap(Box::new(move |_: MessageEvent| {
+                                events.close();
+                                log.dispatch(LogAction::Ended);
+                            }) as Box<dyn FnMut(MessageEvent)>)
+                        };
+                        let _ = events.add_event_listener_with_callback("log", on_log.as_ref().unchecked_ref());
+                        let _ = events.add_event_listener_with_callback("end", on_end.as_ref().unchecked_ref());
+                        source = Some((events, vec![on_log, on_end]));
+                    }
+                    Err(err) => log.dispatch(LogAction::Failed(format!("{:?}", err))),
+                }
+            } else {
+                let (dag_id, run_id, task_id, try_number) =
+                    (dag_id.clone(), run_id.clone(), task_id.clone(), *try_number);
+                spawn_local(async move {
+                    match api::fetch_task_logs(&dag_id, &run_id, &task_id, try_number, 0).await {
+                        Ok(chunk) => log.dispatch(LogAction::Page(chunk)),
+                        Err(err) => log.dispatch(LogAction::Failed(err)),
+                    }
+                });
+            }
+
+            move || {
+                if let Some((events, _listeners)) = source {
+                    events.close();
+                }
+            }
+        });
+    }
+
+    let on_load_more = {
+        let log = log.clone();
+        let dag_id = props.dag_id.clone();
+        let run_id = props.run_id.clone();
+        let task_id = props.task_id.clone();
+        let try_number = *selected_try;
+        let offset = log.next_offset;
+        Callback::from(move |_: MouseEvent| {
+            let log = log.clone();
+            let (dag_id, run_id, task_id) = (dag_id.clone(), run_id.clone(), task_id.clone());
+            spawn_local(async move {
+                match api::fetch_task_logs(&dag_id, &run_id, &task_id, try_number, offset).await {
+                    Ok(chunk) => log.dispatch(LogAction::Page(chunk)),
+                    Err(err) => log.dispatch(LogAction::Failed(err)),
+                }
+            });
+        })
+    };
+
+    let has_more = !follow && log.next_offset < log.size;
+
+    html! {
+        <div class="log-viewer">
+            <div class="log-viewer-header">
+                <span>{ "Log" }</span>
+                <div class="log-viewer-tries">
+                    {
+                        (1..=props.latest_try.max(1)).map(|try_number| {
+                            let selected_try = selected_try.clone();
+                            let class = if try_number == *selected_try { "log-try selected" } else { "log-try" };
+                            let onclick = Callback::from(move |_: MouseEvent| selected_try.set(try_number));
+                            html! {
+                                <button {class} {onclick}>{ format!("Try {}", try_number) }</button>
+                            }
+                        }).collect::<Html>()
+                    }
+                    {
+                        if log.streaming {
+                            html! { <span class="log-live"><i class="fas fa-circle mr-1"></i>{ "Live" }</span> }
+                        } else {
+                            html! {}
+                        }
+                    }
+                </div>
+            </div>
+            {
+                if let Some(error) = &log.error {
+                    html! { <div class="error">{ format!("Error loading log: {}", error) }</div> }
+                } else if log.content.is_empty() && !log.streaming {
+                    html! { <p class="task-details-note">{ "No output for this attempt." }</p> }
+                } else {
+                    html! { <pre class="log-content">{ &log.content }</pre> }
+                }
+            }
+            {
+                if has_more {
+                    html! {
+                        <button class="log-load-more" onclick={on_load_more}>
+                            { format!("Load more ({} of {} bytes shown)", log.next_offset, log.size) }
+                        </button>
+                    }
+                } else {
+                    html! {}
+                }
+            }
+        </div>
+    }
+}
@@ -0,0 +1,160 @@
+use crate::models::user::{AuthContext, AuthStatus};
+use crate::utils::api::{login, logout};
+use web_sys::HtmlInputElement;
+use yew::prelude::*;
+
+#[derive(Properties, PartialEq)]
+pub struct LoginProps {
+    /// Where to go once signed in
+    #[prop_or_else(|| "/".to_string())]
+    pub next: String,
+}
+
+/// Username and password form. A successful sign-in reloads the app at `next`, so every view
+/// starts over with the new session.
+#[function_component(Login)]
+pub fn login_page(props: &LoginProps) -> Html {
+    let auth_ctx = use_context::<AuthContext>().expect("No auth context found");
+    let username = use_state(String::new);
+    let password = use_state(String::new);
+    let error = use_state(|| None::<String>);
+    let submitting = use_state(|| false);
+
+    let on_username = {
+        let username = username.clone();
+        Callback::from(move |e: InputEvent| {
+            let input: HtmlInputElement = e.target_unchecked_into();
+            username.set(input.value());
+        })
+    };
+
+    let on_password = {
+        let password = password.clone();
+        Callback::from(move |e: InputEvent| {
+            let input: HtmlInputElement = e.target_unchecked_into();
+            password.set(input.value());
+        })
+    };
+
+    let on_submit = {
+        let username = username.clone();
+        let password = password.clone();
+        let error = error.clone();
+        let submitting = submitting.clone();
+        let next = props.next.clone();
+        Callback::from(move |e: SubmitEvent| {
+            e.prevent_default();
+            let (username, password) = ((*username).clone(), (*password).clone());
+            let error = error.clone();
+            let submitting = submitting.clone();
+            let next = next.clone();
+            submitting.set(true);
+            wasm_bindgen_futures::spawn_local(async move {
+                match login(&username, &password).await {
+                    Ok(_) => {
+                        let _ = gloo::utils::window().location().set_href(&next);
+                    }
+                    Err(err) => {
+                        error.set(Some(err));
+                        submitting.set(false);
+                    }
+                }
+            });
+        })
+    };
+
+    if !auth_ctx.auth_enabled() {
+        return html! {
+            <div class="max-w-sm mx-auto mt-16 p-6 bg-white dark:bg-gray-900 rounded-lg shadow text-gray-700 dark:text-gray-200">
+                <p>{"Sign-in is not required on this server."}</p>
+                <a href="/" class="text-blue-600 hover:text-blue-800">{"Go to DAGs"}</a>
+            </div>
+        };
+    }
+
+    html! {
+        <div class="max-w-sm mx-auto mt-16 p-6 bg-white dark:bg-gray-900 rounded-lg shadow">
+            <h2 class="text-xl font-semibold text-gray-800 dark:text-gray-100 mb-4">{"Sign in to Cyclonetix"}</h2>
+            {
+                if let Some(message) = &*error {
+                    html! {
+                        <div class="bg-red-50 dark:bg-red-900 border border-red-200 dark:border-red-800 text-red-700 dark:text-red-200 px-3 py-2 rounded mb-3 text-sm">
+                            {message}
+                        </div>
+                    }
+                } else {
+                    html! {}
+                }
+            }
+            <form onsubmit={on_submit} class="space-y-3">
+                <div>
+                    <label class="block text-sm text-gray-600 dark:text-gray-300 mb-1">{"Username"}</label>
+                    <input
+                        type="text"
+                        autocomplete="username"
+                        class="w-full border border-gray-300 dark:border-gray-600 rounded px-2 py-1 dark:bg-gray-800 dark:text-gray-200"
+                        value={(*username).clone()}
+                        oninput={on_username}
+                    />
+                </div>
+                <div>
+                    <label class="block text-sm text-gray-600 dark:text-gray-300 mb-1">{"Password"}</label>
+                    <input
+                        type="password"
+                        autocomplete="current-password"
+                        class="w-full border border-gray-300 dark:border-gray-600 rounded px-2 py-1 dark:bg-gray-800 dark:text-gray-200"
+                        value={(*password).clone()}
+                        oninput={on_password}
+                    />
+                </div>
+                <button
+                    type="submit"
+                    class="w-full bg-blue-600 hover:bg-blue-700 text-white rounded px-3 py-2 disabled:opacity-50"
+                    disabled={*submitting || username.is_empty() || password.is_empty()}
+                >
+                    {if *submitting { "Signing in..." } else { "Sign in" }}
+                </button>
+            </form>
+        </div>
+    }
+}
+
+/// Ends the session as soon as it is shown
+#[function_component(Logout)]
+pub fn logout_page() -> Html {
+    let auth_ctx = use_context::<AuthContext>().expect("No auth context found");
+    let error = use_state(|| None::<String>);
+
+    {
+        let auth_ctx = auth_ctx.clone();
+        let error = error.clone();
+        use_effect_with((), move |_| {
+            wasm_bindgen_futures::spawn_local(async move {
+                match logout().await {
+                    Ok(()) => {
+                        if let Some(status) = &auth_ctx.status {
+                            auth_ctx.set_status.emit(AuthStatus {
+                                auth_enabled: status.auth_enabled,
+                                user: None,
+                            });
+                        }
+                    }
+                    Err(err) => error.set(Some(err)),
+                }
+            });
+            || {}
+        });
+    }
+
+    html! {
+        <div class="max-w-sm mx-auto mt-16 p-6 bg-white dark:bg-gray-900 rounded-lg shadow text-gray-700 dark:text-gray-200">
+            {
+                match &*error {
+                    Some(message) => html! { <p class="text-red-600">{format!("Could not sign out: {}", message)}</p> },
+                    None => html! { <p class="mb-2">{"You have signed out."}</p> },
+                }
+            }
+            <a href="/login" class="text-blue-600 hover:text-blue-800">{"Sign in again"}</a>
+        </div>
+    }
+}
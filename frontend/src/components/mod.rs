@@ -1,8 +1,15 @@
+pub mod audit_log;
 pub mod dag_card;
 pub mod dag_list;
 pub mod dag_graph;
+pub mod dag_runs;
+pub mod log_viewer;
+pub mod login;
 pub mod nav_bar;
 pub mod search_filter;
 pub mod theme_toggle;
+pub mod trigger_dialog;
 pub mod table;
+pub mod tag_input;
+pub mod users;
 
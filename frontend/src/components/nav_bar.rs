@@ -1,8 +1,39 @@
 use crate::components::theme_toggle::ThemeToggle;
+use crate::models::user::AuthContext;
 use yew::prelude::*;
 
 #[function_component(NavBar)]
 pub fn nav_bar() -> Html {
+    let auth_ctx = use_context::<AuthContext>().expect("No auth context found");
+
+    // Who is signed in, with a way out; nothing to sign out of when auth is disabled
+    let account = match auth_ctx.user() {
+        Some(user) => html! {
+            <>
+                <a href="/users#tokens" class="flex items-center" title={user.username.clone()}>
+                    <span class="bg-blue-600 text-white rounded-full h-8 w-8 flex items-center justify-center">
+                        <i class="fas fa-user"></i>
+                    </span>
+                    <span class="ml-2 text-gray-700 hidden md:inline-block">{user.name()}</span>
+                </a>
+                <a href="/logout" class="ml-3 text-gray-700 hover:text-blue-600" title="Sign out">
+                    <i class="fas fa-sign-out-alt text-lg"></i>
+                </a>
+            </>
+        },
+        None if auth_ctx.auth_enabled() => html! {
+            <a href="/login" class="text-gray-700 hover:text-blue-600 font-medium">{"Sign in"}</a>
+        },
+        None => html! {
+            <a href="/users" class="flex items-center" title="Auth is disabled">
+                <span class="bg-blue-600 text-white rounded-full h-8 w-8 flex items-center justify-center">
+                    <i class="fas fa-user"></i>
+                </span>
+                <span class="ml-2 text-gray-700 hidden md:inline-block">{"Anonymous"}</span>
+            </a>
+        },
+    };
+
     html! {
         <nav class="airflow-navbar py-2 shadow-sm">
             <div class="container mx-auto px-4">
@@ -25,7 +56,11 @@ pub fn nav_bar() -> Html {
                                 <i class="fas fa-tasks mr-1"></i>
                                 {"Tasks"}
                             </a>
-                            <a href="#" class="text-gray-700 hover:text-blue-600 px-3 py-2 rounded-md font-medium">
+                            <a href="/audit" class="text-gray-700 hover:text-blue-600 px-3 py-2 rounded-md font-medium">
+                                <i class="fas fa-clipboard-list mr-1"></i>
+                                {"Audit"}
+                            </a>
+                            <a href="/users" class="text-gray-700 hover:text-blue-600 px-3 py-2 rounded-md font-medium">
                                 <i class="fas fa-users mr-1"></i>
                                 {"Users"}
                             </a>
@@ -44,10 +79,7 @@ pub fn nav_bar() -> Html {
                             <i class="fas fa-question-circle text-lg"></i>
                         </a>
                         <div class="flex items-center">
-                            <span class="bg-blue-600 text-white rounded-full h-8 w-8 flex items-center justify-center">
-                                <i class="fas fa-user"></i>
-                            </span>
-                            <span class="ml-2 text-gray-700 hidden md:inline-block">{"Prefs"}</span>
+                            { account }
                         </div>
                     </div>
                 </div>
@@ -1,5 +1,9 @@
-use crate::models::dag::DAGsQuery;
-use web_sys::HtmlInputElement;
+use crate::components::tag_input::TagInput;
+use crate::models::dag::{DAGsQuery, TagCount};
+use crate::models::search::{highlight, suggest, QueryError, Suggestion};
+use crate::utils::api::fetch_tags;
+use wasm_bindgen_futures::spawn_local;
+use web_sys::{HtmlElement, HtmlInputElement};
 use yew::prelude::*;
 use wasm_bindgen::JsCast;
 
@@ -11,6 +15,88 @@ pub struct SearchFilterProps {
     pub on_rows_change: Option<Callback<usize>>,
     #[prop_or(Some(10))]
     pub current_limit: Option<usize>,
+    /// Where the server could not parse the search, underlined in the search box
+    #[prop_or_default]
+    pub query_error: Option<QueryError>,
+}
+
+/// Completions offered for the word before the cursor
+#[derive(Clone, PartialEq, Default)]
+struct Completions {
+    /// Character range the chosen suggestion replaces
+    start: usize,
+    end: usize,
+    suggestions: Vec<Suggestion>,
+    selected: usize,
+}
+
+impl Completions {
+    fn at_cursor(value: &str, cursor: usize, facets: &[TagCount]) -> Self {
+        let tags: Vec<String> = facets.iter().map(|facet| facet.tag.clone()).collect();
+        let (start, suggestions) = suggest(value, cursor, &tags);
+        Completions {
+            start,
+            end: cursor,
+            suggestions,
+            selected: 0,
+        }
+    }
+
+    /// The query with the selected suggestion in place of the word, and the cursor after it
+    fn apply(&self, value: &str) -> (String, usize) {
+        let chars: Vec<char> = value.chars().collect();
+        let suggestion = &self.suggestions[self.selected].text;
+        // Values are complete terms; fields still need theirs
+        let inserted = if suggestion.ends_with(':') {
+            suggestion.clone()
+        } else {
+            format!("{} ", suggestion)
+        };
+        let before: String = chars[..self.start].iter().collect();
+        let after: String = chars[self.end..].iter().collect();
+        let cursor = self.start + inserted.chars().count();
+        (format!("{}{}{}", before, inserted, after.trim_start()), cursor)
+    }
+}
+
+/// Character offset of the cursor in a text input
+fn cursor_position(input: &HtmlInputElement) -> usize {
+    let value = input.value();
+    let utf16_offset = input.selection_start().ok().flatten().unwrap_or(0) as usize;
+    char::decode_utf16(value.encode_utf16().take(utf16_offset)).count()
+}
+
+/// The highlighted query, with the part the server rejected underlined
+fn highlighted_query(value: &str, query_error: Option<&QueryError>) -> Html {
+    let error_range = query_error.map(|err| err.position..err.position + err.length);
+    let mut offset = 0;
+    let mut parts = Vec::new();
+    for (kind, text) in highlight(value) {
+        let chars: Vec<char> = text.chars().collect();
+        let span_range = offset..offset + chars.len();
+        offset = span_range.end;
+        // Split the stretch where the error starts and ends
+        let mut cuts = vec![span_range.start, span_range.end];
+        if let Some(range) = &error_range {
+            for cut in [range.start, range.end] {
+                if span_range.contains(&cut) {
+                    cuts.push(cut);
+                }
+            }
+        }
+        cuts.sort_unstable();
+        cuts.dedup();
+        for pair in cuts.windows(2) {
+            let piece: String = chars[pair[0] - span_range.start..pair[1] - span_range.start].iter().collect();
+            let in_error = error_range.as_ref().is_some_and(|range| range.contains(&pair[0]));
+            let class = classes!(
+                kind.class(),
+                in_error.then_some("underline decoration-wavy decoration-red-500")
+            );
+            parts.push(html! { <span class={class}>{piece}</span> });
+        }
+    }
+    parts.into_iter().collect()
 }
 
 #[function_component(SearchFilter)]
@@ -20,7 +106,6 @@ pub fn search_filter(props: &SearchFilterProps) -> Html {
 
     let search_value = use_state(|| query.search.clone().unwrap_or_default());
     let status_value = use_state(|| query.status.clone().unwrap_or_default());
-    let tags_value = use_state(|| query.tags.clone().unwrap_or_default());
 
     // Initialize rows_limit from local storage if available, otherwise use props.current_limit.
     let rows_limit = {
@@ -51,28 +136,139 @@ pub fn search_filter(props: &SearchFilterProps) -> Html {
         });
     }
 
-    let on_search_input = {
-        let search_value = search_value.clone();
+    // Tag counts follow the search and status, so the chips offer what would still match
+    let tag_facets = use_state(Vec::<TagCount>::new);
+    {
+        let tag_facets = tag_facets.clone();
+        let facet_query = DAGsQuery {
+            search: query.search.clone(),
+            status: query.status.clone(),
+            ..Default::default()
+        };
+        use_effect_with(facet_query, move |facet_query| {
+            let facet_query = facet_query.clone();
+            spawn_local(async move {
+                // A malformed search is reported by the DAG list; keep the last counts meanwhile
+                match fetch_tags(&facet_query).await {
+                    Ok(facets) => tag_facets.set(facets),
+                    Err(err) => log::warn!("Failed to load tags: {}", err),
+                }
+            });
+            || ()
+        });
+    }
+
+    let search_input = use_node_ref();
+    let search_overlay = use_node_ref();
+    let completions = use_state(Completions::default);
+
+    // Keep the highlighted copy scrolled along with a query longer than the box
+    let sync_overlay = {
+        let search_overlay = search_overlay.clone();
+        move |input: &HtmlInputElement| {
+            if let Some(overlay) = search_overlay.cast::<HtmlElement>() {
+                overlay.set_scroll_left(input.scroll_left());
+            }
+        }
+    };
+
+    let emit_search = {
         let on_search = on_search.clone();
         let query = query.clone();
+        move |value: String| {
+            let mut new_query = query.clone();
+            if value.trim().is_empty() {
+                new_query.search = None;
+            } else {
+                new_query.search = Some(value);
+            }
+            on_search.emit(new_query);
+        }
+    };
+
+    let on_search_input = {
+        let search_value = search_value.clone();
+        let completions = completions.clone();
+        let tag_facets = tag_facets.clone();
+        let emit_search = emit_search.clone();
+        let sync_overlay = sync_overlay.clone();
 
         Callback::from(move |e: InputEvent| {
             let target = e.target().unwrap();
             let input = target.dyn_into::<HtmlInputElement>().unwrap();
             let value = input.value();
             search_value.set(value.clone());
+            completions.set(Completions::at_cursor(&value, cursor_position(&input), &tag_facets));
+            sync_overlay(&input);
+            emit_search(value);
+        })
+    };
 
-            let mut new_query = query.clone();
-            if value.is_empty() {
-                new_query.search = None;
-            } else {
-                new_query.search = Some(value);
+    // Put the chosen completion into the box and offer the next ones, e.g. values after a field
+    let accept_completion = {
+        let search_value = search_value.clone();
+        let completions = completions.clone();
+        let tag_facets = tag_facets.clone();
+        let search_input = search_input.clone();
+        let emit_search = emit_search.clone();
+        move |chosen: &Completions| {
+            let (value, cursor) = chosen.apply(&search_value);
+            if let Some(input) = search_input.cast::<HtmlInputElement>() {
+                input.set_value(&value);
+                let utf16_cursor: usize = value.chars().take(cursor).map(char::len_utf16).sum();
+                let _ = input.set_selection_range(utf16_cursor as u32, utf16_cursor as u32);
+                let _ = input.focus();
             }
+            completions.set(Completions::at_cursor(&value, cursor, &tag_facets));
+            search_value.set(value.clone());
+            emit_search(value);
+        }
+    };
 
-            on_search.emit(new_query);
+    let on_search_keydown = {
+        let completions = completions.clone();
+        let accept_completion = accept_completion.clone();
+        Callback::from(move |e: KeyboardEvent| {
+            if completions.suggestions.is_empty() {
+                return;
+            }
+            let count = completions.suggestions.len();
+            let mut next = (*completions).clone();
+            match e.key().as_str() {
+                "ArrowDown" => next.selected = (next.selected + 1) % count,
+                "ArrowUp" => next.selected = (next.selected + count - 1) % count,
+                "Tab" | "Enter" => {
+                    e.prevent_default();
+                    accept_completion(&next);
+                    return;
+                }
+                "Escape" => next = Completions::default(),
+                _ => return,
+            }
+            e.prevent_default();
+            completions.set(next);
         })
     };
 
+    // Moving the cursor changes which word is being completed
+    let on_search_keyup = {
+        let completions = completions.clone();
+        let tag_facets = tag_facets.clone();
+        let sync_overlay = sync_overlay.clone();
+        Callback::from(move |e: KeyboardEvent| {
+            if matches!(e.key().as_str(), "ArrowLeft" | "ArrowRight" | "Home" | "End") {
+                let input: HtmlInputElement = e.target_unchecked_into();
+                completions.set(Completions::at_cursor(&input.value(), cursor_position(&input), &tag_facets));
+                sync_overlay(&input);
+            }
+        })
+    };
+
+    let on_search_blur = {
+        let completions = completions.clone();
+        Callback::from(move |_: FocusEvent| completions.set(Completions::default()))
+    };
+
     let on_status_change = {
         let status_value = status_value.clone();
         let on_search = on_search.clone();
@@ -95,24 +291,32 @@ pub fn search_filter(props: &SearchFilterProps) -> Html {
         })
     };
 
-    let on_tags_input = {
-        let tags_value = tags_value.clone();
+    let selected_tags: Vec<String> = query
+        .tags
+        .as_deref()
+        .unwrap_or_default()
+        .split(',')
+        .map(str::trim)
+        .filter(|tag| !tag.is_empty())
+        .map(str::to_string)
+        .collect();
+
+    let on_tags_change = {
         let on_search = on_search.clone();
         let query = query.clone();
-
-        Callback::from(move |e: InputEvent| {
-            let target = e.target().unwrap();
-            let input = target.dyn_into::<HtmlInputElement>().unwrap();
-            let value = input.value();
-            tags_value.set(value.clone());
-
+        Callback::from(move |tags: Vec<String>| {
             let mut new_query = query.clone();
-            if value.is_empty() {
-                new_query.tags = None;
-            } else {
-                new_query.tags = Some(value);
-            }
+            new_query.tags = if tags.is_empty() { None } else { Some(tags.join(",")) };
+            on_search.emit(new_query);
+        })
+    };
 
+    let on_match_all_change = {
+        let on_search = on_search.clone();
+        let query = query.clone();
+        Callback::from(move |match_all: bool| {
+            let mut new_query = query.clone();
+            new_query.tags_mode = Some(if match_all { "all" } else { "any" }.to_string());
             on_search.emit(new_query);
         })
     };
@@ -124,19 +328,83 @@ pub fn search_filter(props: &SearchFilterProps) -> Html {
                     <label for="search" class="block text-sm font-medium text-gray-700 dark:text-gray-300 mb-1">
                         {"Search DAGs"}
                     </label>
-                    <div class="relative rounded-md shadow-sm">
+                    <div class="relative rounded-md shadow-sm bg-white dark:bg-gray-700">
                         <div class="absolute inset-y-0 left-0 pl-3 flex items-center pointer-events-none">
                             <i class="fas fa-search text-gray-400"></i>
                         </div>
+                        // The input's own text is transparent; this highlighted copy shows through it
+                        <div
+                            ref={search_overlay.clone()}
+                            class="absolute inset-0 pl-10 pr-12 py-2 border-2 border-transparent whitespace-pre overflow-hidden pointer-events-none text-gray-700 dark:text-gray-300"
+                            aria-hidden="true"
+                        >
+                            {highlighted_query(&search_value, props.query_error.as_ref())}
+                        </div>
                         <input
                             type="text"
                             id="search"
-                            class="focus:ring-blue-500 focus:border-blue-500 block w-full pl-10 pr-12 py-2 border-2 border-gray-300 dark:border-gray-500 bg-white dark:bg-gray-700 text-gray-700 dark:text-gray-300 rounded-md"
-                            placeholder="Search by DAG ID or owner"
+                            ref={search_input}
+                            class="relative focus:ring-blue-500 focus:border-blue-500 block w-full pl-10 pr-12 py-2 border-2 border-gray-300 dark:border-gray-500 bg-transparent rounded-md"
+                            style="color: transparent; caret-color: #6b7280;"
+                            placeholder="owner:me tag:etl -status:paused \"sales\""
+                            autocomplete="off"
+                            spellcheck="false"
                             value={(*search_value).clone()}
                             oninput={on_search_input}
+                            onkeydown={on_search_keydown}
+                            onkeyup={on_search_keyup}
+                            onblur={on_search_blur}
                         />
+                        {
+                            if completions.suggestions.is_empty() {
+                                html! {}
+                            } else {
+                                html! {
+                                    <ul class="absolute z-20 left-0 right-0 mt-1 bg-white dark:bg-gray-800 border border-gray-200 dark:border-gray-600 rounded-md shadow-lg text-sm">
+                                        { for completions.suggestions.iter().enumerate().map(|(index, suggestion)| {
+                                            let selected = index == completions.selected;
+                                            // mousedown, so the choice lands before the input's blur closes the list
+                                            let onmousedown = {
+                                                let completions = completions.clone();
+                                                let accept_completion = accept_completion.clone();
+                                                Callback::from(move |e: MouseEvent| {
+                                                    e.prevent_default();
+                                                    let mut chosen = (*completions).clone();
+                                                    chosen.selected = index;
+                                                    accept_completion(&chosen);
+                                                })
+                                            };
+                                            html! {
+                                                <li
+                                                    class={classes!(
+                                                        "flex", "justify-between", "px-3", "py-1", "cursor-pointer",
+                                                        "text-gray-700", "dark:text-gray-200",
+                                                        if selected {
+                                                            "bg-blue-100 dark:bg-blue-900"
+                                                        } else {
+                                                            "hover:bg-gray-100 dark:hover:bg-gray-700"
+                                                        }
+                                                    )}
+                                                    {onmousedown}
+                                                >
+                                                    <span class="font-mono">{&suggestion.text}</span>
+                                                    <span class="text-xs text-gray-400">{suggestion.hint}</span>
+                                                </li>
+                                            }
+                                        }) }
+                                    </ul>
+                                }
+                            }
+                        }
                     </div>
+                    {
+                        match &props.query_error {
+                            Some(err) => html! {
+                                <p class="mt-1 text-xs text-red-600 dark:text-red-400">{&err.message}</p>
+                            },
+                            None => html! {},
+                        }
+                    }
                 </div>
 
                 <div class="md:col-span-2">
@@ -159,22 +427,13 @@ pub fn search_filter(props: &SearchFilterProps) -> Html {
                 </div>
 
                 <div class="md:col-span-4">
-                    <label for="tags" class="block text-sm font-medium text-gray-700 dark:text-gray-300 mb-1">
-                        {"Tags (comma separated)"}
-                    </label>
-                    <div class="relative rounded-md shadow-sm">
-                        <div class="absolute inset-y-0 left-0 pl-3 flex items-center pointer-events-none">
-                            <i class="fas fa-tags text-gray-400"></i>
-                        </div>
-                        <input
-                            type="text"
-                            id="tags"
-                            class="focus:ring-blue-500 focus:border-blue-500 block w-full pl-10 py-2 border-2 border-gray-300 dark:border-gray-500 bg-white dark:bg-gray-700 text-gray-700 dark:text-gray-300 rounded-md"
-                            placeholder="e.g. production,etl"
-                            value={(*tags_value).clone()}
-                            oninput={on_tags_input}
-                        />
-                    </div>
+                    <TagInput
+                        selected={selected_tags}
+                        facets={(*tag_facets).clone()}
+                        match_all={query.tags_mode.as_deref() == Some("all")}
+                        on_change={on_tags_change}
+                        {on_match_all_change}
+                    />
                 </div>
 
                 {
@@ -0,0 +1,214 @@
+use crate::models::dag::TagCount;
+use web_sys::HtmlInputElement;
+use yew::prelude::*;
+
+#[derive(Properties, PartialEq)]
+pub struct TagInputProps {
+    /// Tags chosen so far, in the order they were added
+    pub selected: Vec<String>,
+    /// Tags to offer, with their DAG counts
+    pub facets: Vec<TagCount>,
+    /// Whether DAGs need all the selected tags rather than any of them
+    pub match_all: bool,
+    pub on_change: Callback<Vec<String>>,
+    pub on_match_all_change: Callback<bool>,
+}
+
+/// Chips for the selected tags, with a type-ahead list of the tags that exist
+#[function_component(TagInput)]
+pub fn tag_input(props: &TagInputProps) -> Html {
+    let text = use_state(String::new);
+    let open = use_state(|| false);
+    let input_ref = use_node_ref();
+
+    let is_selected = |tag: &str| props.selected.iter().any(|chosen| chosen.eq_ignore_ascii_case(tag));
+    let needle = text.trim().to_lowercase();
+    let options: Vec<&TagCount> = props
+        .facets
+        .iter()
+        .filter(|facet| !is_selected(&facet.tag) && facet.tag.to_lowercase().contains(&needle))
+        .collect();
+
+    let add = {
+        let selected = props.selected.clone();
+        let on_change = props.on_change.clone();
+        let text = text.clone();
+        move |tag: String| {
+            let tag = tag.trim().to_string();
+            if !tag.is_empty() && !selected.iter().any(|chosen| chosen.eq_ignore_ascii_case(&tag)) {
+                let mut tags = selected.clone();
+                tags.push(tag);
+                on_change.emit(tags);
+            }
+            text.set(String::new());
+        }
+    };
+
+    let remove = {
+        let selected = props.selected.clone();
+        let on_change = props.on_change.clone();
+        move |index: usize| {
+            let mut tags = selected.clone();
+            tags.remove(index);
+            on_change.emit(tags);
+        }
+    };
+
+    let oninput = {
+        let text = text.clone();
+        let open = open.clone();
+        Callback::from(move |e: InputEvent| {
+            let input: HtmlInputElement = e.target_unchecked_into();
+            text.set(input.value());
+            open.set(true);
+        })
+    };
+
+    // Enter takes the first offered tag, or the text as typed; Backspace in an empty box drops the last chip
+    let onkeydown = {
+        let text = text.clone();
+        let open = open.clone();
+        let first_option = options.first().map(|facet| facet.tag.clone());
+        let add = add.clone();
+        let remove = remove.clone();
+        let selected_count = props.selected.len();
+        Callback::from(move |e: KeyboardEvent| match e.key().as_str() {
+            "Enter" | "," => {
+                e.prevent_default();
+                if !text.trim().is_empty() {
+                    add(first_option.clone().unwrap_or_else(|| (*text).clone()));
+                }
+            }
+            "Backspace" if text.is_empty() && selected_count > 0 => remove(selected_count - 1),
+            "Escape" => open.set(false),
+            _ => {}
+        })
+    };
+
+    let onfocus = {
+        let open = open.clone();
+        Callback::from(move |_: FocusEvent| open.set(true))
+    };
+
+    let onblur = {
+        let open = open.clone();
+        Callback::from(move |_: FocusEvent| open.set(false))
+    };
+
+    let focus_input = {
+        let input_ref = input_ref.clone();
+        Callback::from(move |_: MouseEvent| {
+            if let Some(input) = input_ref.cast::<HtmlInputElement>() {
+                let _ = input.focus();
+            }
+        })
+    };
+
+    let mode_button = |match_all: bool, label: &'static str| {
+        let on_match_all_change = props.on_match_all_change.clone();
+        let active = props.match_all == match_all;
+        html! {
+            <button
+                type="button"
+                class={classes!(
+                    "px-2", "rounded",
+                    if active {
+                        "bg-blue-600 text-white"
+                    } else {
+                        "text-gray-500 dark:text-gray-400 hover:text-blue-600"
+                    }
+                )}
+                title={if match_all { "DAGs need every tag" } else { "DAGs need one of the tags" }}
+                onclick={Callback::from(move |_: MouseEvent| on_match_all_change.emit(match_all))}
+            >
+                {label}
+            </button>
+        }
+    };
+
+    html! {
+        <div>
+            <div class="flex justify-between items-center mb-1">
+                <label for="tags" class="block text-sm font-medium text-gray-700 dark:text-gray-300">
+                    {"Tags"}
+                </label>
+                <div class="flex text-xs">
+                    {mode_button(false, "any")}
+                    {mode_button(true, "all")}
+                </div>
+            </div>
+            <div class="relative">
+                <div
+                    class="flex flex-wrap items-center gap-1 min-h-[2.75rem] px-3 py-1 border-2 border-gray-300 dark:border-gray-500 bg-white dark:bg-gray-700 text-gray-700 dark:text-gray-300 rounded-md shadow-sm cursor-text"
+                    onclick={focus_input}
+                >
+                    <i class="fas fa-tags text-gray-400 mr-1"></i>
+                    {
+                        for props.selected.iter().enumerate().map(|(index, tag)| {
+                            let remove = remove.clone();
+                            html! {
+                                <span class="tag inline-flex items-center">
+                                    {tag}
+                                    <button
+                                        type="button"
+                                        class="ml-1 text-gray-500 hover:text-red-600"
+                                        title={format!("Remove {}", tag)}
+                                        onclick={Callback::from(move |e: MouseEvent| {
+                                            e.stop_propagation();
+                                            remove(index);
+                                        })}
+                                    >
+                                        {"×"}
+                                    </button>
+                                </span>
+                            }
+                        })
+                    }
+                    <input
+                        type="text"
+                        id="tags"
+                        ref={input_ref}
+                        class="flex-1 min-w-[5rem] border-0 p-0 focus:ring-0 bg-transparent text-sm"
+                        placeholder={if props.selected.is_empty() { "Add tags" } else { "" }}
+                        autocomplete="off"
+                        value={(*text).clone()}
+                        {oninput}
+                        {onkeydown}
+                        {onfocus}
+                        {onblur}
+                    />
+                </div>
+                {
+                    if *open && !options.is_empty() {
+                        html! {
+                            <ul class="absolute z-20 left-0 right-0 mt-1 max-h-60 overflow-y-auto bg-white dark:bg-gray-800 border border-gray-200 dark:border-gray-600 rounded-md shadow-lg text-sm">
+                                {
+                                    for options.iter().map(|facet| {
+                                        let add = add.clone();
+                                        let tag = facet.tag.clone();
+                                        // mousedown, so the tag is added before the input's blur closes the list
+                                        let onmousedown = Callback::from(move |e: MouseEvent| {
+                                            e.prevent_default();
+                                            add(tag.clone());
+                                        });
+                                        html! {
+                                            <li
+                                                class="flex justify-between px-3 py-1 cursor-pointer text-gray-700 dark:text-gray-200 hover:bg-gray-100 dark:hover:bg-gray-700"
+                                                {onmousedown}
+                                            >
+                                                <span>{&facet.tag}</span>
+                                                <span class="text-xs text-gray-400">{facet.count}</span>
+                                            </li>
+                                        }
+                                    })
+                                }
+                            </ul>
+                        }
+                    } else {
+                        html! {}
+                    }
+                }
+            </div>
+        </div>
+    }
+}
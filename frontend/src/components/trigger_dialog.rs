@@ -0,0 +1,386 @@
+use crate::models::dag::{DagRun, DAG};
+use crate::utils::api::trigger_dag_run;
+use chrono::{NaiveDateTime, TimeZone, Utc};
+use serde_json::{Map, Value};
+use wasm_bindgen_futures::spawn_local;
+use web_sys::{HtmlInputElement, HtmlSelectElement, HtmlTextAreaElement};
+use yew::prelude::*;
+
+#[derive(Properties, PartialEq)]
+pub struct TriggerDialogProps {
+    pub dag: DAG,
+    pub on_close: Callback<()>,
+    pub on_triggered: Callback<DagRun>,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum EditorMode {
+    Form,
+    Json,
+}
+
+/// Starting `conf`: the top-level defaults declared by the DAG's params schema
+fn schema_defaults(schema: Option<&Value>) -> Map<String, Value> {
+    schema
+        .and_then(|schema| schema.get("properties"))
+        .and_then(Value::as_object)
+        .map(|properties| {
+            properties
+                .iter()
+                .filter_map(|(name, property)| {
+                    property.get("default").map(|default| (name.clone(), default.clone()))
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn pretty(conf: &Map<String, Value>) -> String {
+    serde_json::to_string_pretty(conf).unwrap_or_else(|_| "{}".to_string())
+}
+
+/// Value of a form field, typed after the schema property it edits. Empty input removes the key.
+fn field_value(property: &Value, raw: &str) -> Option<Result<Value, String>> {
+    if raw.is_empty() {
+        return None;
+    }
+    let parsed = match property.get("type").and_then(Value::as_str) {
+        Some("integer") => raw
+            .parse::<i64>()
+            .map(Value::from)
+            .map_err(|_| format!("'{}' is not an integer", raw)),
+        Some("number") => raw
+            .parse::<f64>()
+            .map(Value::from)
+            .map_err(|_| format!("'{}' is not a number", raw)),
+        Some("string") => Ok(Value::String(raw.to_string())),
+        _ => serde_json::from_str(raw).map_err(|e| format!("invalid JSON: {}", e)),
+    };
+    Some(parsed)
+}
+
+/// Text shown in a form field for the current value
+fn field_text(value: Option<&Value>) -> String {
+    match value {
+        None | Some(Value::Null) => String::new(),
+        Some(Value::String(text)) => text.clone(),
+        Some(other) => other.to_string(),
+    }
+}
+
+/// Dialog that creates a manual run, with the run's `conf` edited either through a form built
+/// from the DAG's params schema or as raw JSON
+#[function_component(TriggerDialog)]
+pub fn trigger_dialog(props: &TriggerDialogProps) -> Html {
+    let schema = props.dag.params_schema.clone();
+    let properties: Vec<(String, Value)> = schema
+        .as_ref()
+        .and_then(|schema| schema.get("properties"))
+        .and_then(Value::as_object)
+        .map(|properties| properties.iter().map(|(k, v)| (k.clone(), v.clone())).collect())
+        .unwrap_or_default();
+    let required: Vec<String> = schema
+        .as_ref()
+        .and_then(|schema| schema.get("required"))
+        .and_then(Value::as_array)
+        .map(|names| names.iter().filter_map(|n| n.as_str().map(str::to_string)).collect())
+        .unwrap_or_default();
+
+    let conf = {
+        let schema = schema.clone();
+        use_state(move || schema_defaults(schema.as_ref()))
+    };
+    let json_text = {
+        let conf = conf.clone();
+        use_state(move || pretty(&conf))
+    };
+    let mode = use_state(|| {
+        if properties.is_empty() {
+            EditorMode::Json
+        } else {
+            EditorMode::Form
+        }
+    });
+    let logical_date = use_state(String::new);
+    // Local parse errors block submitting; `error` holds what the server or date parsing reported
+    let input_error = use_state(|| None::<String>);
+    let error = use_state(|| None::<String>);
+    let submitting = use_state(|| false);
+
+    // A form edit rewrites the JSON text, so both views always show the same conf
+    let set_field = {
+        let conf = conf.clone();
+        let json_text = json_text.clone();
+        let input_error = input_error.clone();
+        Callback::from(move |(name, value): (String, Option<Result<Value, String>>)| {
+            let mut next = (*conf).clone();
+            match value {
+                Some(Ok(value)) => {
+                    next.insert(name, value);
+                }
+                Some(Err(err)) => {
+                    input_error.set(Some(format!("{}: {}", name, err)));
+                    return;
+                }
+                None => {
+                    next.remove(&name);
+                }
+            }
+            input_error.set(None);
+            json_text.set(pretty(&next));
+            conf.set(next);
+        })
+    };
+
+    let on_json_input = {
+        let conf = conf.clone();
+        let json_text = json_text.clone();
+        let input_error = input_error.clone();
+        Callback::from(move |e: InputEvent| {
+            let textarea: HtmlTextAreaElement = e.target_unchecked_into();
+            let text = textarea.value();
+            match serde_json::from_str::<Value>(&text) {
+                Ok(Value::Object(parsed)) => {
+                    conf.set(parsed);
+                    input_error.set(None);
+                }
+                Ok(_) => input_error.set(Some("conf must be a JSON object".to_string())),
+                Err(err) => input_error.set(Some(format!("Invalid JSON: {}", err))),
+            }
+            json_text.set(text);
+        })
+    };
+
+    let on_logical_date = {
+        let logical_date = logical_date.clone();
+        Callback::from(move |e: InputEvent| {
+            let input: HtmlInputElement = e.target_unchecked_into();
+            logical_date.set(input.value());
+        })
+    };
+
+    let set_mode = |target: EditorMode| {
+        let mode = mode.clone();
+        Callback::from(move |_: MouseEvent| mode.set(target))
+    };
+
+    let on_close = {
+        let on_close = props.on_close.clone();
+        Callback::from(move |_: MouseEvent| on_close.emit(()))
+    };
+
+    let on_submit = {
+        let dag_id = props.dag.dag_id.clone();
+        let conf = conf.clone();
+        let logical_date = logical_date.clone();
+        let error = error.clone();
+        let submitting = submitting.clone();
+        let on_triggered = props.on_triggered.clone();
+        Callback::from(move |_: MouseEvent| {
+            // The datetime-local input has no zone; the dialog labels it as UTC
+            let date = if logical_date.is_empty() {
+                None
+            } else {
+                match NaiveDateTime::parse_from_str(&logical_date, "%Y-%m-%dT%H:%M") {
+                    Ok(naive) => Some(Utc.from_utc_datetime(&naive)),
+                    Err(_) => {
+                        error.set(Some(format!("Invalid logical date '{}'", *logical_date)));
+                        return;
+                    }
+                }
+            };
+
+            let dag_id = dag_id.clone();
+            let conf = (*conf).clone();
+            let error = error.clone();
+            let submitting = submitting.clone();
+            let on_triggered = on_triggered.clone();
+            submitting.set(true);
+            error.set(None);
+            spawn_local(async move {
+                match trigger_dag_run(&dag_id, date, &conf).await {
+                    Ok(run) => on_triggered.emit(run),
+                    Err(err) => error.set(Some(err)),
+                }
+                submitting.set(false);
+            });
+        })
+    };
+
+    let form = html! {
+        <div class="space-y-3">
+            {
+                properties.iter().map(|(name, property)| {
+                    let label = property
+                        .get("title")
+                        .and_then(Value::as_str)
+                        .unwrap_or(name)
+                        .to_string();
+                    let description = property.get("description").and_then(Value::as_str).map(str::to_string);
+                    let is_required = required.contains(name);
+                    let current = conf.get(name);
+
+                    let input = if let Some(options) = property.get("enum").and_then(Value::as_array) {
+                        let set_field = set_field.clone();
+                        let name = name.clone();
+                        let options = options.clone();
+                        let choices = options.clone();
+                        let onchange = Callback::from(move |e: Event| {
+                            let select: HtmlSelectElement = e.target_unchecked_into();
+                            let value = select
+                                .value()
+                                .parse::<usize>()
+                                .ok()
+                                .and_then(|index| choices.get(index).cloned())
+                                .map(Ok);
+                            set_field.emit((name.clone(), value));
+                        });
+                        html! {
+                            <select class="w-full border border-gray-300 rounded px-2 py-1 text-sm dark:bg-gray-800 dark:text-gray-200" {onchange}>
+                                <option value="" selected={current.is_none()}>{"—"}</option>
+                                {
+                                    options.iter().enumerate().map(|(index, option)| html! {
+                                        <option value={index.to_string()} selected={current == Some(option)}>
+                                            {field_text(Some(option))}
+                                        </option>
+                                    }).collect::<Html>()
+                                }
+                            </select>
+                        }
+                    } else if property.get("type").and_then(Value::as_str) == Some("boolean") {
+                        let set_field = set_field.clone();
+                        let name = name.clone();
+                        let onchange = Callback::from(move |e: Event| {
+                            let input: HtmlInputElement = e.target_unchecked_into();
+                            set_field.emit((name.clone(), Some(Ok(Value::Bool(input.checked())))));
+                        });
+                        html! {
+                            <input type="checkbox" checked={current == Some(&Value::Bool(true))} {onchange} />
+                        }
+                    } else {
+                        let set_field = set_field.clone();
+                        let name = name.clone();
+                        let property = property.clone();
+                        let input_type = match property.get("type").and_then(Value::as_str) {
+                            Some("integer") | Some("number") => "number",
+                            _ => "text",
+                        };
+                        let onchange = Callback::from(move |e: Event| {
+                            let input: HtmlInputElement = e.target_unchecked_into();
+                            set_field.emit((name.clone(), field_value(&property, &input.value())));
+                        });
+                        html! {
+                            <input
+                                type={input_type}
+                                class="w-full border border-gray-300 rounded px-2 py-1 text-sm"
+                                value={field_text(current)}
+                                {onchange}
+                            />
+                        }
+                    };
+
+                    html! {
+                        <div key={name.clone()}>
+                            <label class="block text-sm font-medium text-gray-700">
+                                {label}
+                                { if is_required { html! { <span class="text-red-600 ml-1">{"*"}</span> } } else { html! {} } }
+                            </label>
+                            {input}
+                            {
+                                match description {
+                                    Some(description) => html! { <p class="text-xs text-gray-500 mt-1">{description}</p> },
+                                    None => html! {},
+                                }
+                            }
+                        </div>
+                    }
+                }).collect::<Html>()
+            }
+        </div>
+    };
+
+    let json_editor = html! {
+        <textarea
+            class="conf-editor"
+            spellcheck="false"
+            value={(*json_text).clone()}
+            oninput={on_json_input}
+        />
+    };
+
+    let tab_class = |target: EditorMode| {
+        if *mode == target { "conf-tab selected" } else { "conf-tab" }
+    };
+
+    html! {
+        <div class="trigger-dialog-overlay">
+            <div class="trigger-dialog airflow-card bg-white rounded-lg p-4">
+                <div class="flex justify-between items-center mb-3">
+                    <h3 class="text-lg font-semibold text-gray-800">
+                        {format!("Trigger {}", props.dag.dag_id)}
+                    </h3>
+                    <button class="task-details-close" onclick={on_close.clone()} title="Close">
+                        <i class="fas fa-times"></i>
+                    </button>
+                </div>
+
+                <label class="block text-sm font-medium text-gray-700">{"Logical date (UTC)"}</label>
+                <input
+                    type="datetime-local"
+                    class="w-full border border-gray-300 rounded px-2 py-1 text-sm mb-1"
+                    value={(*logical_date).clone()}
+                    oninput={on_logical_date}
+                />
+                <p class="text-xs text-gray-500 mb-3">{"Leave empty to use the current time."}</p>
+
+                <div class="flex items-center mb-2">
+                    <span class="text-sm font-medium text-gray-700 mr-2">{"Run configuration"}</span>
+                    {
+                        if properties.is_empty() {
+                            html! {}
+                        } else {
+                            html! {
+                                <>
+                                    <button class={tab_class(EditorMode::Form)} onclick={set_mode(EditorMode::Form)}>{"Form"}</button>
+                                    <button class={tab_class(EditorMode::Json)} onclick={set_mode(EditorMode::Json)}>{"JSON"}</button>
+                                </>
+                            }
+                        }
+                    }
+                </div>
+                {
+                    match *mode {
+                        EditorMode::Form => form,
+                        EditorMode::Json => json_editor,
+                    }
+                }
+
+                {
+                    if let Some(message) = input_error.as_ref().or(error.as_ref()) {
+                        html! {
+                            <div class="bg-red-50 border border-red-200 text-red-700 px-3 py-2 rounded mt-3 text-sm">
+                                {message}
+                            </div>
+                        }
+                    } else {
+                        html! {}
+                    }
+                }
+
+                <div class="flex justify-end space-x-2 mt-4">
+                    <button class="px-3 py-1 bg-gray-200 hover:bg-gray-300 rounded text-sm" onclick={on_close}>
+                        {"Cancel"}
+                    </button>
+                    <button
+                        class="btn-airflow px-3 py-1 rounded text-sm text-white"
+                        onclick={on_submit}
+                        disabled={*submitting || input_error.is_some()}
+                    >
+                        <i class="fas fa-play mr-1"></i>
+                        { if *submitting { "Triggering..." } else { "Trigger" } }
+                    </button>
+                </div>
+            </div>
+        </div>
+    }
+}
@@ -0,0 +1,499 @@
+use crate::components::table::{Table, TableBody, TableHead};
+use crate::models::user::{ApiTokenInfo, AuthContext, DagGrant, NewUserPayload, Role, UpdateUserPayload, UserInfo};
+use crate::utils::api::{
+    create_token, create_user, delete_user, fetch_tokens, fetch_users, format_datetime, revoke_token, update_user,
+};
+use web_sys::{HtmlInputElement, HtmlSelectElement};
+use yew::prelude::*;
+use yew_hooks::prelude::*;
+
+const INPUT_CLASS: &str =
+    "border border-gray-300 dark:border-gray-600 rounded px-2 py-1 text-sm dark:bg-gray-800 dark:text-gray-200";
+const TH_CLASS: &str = "px-4 py-2 text-left text-xs font-medium text-gray-500 dark:text-gray-400 uppercase";
+
+/// User management for administrators, and the signed-in user's own API tokens
+#[function_component(Users)]
+pub fn users_page() -> Html {
+    let auth_ctx = use_context::<AuthContext>().expect("No auth context found");
+
+    html! {
+        <div class="p-4 space-y-8">
+            {
+                if auth_ctx.is_admin() {
+                    html! { <UserAdmin current={auth_ctx.user().map(|user| user.username.clone())} /> }
+                } else {
+                    html! {}
+                }
+            }
+            {
+                if auth_ctx.user().is_some() {
+                    html! { <ApiTokens /> }
+                } else if !auth_ctx.auth_enabled() {
+                    html! {
+                        <p class="text-sm text-gray-500 dark:text-gray-400">
+                            {"API tokens are available once auth is enabled on the server."}
+                        </p>
+                    }
+                } else {
+                    html! {}
+                }
+            }
+        </div>
+    }
+}
+
+fn text_input(state: &UseStateHandle<String>) -> Callback<InputEvent> {
+    let state = state.clone();
+    Callback::from(move |e: InputEvent| {
+        let input: HtmlInputElement = e.target_unchecked_into();
+        state.set(input.value());
+    })
+}
+
+fn error_banner(error: &Option<String>) -> Html {
+    match error {
+        Some(message) => html! {
+            <div class="bg-red-50 dark:bg-red-900 border border-red-200 dark:border-red-800 text-red-700 dark:text-red-200 px-4 py-2 rounded mb-3 text-sm">
+                {message}
+            </div>
+        },
+        None => html! {},
+    }
+}
+
+#[derive(Properties, PartialEq)]
+struct UserAdminProps {
+    /// The signed-in administrator, who is not offered to delete themselves
+    current: Option<String>,
+}
+
+#[function_component(UserAdmin)]
+fn user_admin(props: &UserAdminProps) -> Html {
+    let users = use_state(Vec::<UserInfo>::new);
+    let error = use_state(|| None::<String>);
+    let username = use_state(String::new);
+    let display_name = use_state(String::new);
+    let password = use_state(String::new);
+    let role = use_state(Role::default);
+
+    let load = {
+        let users = users.clone();
+        let error = error.clone();
+        use_async(async move {
+            match fetch_users().await {
+                Ok(list) => {
+                    users.set(list);
+                    Ok(())
+                }
+                Err(err) => {
+                    error.set(Some(err.clone()));
+                    Err(err)
+                }
+            }
+        })
+    };
+
+    {
+        let load = load.clone();
+        use_effect_with((), move |_| {
+            load.run();
+            || {}
+        });
+    }
+
+    // Runs an update, then reloads the list or shows why it failed
+    let apply = {
+        let load = load.clone();
+        let error = error.clone();
+        Callback::from(move |(username, payload): (String, UpdateUserPayload)| {
+            let load = load.clone();
+            let error = error.clone();
+            wasm_bindgen_futures::spawn_local(async move {
+                match update_user(&username, &payload).await {
+                    Ok(_) => {
+                        error.set(None);
+                        load.run();
+                    }
+                    Err(err) => error.set(Some(err)),
+                }
+            });
+        })
+    };
+
+    let on_delete = {
+        let load = load.clone();
+        let error = error.clone();
+        Callback::from(move |username: String| {
+            let confirmed = gloo::utils::window()
+                .confirm_with_message(&format!("Delete user {} and their API tokens?", username))
+                .unwrap_or(false);
+            if !confirmed {
+                return;
+            }
+            let load = load.clone();
+            let error = error.clone();
+            wasm_bindgen_futures::spawn_local(async move {
+                match delete_user(&username).await {
+                    Ok(()) => {
+                        error.set(None);
+                        load.run();
+                    }
+                    Err(err) => error.set(Some(err)),
+                }
+            });
+        })
+    };
+
+    let on_create = {
+        let (username, display_name, password, role) =
+            (username.clone(), display_name.clone(), password.clone(), role.clone());
+        let load = load.clone();
+        let error = error.clone();
+        Callback::from(move |e: SubmitEvent| {
+            e.prevent_default();
+            let payload = NewUserPayload {
+                username: (*username).trim().to_string(),
+                password: (*password).clone(),
+                display_name: Some(display_name.trim().to_string()).filter(|name| !name.is_empty()),
+                role: *role,
+                grants: Vec::new(),
+            };
+            let (username, display_name, password, role) =
+                (username.clone(), display_name.clone(), password.clone(), role.clone());
+            let load = load.clone();
+            let error = error.clone();
+            wasm_bindgen_futures::spawn_local(async move {
+                match create_user(&payload).await {
+                    Ok(_) => {
+                        username.set(String::new());
+                        display_name.set(String::new());
+                        password.set(String::new());
+                        role.set(Role::default());
+                        error.set(None);
+                        load.run();
+                    }
+                    Err(err) => error.set(Some(err)),
+                }
+            });
+        })
+    };
+
+    let on_role_select = {
+        let role = role.clone();
+        Callback::from(move |e: Event| {
+            let select: HtmlSelectElement = e.target_unchecked_into();
+            role.set(Role::parse(&select.value()).unwrap_or_default());
+        })
+    };
+
+    let rows = users.iter().map(|user| {
+        let is_current = props.current.as_deref() == Some(user.username.as_str());
+        let change_role = {
+            let apply = apply.clone();
+            let (username, current) = (user.username.clone(), user.role);
+            Callback::from(move |e: Event| {
+                let select: HtmlSelectElement = e.target_unchecked_into();
+                if let Some(role) = Role::parse(&select.value()).filter(|role| *role != current) {
+                    apply.emit((username.clone(), UpdateUserPayload { role: Some(role), ..Default::default() }));
+                }
+            })
+        };
+        let edit_grants = {
+            let apply = apply.clone();
+            let error = error.clone();
+            let username = user.username.clone();
+            let current = user.grants.iter().map(ToString::to_string).collect::<Vec<_>>().join(", ");
+            Callback::from(move |_: MouseEvent| {
+                let Some(input) = gloo::utils::window()
+                    .prompt_with_message_and_default(
+                        &format!("Grants for {}, comma separated, e.g. owner:data-team=operator, tag:etl=admin", username),
+                        &current,
+                    )
+                    .ok()
+                    .flatten()
+                else {
+                    return;
+                };
+                let grants = input
+                    .split(',')
+                    .filter(|grant| !grant.trim().is_empty())
+                    .map(DagGrant::parse)
+                    .collect::<Result<Vec<_>, _>>();
+                match grants {
+                    Ok(grants) => {
+                        apply.emit((username.clone(), UpdateUserPayload { grants: Some(grants), ..Default::default() }))
+                    }
+                    Err(err) => error.set(Some(err)),
+                }
+            })
+        };
+        let toggle_disabled = {
+            let apply = apply.clone();
+            let (username, disabled) = (user.username.clone(), user.disabled);
+            Callback::from(move |_: MouseEvent| {
+                apply.emit((username.clone(), UpdateUserPayload { disabled: Some(!disabled), ..Default::default() }))
+            })
+        };
+        let reset_password = {
+            let apply = apply.clone();
+            let username = user.username.clone();
+            Callback::from(move |_: MouseEvent| {
+                let password = gloo::utils::window()
+                    .prompt_with_message(&format!("New password for {}", username))
+                    .ok()
+                    .flatten()
+                    .filter(|password| !password.is_empty());
+                if let Some(password) = password {
+                    apply.emit((username.clone(), UpdateUserPayload { password: Some(password), ..Default::default() }));
+                }
+            })
+        };
+        let delete = {
+            let on_delete = on_delete.clone();
+            let username = user.username.clone();
+            Callback::from(move |_: MouseEvent| on_delete.emit(username.clone()))
+        };
+
+        html! {
+            <tr class="text-sm text-gray-700 dark:text-gray-200">
+                <td class="px-4 py-2 font-mono">{&user.username}</td>
+                <td class="px-4 py-2">{user.display_name.as_deref().unwrap_or("-")}</td>
+                <td class="px-4 py-2">
+                    <select class={INPUT_CLASS} onchange={change_role}>
+                        { for Role::ALL.iter().map(|role| html! {
+                            <option value={role.as_str()} selected={*role == user.role}>{role.as_str()}</option>
+                        }) }
+                    </select>
+                </td>
+                <td class="px-4 py-2 font-mono text-xs">
+                    {
+                        if user.grants.is_empty() {
+                            html! { "-" }
+                        } else {
+                            html! { for user.grants.iter().map(|grant| html! { <div>{grant.to_string()}</div> }) }
+                        }
+                    }
+                </td>
+                <td class="px-4 py-2">{if user.disabled { "Disabled" } else { "Active" }}</td>
+                <td class="px-4 py-2">{format_datetime(user.last_login)}</td>
+                <td class="px-4 py-2 space-x-3 whitespace-nowrap">
+                    <button class="text-blue-600 hover:text-blue-800" onclick={edit_grants}>{"Edit grants"}</button>
+                    <button class="text-blue-600 hover:text-blue-800" onclick={toggle_disabled}>
+                        {if user.disabled { "Enable" } else { "Disable" }}
+                    </button>
+                    <button class="text-blue-600 hover:text-blue-800" onclick={reset_password}>{"Set password"}</button>
+                    {
+                        if is_current {
+                            html! {}
+                        } else {
+                            html! { <button class="text-red-600 hover:text-red-800" onclick={delete}>{"Delete"}</button> }
+                        }
+                    }
+                </td>
+            </tr>
+        }
+    });
+
+    html! {
+        <section>
+            <h2 class="text-xl font-semibold text-gray-800 dark:text-gray-100 mb-3">{"Users"}</h2>
+            { error_banner(&error) }
+            <div class="bg-white dark:bg-gray-900 rounded-lg shadow dark:shadow-gray-800 mb-4">
+                <Table>
+                    <TableHead>
+                        <tr>
+                            <th class={TH_CLASS}>{"Username"}</th>
+                            <th class={TH_CLASS}>{"Name"}</th>
+                            <th class={TH_CLASS}>{"Role"}</th>
+                            <th class={TH_CLASS}>{"Grants"}</th>
+                            <th class={TH_CLASS}>{"Status"}</th>
+                            <th class={TH_CLASS}>{"Last Sign-in"}</th>
+                            <th class={TH_CLASS}>{"Actions"}</th>
+                        </tr>
+                    </TableHead>
+                    <TableBody
+                        loading={load.loading}
+                        empty={users.is_empty()}
+                        col_span={7}
+                        no_data_message={"No users yet".to_string()}
+                    >
+                        { for rows }
+                    </TableBody>
+                </Table>
+            </div>
+            <form onsubmit={on_create} class="flex flex-wrap items-center gap-2">
+                <input class={INPUT_CLASS} placeholder="Username" value={(*username).clone()} oninput={text_input(&username)} />
+                <input class={INPUT_CLASS} placeholder="Display name" value={(*display_name).clone()} oninput={text_input(&display_name)} />
+                <input class={INPUT_CLASS} type="password" autocomplete="new-password" placeholder="Password"
+                    value={(*password).clone()} oninput={text_input(&password)} />
+                <select class={INPUT_CLASS} onchange={on_role_select}>
+                    { for Role::ALL.iter().map(|option| html! {
+                        <option value={option.as_str()} selected={*option == *role}>{option.as_str()}</option>
+                    }) }
+                </select>
+                <button
+                    type="submit"
+                    class="bg-blue-600 hover:bg-blue-700 text-white rounded px-3 py-1 text-sm disabled:opacity-50"
+                    disabled={username.trim().is_empty() || password.is_empty()}
+                >
+                    {"Add user"}
+                </button>
+            </form>
+        </section>
+    }
+}
+
+#[function_component(ApiTokens)]
+fn api_tokens() -> Html {
+    let tokens = use_state(Vec::<ApiTokenInfo>::new);
+    let error = use_state(|| None::<String>);
+    let name = use_state(String::new);
+    let expires_in_days = use_state(String::new);
+    // The secret of the token created last; the server never returns it again
+    let created = use_state(|| None::<String>);
+
+    let load = {
+        let tokens = tokens.clone();
+        let error = error.clone();
+        use_async(async move {
+            match fetch_tokens().await {
+                Ok(list) => {
+                    tokens.set(list);
+                    Ok(())
+                }
+                Err(err) => {
+                    error.set(Some(err.clone()));
+                    Err(err)
+                }
+            }
+        })
+    };
+
+    {
+        let load = load.clone();
+        use_effect_with((), move |_| {
+            load.run();
+            || {}
+        });
+    }
+
+    let on_create = {
+        let (name, expires_in_days, created) = (name.clone(), expires_in_days.clone(), created.clone());
+        let load = load.clone();
+        let error = error.clone();
+        Callback::from(move |e: SubmitEvent| {
+            e.prevent_default();
+            let days = match expires_in_days.trim() {
+                "" => None,
+                days => match days.parse::<u32>() {
+                    Ok(days) => Some(days),
+                    Err(_) => {
+                        error.set(Some("Expiry must be a whole number of days".to_string()));
+                        return;
+                    }
+                },
+            };
+            let token_name = name.trim().to_string();
+            let (name, expires_in_days, created) = (name.clone(), expires_in_days.clone(), created.clone());
+            let load = load.clone();
+            let error = error.clone();
+            wasm_bindgen_futures::spawn_local(async move {
+                match create_token(&token_name, days).await {
+                    Ok(response) => {
+                        created.set(Some(response.token));
+                        name.set(String::new());
+                        expires_in_days.set(String::new());
+                        error.set(None);
+                        load.run();
+                    }
+                    Err(err) => error.set(Some(err)),
+                }
+            });
+        })
+    };
+
+    let rows = tokens.iter().map(|token| {
+        let revoke = {
+            let load = load.clone();
+            let error = error.clone();
+            let id = token.id.clone();
+            Callback::from(move |_: MouseEvent| {
+                let load = load.clone();
+                let error = error.clone();
+                let id = id.clone();
+                wasm_bindgen_futures::spawn_local(async move {
+                    match revoke_token(&id).await {
+                        Ok(()) => load.run(),
+                        Err(err) => error.set(Some(err)),
+                    }
+                });
+            })
+        };
+        html! {
+            <tr class="text-sm text-gray-700 dark:text-gray-200">
+                <td class="px-4 py-2">{&token.name}</td>
+                <td class="px-4 py-2">{format_datetime(Some(token.created_at))}</td>
+                <td class="px-4 py-2">{token.expires_at.map_or_else(|| "Never".to_string(), |at| format_datetime(Some(at)))}</td>
+                <td class="px-4 py-2">{format_datetime(token.last_used)}</td>
+                <td class="px-4 py-2">
+                    <button class="text-red-600 hover:text-red-800" onclick={revoke}>{"Revoke"}</button>
+                </td>
+            </tr>
+        }
+    });
+
+    html! {
+        <section id="tokens">
+            <h2 class="text-xl font-semibold text-gray-800 dark:text-gray-100 mb-1">{"My API tokens"}</h2>
+            <p class="text-sm text-gray-500 dark:text-gray-400 mb-3">
+                {"Send a token as "}<code>{"Authorization: Bearer <token>"}</code>{" to call the API as yourself."}
+            </p>
+            { error_banner(&error) }
+            {
+                if let Some(token) = &*created {
+                    html! {
+                        <div class="bg-green-50 dark:bg-green-900 border border-green-200 dark:border-green-800 text-green-800 dark:text-green-100 px-4 py-2 rounded mb-3 text-sm">
+                            <p>{"Copy the new token now; it will not be shown again:"}</p>
+                            <code class="font-mono break-all select-all">{token}</code>
+                        </div>
+                    }
+                } else {
+                    html! {}
+                }
+            }
+            <div class="bg-white dark:bg-gray-900 rounded-lg shadow dark:shadow-gray-800 mb-4">
+                <Table>
+                    <TableHead>
+                        <tr>
+                            <th class={TH_CLASS}>{"Name"}</th>
+                            <th class={TH_CLASS}>{"Created"}</th>
+                            <th class={TH_CLASS}>{"Expires"}</th>
+                            <th class={TH_CLASS}>{"Last Used"}</th>
+                            <th class={TH_CLASS}></th>
+                        </tr>
+                    </TableHead>
+                    <TableBody
+                        loading={load.loading}
+                        empty={tokens.is_empty()}
+                        col_span={5}
+                        no_data_message={"No API tokens".to_string()}
+                    >
+                        { for rows }
+                    </TableBody>
+                </Table>
+            </div>
+            <form onsubmit={on_create} class="flex flex-wrap items-center gap-2">
+                <input class={INPUT_CLASS} placeholder="Token name, e.g. ci" value={(*name).clone()} oninput={text_input(&name)} />
+                <input class={INPUT_CLASS} placeholder="Expires in days (optional)" value={(*expires_in_days).clone()}
+                    oninput={text_input(&expires_in_days)} />
+                <button
+                    type="submit"
+                    class="bg-blue-600 hover:bg-blue-700 text-white rounded px-3 py-1 text-sm disabled:opacity-50"
+                    disabled={name.trim().is_empty()}
+                >
+                    {"Create token"}
+                </button>
+            </form>
+        </section>
+    }
+}
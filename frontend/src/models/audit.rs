@@ -0,0 +1,46 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// Actions the server records, in the order the filter offers them
+pub const AUDIT_ACTIONS: [&str; 9] = [
+    "pause",
+    "unpause",
+    "trigger",
+    "backfill",
+    "clear",
+    "force-success",
+    "skip",
+    "set-task-status",
+    "config-change",
+];
+
+/// One entry of the audit log
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct AuditEntry {
+    pub id: u64,
+    pub timestamp: DateTime<Utc>,
+    pub actor: String,
+    pub action: String,
+    pub dag_id: String,
+    pub run_id: Option<String>,
+    pub task_id: Option<String>,
+    pub before: Option<Value>,
+    pub after: Option<Value>,
+    pub comment: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+pub struct AuditQuery {
+    pub actor: Option<String>,
+    pub action: Option<String>,
+    pub dag_id: Option<String>,
+    pub page: Option<usize>,
+    pub limit: Option<usize>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct AuditResponse {
+    pub entries: Vec<AuditEntry>,
+    pub total_count: usize,
+}
@@ -1,5 +1,6 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
 use uuid::Uuid;
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -18,6 +19,9 @@ pub struct DAG {
     pub running_count: usize,
     pub schedule_interval: String,
     pub tags: Vec<String>,
+    /// JSON schema for the `conf` of manually triggered runs
+    #[serde(default)]
+    pub params_schema: Option<Value>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
@@ -25,7 +29,22 @@ pub struct DAG {
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct DAGsResponse {
     pub dags: Vec<DAG>,
+    /// DAGs matching the filters, across all pages
     pub total_count: usize,
+    #[serde(default)]
+    pub next_cursor: Option<String>,
+}
+
+/// A tag and how many DAGs carry it under the current search and status filters
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct TagCount {
+    pub tag: String,
+    pub count: usize,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct TagsResponse {
+    pub tags: Vec<TagCount>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
@@ -34,12 +53,19 @@ pub struct DAGsQuery {
     pub limit: Option<usize>,
     pub search: Option<String>,
     pub status: Option<String>,
+    /// Comma-separated tags
     pub tags: Option<String>,
+    /// `any` (the default) or `all` of `tags`
+    pub tags_mode: Option<String>,
+    /// Comma-separated `field:asc|desc` keys, most significant first
     pub sort_by: Option<String>,
     pub sort_order: Option<String>,
+    /// Page after this cursor instead of by `page`
+    pub cursor: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[allow(non_camel_case_types)]
 pub enum TaskStatus {
     PENDING,
     SUCCEEDED,
@@ -48,6 +74,7 @@ pub enum TaskStatus {
     FAILED,
     SKIPPED,
     PAUSED,
+    UP_FOR_RETRY,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -61,6 +88,8 @@ pub struct Task {
     pub operator: String,
     pub retries: usize,
     pub max_retries: usize,
+    #[serde(default)]
+    pub try_number: usize,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -72,11 +101,184 @@ pub struct Edge {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DAGGraph {
     pub dag_id: String,
+    #[serde(default)]
+    pub version: u32,
     pub tasks: Vec<Task>,
     pub edges: Vec<Edge>,
 }
 
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub enum RunState {
+    QUEUED,
+    RUNNING,
+    SUCCEEDED,
+    FAILED,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub enum RunType {
+    SCHEDULED,
+    MANUAL,
+    BACKFILL,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct TaskInstance {
+    pub task_id: String,
+    pub status: TaskStatus,
+    pub try_number: usize,
+    pub start_time: Option<DateTime<Utc>>,
+    pub end_time: Option<DateTime<Utc>>,
+    pub duration: Option<f64>,
+    #[serde(default)]
+    pub exit_code: Option<i32>,
+    #[serde(default)]
+    pub next_retry_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub attempts: Vec<TaskAttempt>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct TaskAttempt {
+    pub try_number: usize,
+    pub status: TaskStatus,
+    pub start_time: Option<DateTime<Utc>>,
+    pub end_time: Option<DateTime<Utc>>,
+    pub duration: Option<f64>,
+    pub exit_code: Option<i32>,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct DagRun {
+    pub run_id: String,
+    pub dag_id: String,
+    pub logical_date: DateTime<Utc>,
+    pub run_type: RunType,
+    pub state: RunState,
+    pub graph_version: u32,
+    pub queued_at: DateTime<Utc>,
+    pub start_date: Option<DateTime<Utc>>,
+    pub end_date: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub conf: Map<String, Value>,
+    /// Who triggered a manual or backfill run
+    #[serde(default)]
+    pub triggered_by: Option<String>,
+    pub task_instances: Vec<TaskInstance>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct LogChunk {
+    pub try_number: usize,
+    pub offset: u64,
+    pub next_offset: u64,
+    pub size: u64,
+    pub content: String,
+}
+
+/// Which tasks a clear reaches besides the selected one
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct ClearOptions {
+    pub upstream: bool,
+    pub downstream: bool,
+    pub recursive: bool,
+    pub failed_only: bool,
+    pub dry_run: bool,
+    /// Why, for the audit log
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub comment: Option<String>,
+}
+
+impl Default for ClearOptions {
+    fn default() -> Self {
+        Self {
+            upstream: false,
+            downstream: true,
+            recursive: true,
+            failed_only: false,
+            dry_run: true,
+            comment: None,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct ClearResponse {
+    pub task_ids: Vec<String>,
+    pub dry_run: bool,
+    pub run: DagRun,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct DagRunsResponse {
+    pub runs: Vec<DagRun>,
+    pub total_count: usize,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+pub struct DagRunsQuery {
+    pub page: Option<usize>,
+    pub limit: Option<usize>,
+    pub state: Option<String>,
+}
+
+/// A change pushed by the server's `/api/events` stream
+#[derive(Debug, Clone, Deserialize, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ServerEvent {
+    DagPaused {
+        dag_id: String,
+        paused: bool,
+    },
+    RunCreated {
+        run: DagRun,
+    },
+    RunStateChanged {
+        dag_id: String,
+        run_id: String,
+        previous: RunState,
+        state: RunState,
+    },
+    TaskStateChanged {
+        dag_id: String,
+        run_id: String,
+        previous: TaskStatus,
+        task: TaskInstance,
+    },
+    LogAppended {
+        dag_id: String,
+        run_id: String,
+        task_id: String,
+        try_number: usize,
+        size: u64,
+    },
+    /// Events were missed; reload whatever is shown
+    Resync {
+        #[serde(default)]
+        missed: u64,
+    },
+}
+
 impl DAG {
+    /// Move the run counters along for a run that was created (`previous` is `None`) or
+    /// changed state
+    pub fn count_run(&mut self, previous: Option<RunState>, state: RunState) {
+        match previous {
+            None => self.runs_count += 1,
+            Some(previous) => match previous {
+                RunState::SUCCEEDED => self.success_count = self.success_count.saturating_sub(1),
+                RunState::FAILED => self.failed_count = self.failed_count.saturating_sub(1),
+                RunState::QUEUED | RunState::RUNNING => self.running_count = self.running_count.saturating_sub(1),
+            },
+        }
+        match state {
+            RunState::SUCCEEDED => self.success_count += 1,
+            RunState::FAILED => self.failed_count += 1,
+            RunState::QUEUED | RunState::RUNNING => self.running_count += 1,
+        }
+    }
+
     pub fn status(&self) -> &'static str {
         if self.paused {
             "paused"
@@ -92,6 +294,18 @@ impl DAG {
     }
 }
 
+impl Task {
+    /// Take over the state of the task's instance in a run, as the server's run graph does
+    pub fn apply_instance(&mut self, instance: &TaskInstance) {
+        self.status = instance.status.clone();
+        self.try_number = instance.try_number;
+        self.retries = instance.try_number.saturating_sub(1);
+        self.start_time = instance.start_time;
+        self.end_time = instance.end_time;
+        self.duration = instance.duration;
+    }
+}
+
 impl TaskStatus {
     pub fn color(&self) -> &'static str {
         match self {
@@ -102,6 +316,7 @@ impl TaskStatus {
             TaskStatus::QUEUED => "#ff9800",     // Orange
             TaskStatus::SKIPPED => "#673ab7",    // Purple
             TaskStatus::PAUSED => "#795548",     // Brown
+            TaskStatus::UP_FOR_RETRY => "#ffc107", // Amber
         }
     }
     
@@ -114,6 +329,37 @@ impl TaskStatus {
             TaskStatus::QUEUED => "Queued",
             TaskStatus::SKIPPED => "Skipped",
             TaskStatus::PAUSED => "Paused",
+            TaskStatus::UP_FOR_RETRY => "Up for retry",
         }
     }
-}
\ No newline at end of file
+}
+
+impl RunState {
+    pub fn color(&self) -> &'static str {
+        match self {
+            RunState::SUCCEEDED => "#4caf50",
+            RunState::FAILED => "#f44336",
+            RunState::RUNNING => "#2196f3",
+            RunState::QUEUED => "#ff9800",
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            RunState::SUCCEEDED => "Succeeded",
+            RunState::FAILED => "Failed",
+            RunState::RUNNING => "Running",
+            RunState::QUEUED => "Queued",
+        }
+    }
+}
+
+impl RunType {
+    pub fn label(&self) -> &'static str {
+        match self {
+            RunType::SCHEDULED => "Scheduled",
+            RunType::MANUAL => "Manual",
+            RunType::BACKFILL => "Backfill",
+        }
+    }
+}
@@ -1,2 +1,5 @@
+pub mod audit;
 pub mod dag;
+pub mod search;
 pub mod theme;
+pub mod user;
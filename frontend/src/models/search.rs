@@ -0,0 +1,182 @@
+use serde::{Deserialize, Serialize};
+
+/// Fields of the DAG search language, as the server accepts them
+pub const SEARCH_FIELDS: [&str; 5] = ["owner", "tag", "status", "schedule", "id"];
+
+pub const DAG_STATUSES: [&str; 5] = ["active", "paused", "success", "failed", "running"];
+
+pub const SCHEDULE_PRESETS: [&str; 5] = ["@hourly", "@daily", "@weekly", "@monthly", "@yearly"];
+
+const OPERATORS: [&str; 2] = ["OR", "NOT"];
+
+/// Where and why the server could not parse a search query; positions count characters
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct QueryError {
+    pub kind: String,
+    pub message: String,
+    pub position: usize,
+    pub length: usize,
+}
+
+/// How a stretch of the query is highlighted
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SearchTokenKind {
+    Space,
+    Text,
+    Quoted,
+    Field,
+    Value,
+    Operator,
+    Paren,
+}
+
+impl SearchTokenKind {
+    pub fn class(&self) -> &'static str {
+        match self {
+            SearchTokenKind::Space | SearchTokenKind::Text => "",
+            SearchTokenKind::Quoted => "text-amber-600 dark:text-amber-400",
+            SearchTokenKind::Field => "text-purple-600 dark:text-purple-400",
+            SearchTokenKind::Value => "text-blue-700 dark:text-blue-300",
+            SearchTokenKind::Operator => "font-semibold text-pink-600 dark:text-pink-400",
+            SearchTokenKind::Paren => "text-gray-400",
+        }
+    }
+}
+
+/// Split a query into highlighted stretches covering every character, following the server's lexer
+pub fn highlight(query: &str) -> Vec<(SearchTokenKind, String)> {
+    let chars: Vec<char> = query.chars().collect();
+    let mut kinds = vec![SearchTokenKind::Space; chars.len()];
+    let mut at = 0;
+    while at < chars.len() {
+        let start = at;
+        match chars[at] {
+            c if c.is_whitespace() => at += 1,
+            '(' | ')' => {
+                kinds[at] = SearchTokenKind::Paren;
+                at += 1;
+            }
+            '-' => {
+                kinds[at] = SearchTokenKind::Operator;
+                at += 1;
+            }
+            '"' => {
+                at = quoted_end(&chars, at);
+                kinds[start..at].fill(SearchTokenKind::Quoted);
+            }
+            _ => {
+                while at < chars.len() && !is_delimiter(chars[at]) {
+                    at += 1;
+                }
+                let word: String = chars[start..at].iter().collect();
+                if OPERATORS.contains(&word.as_str()) || word == "AND" {
+                    kinds[start..at].fill(SearchTokenKind::Operator);
+                } else if let Some(colon) = word.find(':') {
+                    let field_end = start + word[..colon].chars().count() + 1;
+                    kinds[start..field_end].fill(SearchTokenKind::Field);
+                    kinds[field_end..at].fill(SearchTokenKind::Value);
+                    if field_end == at && chars.get(at) == Some(&'"') {
+                        let value_start = at;
+                        at = quoted_end(&chars, at);
+                        kinds[value_start..at].fill(SearchTokenKind::Value);
+                    }
+                } else {
+                    kinds[start..at].fill(SearchTokenKind::Text);
+                }
+            }
+        }
+    }
+
+    let mut spans: Vec<(SearchTokenKind, String)> = Vec::new();
+    for (c, kind) in chars.into_iter().zip(kinds) {
+        match spans.last_mut() {
+            Some((last, text)) if *last == kind => text.push(c),
+            _ => spans.push((kind, c.to_string())),
+        }
+    }
+    spans
+}
+
+fn is_delimiter(c: char) -> bool {
+    c.is_whitespace() || matches!(c, '(' | ')' | '"')
+}
+
+/// End of a `"..."` string starting at `at`; the end of the query when it is unterminated
+fn quoted_end(chars: &[char], at: usize) -> usize {
+    chars[at + 1..]
+        .iter()
+        .position(|c| *c == '"')
+        .map_or(chars.len(), |end| at + 1 + end + 1)
+}
+
+/// A completion for the word before the cursor
+#[derive(Debug, Clone, PartialEq)]
+pub struct Suggestion {
+    /// Shown in the list and inserted in place of the word
+    pub text: String,
+    pub hint: &'static str,
+}
+
+/// Start of the word ending at `cursor`, and the completions for it. `tags` are offered after
+/// `tag:`.
+pub fn suggest(query: &str, cursor: usize, tags: &[String]) -> (usize, Vec<Suggestion>) {
+    let chars: Vec<char> = query.chars().collect();
+    let cursor = cursor.min(chars.len());
+    let mut start = cursor;
+    while start > 0 && !is_delimiter(chars[start - 1]) {
+        start -= 1;
+    }
+    // A leading `-` negates the word rather than being part of it
+    if start < cursor && chars[start] == '-' {
+        start += 1;
+    }
+    let word: String = chars[start..cursor].iter().collect();
+    if word.is_empty() {
+        return (start, vec![]);
+    }
+
+    let suggestions = match word.split_once(':') {
+        Some((field, prefix)) => {
+            let (values, hint): (Vec<String>, &'static str) = match field {
+                "status" => (DAG_STATUSES.iter().map(|s| s.to_string()).collect(), "status"),
+                "schedule" => (SCHEDULE_PRESETS.iter().map(|s| s.to_string()).collect(), "schedule"),
+                "tag" => (tags.to_vec(), "tag"),
+                _ => (vec![], ""),
+            };
+            values
+                .into_iter()
+                .filter(|value| value.to_lowercase().starts_with(&prefix.to_lowercase()) && *value != prefix)
+                .map(|value| Suggestion {
+                    text: format!("{}:{}", field, quote_if_needed(&value)),
+                    hint,
+                })
+                .collect()
+        }
+        None => {
+            let fields = SEARCH_FIELDS
+                .iter()
+                .filter(|field| field.starts_with(&word.to_lowercase()))
+                .map(|field| Suggestion {
+                    text: format!("{}:", field),
+                    hint: "field",
+                });
+            let operators = OPERATORS
+                .iter()
+                .filter(|op| op.starts_with(word.as_str()) && **op != word)
+                .map(|op| Suggestion {
+                    text: op.to_string(),
+                    hint: "operator",
+                });
+            fields.chain(operators).collect()
+        }
+    };
+    (start, suggestions)
+}
+
+fn quote_if_needed(value: &str) -> String {
+    if value.chars().any(is_delimiter) {
+        format!("\"{}\"", value)
+    } else {
+        value.to_string()
+    }
+}
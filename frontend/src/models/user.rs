@@ -0,0 +1,204 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use yew::prelude::*;
+
+/// What a user may do: viewers read, operators also pause, trigger and change task states,
+/// admins also edit DAGs and manage users
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "lowercase")]
+pub enum Role {
+    #[default]
+    Viewer,
+    Operator,
+    Admin,
+}
+
+impl Role {
+    pub const ALL: [Role; 3] = [Role::Viewer, Role::Operator, Role::Admin];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Role::Viewer => "viewer",
+            Role::Operator => "operator",
+            Role::Admin => "admin",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Role> {
+        Role::ALL.into_iter().find(|role| role.as_str() == value.trim())
+    }
+}
+
+/// The DAGs a grant applies to
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum GrantScope {
+    Owner(String),
+    Tag(String),
+}
+
+/// A role on the DAGs with a given owner or tag, on top of the user's own role
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct DagGrant {
+    #[serde(flatten)]
+    pub scope: GrantScope,
+    pub role: Role,
+}
+
+impl DagGrant {
+    pub fn matches(&self, owner: &str, tags: &[String]) -> bool {
+        match &self.scope {
+            GrantScope::Owner(grant_owner) => grant_owner == owner,
+            GrantScope::Tag(tag) => tags.iter().any(|dag_tag| dag_tag.eq_ignore_ascii_case(tag)),
+        }
+    }
+
+    /// Parses `owner:<owner>=<role>` or `tag:<tag>=<role>`
+    pub fn parse(value: &str) -> Result<DagGrant, String> {
+        let invalid = || format!("invalid grant '{}', expected owner:<owner>=<role> or tag:<tag>=<role>", value);
+        let (target, role) = value.trim().rsplit_once('=').ok_or_else(invalid)?;
+        let (kind, name) = target.split_once(':').ok_or_else(invalid)?;
+        if name.is_empty() {
+            return Err(invalid());
+        }
+        let scope = match kind {
+            "owner" => GrantScope::Owner(name.to_string()),
+            "tag" => GrantScope::Tag(name.to_string()),
+            _ => return Err(invalid()),
+        };
+        let role = Role::parse(role).ok_or_else(invalid)?;
+        Ok(DagGrant { scope, role })
+    }
+}
+
+impl fmt::Display for DagGrant {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.scope {
+            GrantScope::Owner(owner) => write!(f, "owner:{}={}", owner, self.role.as_str()),
+            GrantScope::Tag(tag) => write!(f, "tag:{}={}", tag, self.role.as_str()),
+        }
+    }
+}
+
+/// A user account as returned by the API
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct UserInfo {
+    pub username: String,
+    pub display_name: Option<String>,
+    pub role: Role,
+    #[serde(default)]
+    pub grants: Vec<DagGrant>,
+    pub disabled: bool,
+    pub created_at: DateTime<Utc>,
+    pub last_login: Option<DateTime<Utc>>,
+}
+
+impl UserInfo {
+    /// Display name, falling back to the username
+    pub fn name(&self) -> &str {
+        self.display_name.as_deref().unwrap_or(&self.username)
+    }
+}
+
+/// Response of `GET /api/auth/me`
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct AuthStatus {
+    pub auth_enabled: bool,
+    pub user: Option<UserInfo>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct ApiTokenInfo {
+    pub id: String,
+    pub name: String,
+    pub created_at: DateTime<Utc>,
+    pub expires_at: Option<DateTime<Utc>>,
+    pub last_used: Option<DateTime<Utc>>,
+}
+
+/// A freshly created API token; `token` is only ever returned here
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct NewTokenResponse {
+    pub token: String,
+    #[serde(flatten)]
+    pub info: ApiTokenInfo,
+}
+
+#[derive(Debug, Serialize, Clone, Default, PartialEq)]
+pub struct NewUserPayload {
+    pub username: String,
+    pub password: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub display_name: Option<String>,
+    pub role: Role,
+    pub grants: Vec<DagGrant>,
+}
+
+/// Changes to a user; fields left `None` are not touched
+#[derive(Debug, Serialize, Clone, Default, PartialEq)]
+pub struct UpdateUserPayload {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub display_name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub password: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub role: Option<Role>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub grants: Option<Vec<DagGrant>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub disabled: Option<bool>,
+}
+
+// Auth context for global access; `status` is `None` until the server has answered
+#[derive(Clone, Debug, PartialEq)]
+pub struct AuthContext {
+    pub status: Option<AuthStatus>,
+    pub set_status: Callback<AuthStatus>,
+}
+
+impl AuthContext {
+    pub fn new(status: Option<AuthStatus>, set_status: Callback<AuthStatus>) -> Self {
+        Self { status, set_status }
+    }
+
+    pub fn user(&self) -> Option<&UserInfo> {
+        self.status.as_ref().and_then(|status| status.user.as_ref())
+    }
+
+    pub fn auth_enabled(&self) -> bool {
+        self.status.as_ref().is_some_and(|status| status.auth_enabled)
+    }
+
+    /// The current visitor's role on a DAG with this owner and tags: their own role raised by
+    /// any matching grant. Everyone is an admin when auth is disabled; nobody is anything until
+    /// the server has answered.
+    pub fn role_on(&self, owner: &str, tags: &[String]) -> Option<Role> {
+        match &self.status {
+            Some(status) if status.auth_enabled => status.user.as_ref().map(|user| {
+                user.grants
+                    .iter()
+                    .filter(|grant| grant.matches(owner, tags))
+                    .map(|grant| grant.role)
+                    .fold(user.role, Role::max)
+            }),
+            Some(_) => Some(Role::Admin),
+            None => None,
+        }
+    }
+
+    /// Whether the current visitor may pause, trigger and change task states on a DAG
+    pub fn can_operate(&self, owner: &str, tags: &[String]) -> bool {
+        self.role_on(owner, tags).is_some_and(|role| role >= Role::Operator)
+    }
+
+    /// Whether user management is open to the current visitor: any admin, or anyone when auth
+    /// is disabled
+    pub fn is_admin(&self) -> bool {
+        match &self.status {
+            Some(status) if status.auth_enabled => status.user.as_ref().is_some_and(|user| user.role == Role::Admin),
+            Some(_) => true,
+            None => false,
+        }
+    }
+}
@@ -20,6 +20,9 @@ pub type RowSelectionCallback = Callback<Vec<JsValue>>;
 /// Callback type for pagination changed events
 pub type PaginationChangedCallback = Callback<GridPaginationEvent>;
 
+/// Callback type for sort changed events
+pub type SortChangedCallback = Callback<GridSortEvent>;
+
 /// Sorting direction for a column
 #[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
 pub enum SortDirection {
@@ -214,6 +217,13 @@ pub struct GridPaginationEvent {
     pub row_count: usize,
 }
 
+/// Event data for sort changes
+#[derive(Clone, Debug)]
+pub struct GridSortEvent {
+    /// Sorted columns and their direction, most significant first (shift-click adds a column)
+    pub columns: Vec<(String, SortDirection)>,
+}
+
 /// Properties for the AG-Grid component
 #[derive(Properties, PartialEq)]
 pub struct AgGridProps {
@@ -261,6 +271,9 @@ pub struct AgGridProps {
     /// Callback for pagination changes
     #[prop_or_default]
     pub on_pagination_changed: Option<PaginationChangedCallback>,
+    /// Callback for sort changes
+    #[prop_or_default]
+    pub on_sort_changed: Option<SortChangedCallback>,
     /// Callback when grid is ready
     #[prop_or_default]
     pub on_grid_ready: Option<Callback<JsValue>>,
@@ -297,6 +310,7 @@ pub fn ag_grid(props: &AgGridProps) -> Html {
         on_cell_clicked: props.on_cell_clicked.clone(),
         on_selection_changed: props.on_selection_changed.clone(),
         on_pagination_changed: props.on_pagination_changed.clone(),
+        on_sort_changed: props.on_sort_changed.clone(),
         on_grid_ready: props.on_grid_ready.clone(),
     };
     
@@ -352,6 +366,7 @@ struct GridOptions {
     on_cell_clicked: Option<CellClickedCallback>,
     on_selection_changed: Option<RowSelectionCallback>,
     on_pagination_changed: Option<PaginationChangedCallback>,
+    on_sort_changed: Option<SortChangedCallback>,
     on_grid_ready: Option<Callback<JsValue>>,
 }
 
@@ -534,6 +549,22 @@ fn init_grid(container_id: &str, grid_options: &GridOptions, grid_api: UseStateH
         handler.forget();
     }
     
+    // Setup sort changed handler
+    if let Some(on_sort_changed) = &grid_options.on_sort_changed {
+        let callback = on_sort_changed.clone();
+        let handler = Closure::wrap(Box::new(move |params: JsValue| {
+            handle_sort_changed(params, &callback);
+        }) as Box<dyn FnMut(JsValue)>);
+        
+        let _ = Reflect::set(
+            &js_grid_options,
+            &JsValue::from_str("onSortChanged"),
+            &handler.as_ref().unchecked_ref(),
+        );
+        
+        handler.forget();
+    }
+    
     // Setup grid ready handler
     let api_state = grid_api.clone();
     let on_grid_ready_opt = grid_options.on_grid_ready.clone();
@@ -777,6 +808,47 @@ fn handle_pagination_changed(params: JsValue, callback: &PaginationChangedCallba
     }
 }
 
+/// Column state as reported by `getColumnState`
+#[derive(Deserialize)]
+struct ColumnSortState {
+    #[serde(rename = "colId")]
+    col_id: String,
+    sort: Option<SortDirection>,
+    #[serde(rename = "sortIndex")]
+    sort_index: Option<usize>,
+}
+
+fn handle_sort_changed(params: JsValue, callback: &SortChangedCallback) {
+    // ag-Grid 30 keeps column state on the column API, later versions on the grid API
+    let column_api = Reflect::get(&params, &JsValue::from_str("columnApi"))
+        .ok()
+        .filter(|api| api.is_object())
+        .or_else(|| Reflect::get(&params, &JsValue::from_str("api")).ok())
+        .unwrap_or(JsValue::NULL);
+    
+    let get_column_state = Reflect::get(&column_api, &JsValue::from_str("getColumnState"))
+        .unwrap_or(JsValue::NULL);
+    if !get_column_state.is_function() {
+        return;
+    }
+    
+    let state = Reflect::apply(&Function::from(get_column_state), &column_api, &Array::new())
+        .unwrap_or(JsValue::NULL);
+    let mut sorted: Vec<(usize, String, SortDirection)> = from_value::<Vec<ColumnSortState>>(state)
+        .unwrap_or_default()
+        .into_iter()
+        .filter_map(|column| {
+            let sort = column.sort?;
+            Some((column.sort_index.unwrap_or(0), column.col_id, sort))
+        })
+        .collect();
+    sorted.sort_by_key(|(index, _, _)| *index);
+    
+    callback.emit(GridSortEvent {
+        columns: sorted.into_iter().map(|(_, column, sort)| (column, sort)).collect(),
+    });
+}
+
 fn handle_grid_ready(params: JsValue, grid_api: &UseStateHandle<Option<JsValue>>, callback: &Option<Callback<JsValue>>) {
     let api = Reflect::get(&params, &JsValue::from_str("api"))
         .unwrap_or(JsValue::NULL);
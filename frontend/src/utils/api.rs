@@ -1,13 +1,16 @@
-use crate::models::dag::{DAGsQuery, DAGsResponse, DAGGraph};
+use crate::models::dag::{
+    ClearOptions, ClearResponse, DAG, DAGsQuery, DAGsResponse, DAGGraph, DagRun, DagRunsQuery, DagRunsResponse,
+    LogChunk, TagCount, TagsResponse,
+};
+use crate::models::audit::{AuditQuery, AuditResponse};
+use crate::models::search::QueryError;
+use crate::models::user::{ApiTokenInfo, AuthStatus, NewTokenResponse, NewUserPayload, UpdateUserPayload, UserInfo};
 use gloo::net::http::Request;
 use serde::Serialize;
 
 const API_BASE_URL: &str = "/api";
 
-pub async fn fetch_dags(query: &DAGsQuery) -> Result<DAGsResponse, String> {
-    let url = format!("{}/dags", API_BASE_URL);
-
-    // Build the query params
+pub async fn fetch_dags(query: &DAGsQuery) -> Result<DAGsResponse, ErrorBody> {
     let mut params = vec![];
 
     if let Some(page) = query.page {
@@ -18,117 +21,164 @@ pub async fn fetch_dags(query: &DAGsQuery) -> Result<DAGsResponse, String> {
         params.push(format!("limit={}", limit));
     }
 
-    if let Some(search) = &query.search {
-        params.push(format!("search={}", search));
+    for (name, value) in [
+        ("search", &query.search),
+        ("status", &query.status),
+        ("tags", &query.tags),
+        ("tags_mode", &query.tags_mode),
+        ("sort_by", &query.sort_by),
+        ("sort_order", &query.sort_order),
+        ("cursor", &query.cursor),
+    ] {
+        if let Some(value) = value {
+            params.push(format!("{}={}", name, js_sys::encode_uri_component(value)));
+        }
     }
 
-    if let Some(status) = &query.status {
-        params.push(format!("status={}", status));
-    }
+    let url = format!("{}/dags", API_BASE_URL);
+    let url = if params.is_empty() {
+        url
+    } else {
+        format!("{}?{}", url, params.join("&"))
+    };
 
-    if let Some(tags) = &query.tags {
-        params.push(format!("tags={}", tags));
-    }
+    let response = Request::get(&url)
+        .send()
+        .await
+        .map_err(|e| format!("Failed to fetch DAGs: {:?}", e))?;
 
-    if let Some(sort_by) = &query.sort_by {
-        params.push(format!("sort_by={}", sort_by));
+    if !response.ok() {
+        return Err(error_body(response).await);
     }
 
-    if let Some(sort_order) = &query.sort_order {
-        params.push(format!("sort_order={}", sort_order));
+    response
+        .json::<DAGsResponse>()
+        .await
+        .map_err(|e| format!("Failed to parse response: {:?}", e).into())
+}
+
+/// Tag counts among the DAGs matching the query's search and status; its tags are ignored
+pub async fn fetch_tags(query: &DAGsQuery) -> Result<Vec<TagCount>, String> {
+    let mut params = vec![];
+
+    for (name, value) in [("search", &query.search), ("status", &query.status)] {
+        if let Some(value) = value {
+            params.push(format!("{}={}", name, js_sys::encode_uri_component(value)));
+        }
     }
 
+    let url = format!("{}/tags", API_BASE_URL);
     let url = if params.is_empty() {
         url
     } else {
         format!("{}?{}", url, params.join("&"))
     };
 
-    // For development: return mock data instead of making actual API call
-    // In production, you would use the commented out code to make real API calls
-    /*
     let response = Request::get(&url)
         .send()
         .await
-        .map_err(|e| format!("Failed to fetch DAGs: {:?}", e))?;
+        .map_err(|e| format!("Failed to fetch tags: {:?}", e))?;
 
     if !response.ok() {
-        return Err(format!("API error: {}", response.status()));
+        return Err(api_error(response).await);
     }
 
     response
-        .json::<DAGsResponse>()
+        .json::<TagsResponse>()
         .await
-        .map_err(|e| format!("Failed to parse response: {:?}", e))
-    */
-        
-    // Mock data for development
-    use crate::models::dag::DAG;
-    use chrono::Utc;
-    use uuid::Uuid;
-    
-    // Create 10 mock DAGs
-    let mut dags = Vec::new();
-    
-    for i in 1..11 {
-        let paused = i % 3 == 0;
-        let running = i % 5 == 0;
-        let failed = i % 7 == 0;
-        let now = Utc::now();
-        
-        dags.push(DAG {
-            id: uuid::Uuid::new_v4(),
-            dag_id: format!("example_dag_{}", i),
-            description: Some(format!("Example DAG {}", i)),
-            file_path: format!("/opt/airflow/dags/example_dag_{}.py", i),
-            owner: "airflow".to_string(),
-            paused,
-            schedule_interval: "0 0 * * *".to_string(),
-            last_run: Some(now),
-            next_run: Some(now),
-            tags: vec!["example".to_string(), format!("tag_{}", i)],
-            runs_count: i * 10,
-            success_count: i * 8,
-            failed_count: if failed { 2 } else { 0 },
-            running_count: if running { 1 } else { 0 },
-            created_at: now,
-            updated_at: now,
-        });
-    }
-    
-    // Apply paging
-    let page = query.page.unwrap_or(1);
-    let limit = query.limit.unwrap_or(10);
-    let total_count = dags.len();
-    
-    let start = (page - 1) * limit;
-    let end = std::cmp::min(start + limit, dags.len());
-    
-    let paged_dags = if start < dags.len() {
-        dags[start..end].to_vec()
-    } else {
-        Vec::new()
-    };
-    
-    Ok(DAGsResponse {
-        dags: paged_dags,
-        total_count,
-    })
+        .map(|response| response.tags)
+        .map_err(|e| format!("Failed to parse tags response: {:?}", e))
 }
 
-pub async fn toggle_dag_paused(dag_id: &str, paused: bool) -> Result<(), String> {
-    let _url = format!("{}/dags/{}/paused", API_BASE_URL, dag_id);
+pub async fn toggle_dag_paused(dag_id: &str, paused: bool) -> Result<DAG, String> {
+    let url = format!("{}/dags/{}/paused", API_BASE_URL, dag_id);
 
     #[derive(Serialize)]
     struct PausedPayload {
         is_paused: bool,
     }
 
-    let _payload = PausedPayload { is_paused: paused };
+    let payload = PausedPayload { is_paused: paused };
 
-    // In a real app, we'd send a POST request here
-    // For this demo, we'll just return Ok since our backend doesn't actually support this endpoint
-    Ok(())
+    let response = Request::post(&url)
+        .json(&payload)
+        .map_err(|e| format!("Failed to serialize request: {:?}", e))?
+        .send()
+        .await
+        .map_err(|e| format!("Failed to update DAG: {:?}", e))?;
+
+    if !response.ok() {
+        return Err(api_error(response).await);
+    }
+
+    response
+        .json::<DAG>()
+        .await
+        .map_err(|e| format!("Failed to parse DAG response: {:?}", e))
+}
+
+/// Create a manual run of a DAG. Validation errors reported by the server are returned as is.
+pub async fn trigger_dag_run(
+    dag_id: &str,
+    logical_date: Option<chrono::DateTime<chrono::Utc>>,
+    conf: &serde_json::Map<String, serde_json::Value>,
+) -> Result<DagRun, String> {
+    let url = format!("{}/dags/{}/runs", API_BASE_URL, dag_id);
+
+    #[derive(Serialize)]
+    struct TriggerPayload<'a> {
+        #[serde(skip_serializing_if = "Option::is_none")]
+        logical_date: Option<chrono::DateTime<chrono::Utc>>,
+        conf: &'a serde_json::Map<String, serde_json::Value>,
+    }
+
+    let payload = TriggerPayload { logical_date, conf };
+
+    let response = Request::post(&url)
+        .json(&payload)
+        .map_err(|e| format!("Failed to serialize request: {:?}", e))?
+        .send()
+        .await
+        .map_err(|e| format!("Failed to trigger DAG: {:?}", e))?;
+
+    if !response.ok() {
+        return Err(api_error(response).await);
+    }
+
+    response
+        .json::<DagRun>()
+        .await
+        .map_err(|e| format!("Failed to parse run response: {:?}", e))
+}
+
+/// The `error` message of a failed API response, falling back to its status code
+async fn api_error(response: gloo::net::http::Response) -> String {
+    error_body(response).await.error
+}
+
+/// Error body of the API; `query_error` locates the problem in a malformed DAG search
+#[derive(Debug, Clone, PartialEq, serde::Deserialize)]
+pub struct ErrorBody {
+    pub error: String,
+    #[serde(default)]
+    pub query_error: Option<QueryError>,
+}
+
+impl From<String> for ErrorBody {
+    fn from(error: String) -> Self {
+        ErrorBody {
+            error,
+            query_error: None,
+        }
+    }
+}
+
+async fn error_body(response: gloo::net::http::Response) -> ErrorBody {
+    let status = response.status();
+    response
+        .json::<ErrorBody>()
+        .await
+        .unwrap_or_else(|_| format!("API error: {}", status).into())
 }
 
 pub fn format_datetime(dt: Option<chrono::DateTime<chrono::Utc>>) -> String {
@@ -141,6 +191,24 @@ pub fn format_datetime(dt: Option<chrono::DateTime<chrono::Utc>>) -> String {
     }
 }
 
+pub async fn fetch_dag(dag_id: &str) -> Result<DAG, String> {
+    let url = format!("{}/dags/{}", API_BASE_URL, dag_id);
+
+    let response = Request::get(&url)
+        .send()
+        .await
+        .map_err(|e| format!("Failed to fetch DAG: {:?}", e))?;
+
+    if !response.ok() {
+        return Err(api_error(response).await);
+    }
+
+    response
+        .json::<DAG>()
+        .await
+        .map_err(|e| format!("Failed to parse DAG response: {:?}", e))
+}
+
 pub async fn fetch_dag_graph(dag_id: &str) -> Result<DAGGraph, String> {
     let url = format!("{}/dags/{}/graph", API_BASE_URL, dag_id);
     
@@ -150,7 +218,7 @@ pub async fn fetch_dag_graph(dag_id: &str) -> Result<DAGGraph, String> {
         .map_err(|e| format!("Failed to fetch DAG graph: {:?}", e))?;
         
     if !response.ok() {
-        return Err(format!("API error: {}", response.status()));
+        return Err(api_error(response).await);
     }
     
     response
@@ -159,17 +227,449 @@ pub async fn fetch_dag_graph(dag_id: &str) -> Result<DAGGraph, String> {
         .map_err(|e| format!("Failed to parse DAG graph response: {:?}", e))
 }
 
-pub async fn update_task_status(dag_id: &str, task_id: &str, status: &str) -> Result<(), String> {
-    let _url = format!("{}/dags/{}/tasks/{}/status", API_BASE_URL, dag_id, task_id);
-    
+pub async fn fetch_dag_run(dag_id: &str, run_id: &str) -> Result<DagRun, String> {
+    let url = format!(
+        "{}/dags/{}/runs/{}",
+        API_BASE_URL,
+        dag_id,
+        js_sys::encode_uri_component(run_id)
+    );
+
+    let response = Request::get(&url)
+        .send()
+        .await
+        .map_err(|e| format!("Failed to fetch run: {:?}", e))?;
+
+    if !response.ok() {
+        return Err(api_error(response).await);
+    }
+
+    response
+        .json::<DagRun>()
+        .await
+        .map_err(|e| format!("Failed to parse run response: {:?}", e))
+}
+
+pub async fn fetch_dag_run_graph(dag_id: &str, run_id: &str) -> Result<DAGGraph, String> {
+    let url = format!(
+        "{}/dags/{}/runs/{}/graph",
+        API_BASE_URL,
+        dag_id,
+        js_sys::encode_uri_component(run_id)
+    );
+
+    let response = Request::get(&url)
+        .send()
+        .await
+        .map_err(|e| format!("Failed to fetch run graph: {:?}", e))?;
+
+    if !response.ok() {
+        return Err(api_error(response).await);
+    }
+
+    response
+        .json::<DAGGraph>()
+        .await
+        .map_err(|e| format!("Failed to parse run graph response: {:?}", e))
+}
+
+fn task_logs_url(dag_id: &str, run_id: &str, task_id: &str) -> String {
+    format!(
+        "{}/dags/{}/runs/{}/tasks/{}/logs",
+        API_BASE_URL,
+        dag_id,
+        js_sys::encode_uri_component(run_id),
+        task_id
+    )
+}
+
+pub async fn fetch_task_logs(
+    dag_id: &str,
+    run_id: &str,
+    task_id: &str,
+    try_number: usize,
+    offset: u64,
+) -> Result<LogChunk, String> {
+    let url = format!(
+        "{}?try={}&offset={}",
+        task_logs_url(dag_id, run_id, task_id),
+        try_number,
+        offset
+    );
+
+    let response = Request::get(&url)
+        .send()
+        .await
+        .map_err(|e| format!("Failed to fetch task logs: {:?}", e))?;
+
+    if !response.ok() {
+        return Err(api_error(response).await);
+    }
+
+    response
+        .json::<LogChunk>()
+        .await
+        .map_err(|e| format!("Failed to parse task logs response: {:?}", e))
+}
+
+/// Server-sent event stream that tails an attempt's log
+pub fn task_logs_stream_url(dag_id: &str, run_id: &str, task_id: &str, try_number: usize) -> String {
+    format!("{}/stream?try={}", task_logs_url(dag_id, run_id, task_id), try_number)
+}
+
+/// Server-sent event stream of DAG, run, task and log changes, optionally of one DAG only
+pub fn events_url(dag_id: Option<&str>) -> String {
+    match dag_id {
+        Some(dag_id) => format!("{}/events?dag_id={}", API_BASE_URL, js_sys::encode_uri_component(dag_id)),
+        None => format!("{}/events", API_BASE_URL),
+    }
+}
+
+/// Change a task instance's status by hand. Transitions the server rejects come back with its
+/// reason, e.g. "cannot change RUNNING to SKIPPED: ...".
+pub async fn update_task_status(
+    dag_id: &str,
+    run_id: &str,
+    task_id: &str,
+    status: &str,
+    upstream: bool,
+    downstream: bool,
+    comment: Option<&str>,
+) -> Result<DagRun, String> {
+    let url = format!(
+        "{}/dags/{}/runs/{}/tasks/{}/status",
+        API_BASE_URL,
+        dag_id,
+        js_sys::encode_uri_component(run_id),
+        task_id
+    );
+
     #[derive(Serialize)]
-    struct StatusPayload {
-        status: String,
+    struct StatusPayload<'a> {
+        status: &'a str,
+        upstream: bool,
+        downstream: bool,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        comment: Option<&'a str>,
+    }
+
+    let payload = StatusPayload { status, upstream, downstream, comment };
+
+    let response = Request::post(&url)
+        .json(&payload)
+        .map_err(|e| format!("Failed to serialize request: {:?}", e))?
+        .send()
+        .await
+        .map_err(|e| format!("Failed to update task status: {:?}", e))?;
+
+    if !response.ok() {
+        return Err(api_error(response).await);
+    }
+
+    response
+        .json::<DagRun>()
+        .await
+        .map_err(|e| format!("Failed to parse run response: {:?}", e))
+}
+
+/// Reset a task instance (and, per `options`, related ones) so it runs again. With
+/// `options.dry_run` set nothing changes and the response lists the tasks that would be cleared.
+pub async fn clear_task(
+    dag_id: &str,
+    run_id: &str,
+    task_id: &str,
+    options: &ClearOptions,
+) -> Result<ClearResponse, String> {
+    let url = format!(
+        "{}/dags/{}/runs/{}/tasks/{}/clear",
+        API_BASE_URL,
+        dag_id,
+        js_sys::encode_uri_component(run_id),
+        task_id
+    );
+
+    let response = Request::post(&url)
+        .json(options)
+        .map_err(|e| format!("Failed to serialize request: {:?}", e))?
+        .send()
+        .await
+        .map_err(|e| format!("Failed to clear task: {:?}", e))?;
+
+    if !response.ok() {
+        return Err(api_error(response).await);
+    }
+
+    response
+        .json::<ClearResponse>()
+        .await
+        .map_err(|e| format!("Failed to parse clear response: {:?}", e))
+}
+
+pub async fn fetch_dag_runs(dag_id: &str, query: &DagRunsQuery) -> Result<DagRunsResponse, String> {
+    let mut params = vec![];
+
+    if let Some(page) = query.page {
+        params.push(format!("page={}", page));
+    }
+
+    if let Some(limit) = query.limit {
+        params.push(format!("limit={}", limit));
+    }
+
+    if let Some(state) = &query.state {
+        params.push(format!("state={}", state));
+    }
+
+    let url = format!("{}/dags/{}/runs", API_BASE_URL, dag_id);
+    let url = if params.is_empty() {
+        url
+    } else {
+        format!("{}?{}", url, params.join("&"))
+    };
+
+    let response = Request::get(&url)
+        .send()
+        .await
+        .map_err(|e| format!("Failed to fetch DAG runs: {:?}", e))?;
+
+    if !response.ok() {
+        return Err(api_error(response).await);
+    }
+
+    response
+        .json::<DagRunsResponse>()
+        .await
+        .map_err(|e| format!("Failed to parse DAG runs response: {:?}", e))
+}
+
+/// One page of the audit log, newest first
+pub async fn fetch_audit(query: &AuditQuery) -> Result<AuditResponse, String> {
+    let mut params = vec![];
+
+    for (name, value) in [("actor", &query.actor), ("action", &query.action), ("dag_id", &query.dag_id)] {
+        if let Some(value) = value {
+            params.push(format!("{}={}", name, js_sys::encode_uri_component(value)));
+        }
+    }
+
+    if let Some(page) = query.page {
+        params.push(format!("page={}", page));
+    }
+
+    if let Some(limit) = query.limit {
+        params.push(format!("limit={}", limit));
+    }
+
+    let url = format!("{}/audit", API_BASE_URL);
+    let url = if params.is_empty() {
+        url
+    } else {
+        format!("{}?{}", url, params.join("&"))
+    };
+
+    let response = Request::get(&url)
+        .send()
+        .await
+        .map_err(|e| format!("Failed to fetch audit log: {:?}", e))?;
+
+    if !response.ok() {
+        return Err(api_error(response).await);
+    }
+
+    response
+        .json::<AuditResponse>()
+        .await
+        .map_err(|e| format!("Failed to parse audit log response: {:?}", e))
+}
+
+/// Whether auth is enabled and who is signed in
+pub async fn fetch_auth_status() -> Result<AuthStatus, String> {
+    let url = format!("{}/auth/me", API_BASE_URL);
+
+    let response = Request::get(&url)
+        .send()
+        .await
+        .map_err(|e| format!("Failed to fetch sign-in status: {:?}", e))?;
+
+    if !response.ok() {
+        return Err(api_error(response).await);
+    }
+
+    response
+        .json::<AuthStatus>()
+        .await
+        .map_err(|e| format!("Failed to parse sign-in status: {:?}", e))
+}
+
+/// Sign in; the server keeps the session in a cookie
+pub async fn login(username: &str, password: &str) -> Result<AuthStatus, String> {
+    let url = format!("{}/auth/login", API_BASE_URL);
+
+    #[derive(Serialize)]
+    struct LoginPayload<'a> {
+        username: &'a str,
+        password: &'a str,
+    }
+
+    let response = Request::post(&url)
+        .json(&LoginPayload { username, password })
+        .map_err(|e| format!("Failed to serialize request: {:?}", e))?
+        .send()
+        .await
+        .map_err(|e| format!("Failed to sign in: {:?}", e))?;
+
+    if !response.ok() {
+        return Err(api_error(response).await);
+    }
+
+    response
+        .json::<AuthStatus>()
+        .await
+        .map_err(|e| format!("Failed to parse sign-in response: {:?}", e))
+}
+
+pub async fn logout() -> Result<(), String> {
+    let url = format!("{}/auth/logout", API_BASE_URL);
+
+    let response = Request::post(&url)
+        .send()
+        .await
+        .map_err(|e| format!("Failed to sign out: {:?}", e))?;
+
+    if !response.ok() {
+        return Err(api_error(response).await);
     }
-    
-    let _payload = StatusPayload { status: status.to_string() };
-    
-    // In a real app, we'd send a PATCH request here
-    // For this demo, we'll just return Ok since our backend doesn't actually support this endpoint
     Ok(())
-}
\ No newline at end of file
+}
+
+pub async fn fetch_users() -> Result<Vec<UserInfo>, String> {
+    let url = format!("{}/users", API_BASE_URL);
+
+    let response = Request::get(&url)
+        .send()
+        .await
+        .map_err(|e| format!("Failed to fetch users: {:?}", e))?;
+
+    if !response.ok() {
+        return Err(api_error(response).await);
+    }
+
+    response
+        .json::<Vec<UserInfo>>()
+        .await
+        .map_err(|e| format!("Failed to parse users response: {:?}", e))
+}
+
+pub async fn create_user(payload: &NewUserPayload) -> Result<UserInfo, String> {
+    let url = format!("{}/users", API_BASE_URL);
+
+    let response = Request::post(&url)
+        .json(payload)
+        .map_err(|e| format!("Failed to serialize request: {:?}", e))?
+        .send()
+        .await
+        .map_err(|e| format!("Failed to create user: {:?}", e))?;
+
+    if !response.ok() {
+        return Err(api_error(response).await);
+    }
+
+    response
+        .json::<UserInfo>()
+        .await
+        .map_err(|e| format!("Failed to parse user response: {:?}", e))
+}
+
+pub async fn update_user(username: &str, payload: &UpdateUserPayload) -> Result<UserInfo, String> {
+    let url = format!("{}/users/{}", API_BASE_URL, js_sys::encode_uri_component(username));
+
+    let response = Request::put(&url)
+        .json(payload)
+        .map_err(|e| format!("Failed to serialize request: {:?}", e))?
+        .send()
+        .await
+        .map_err(|e| format!("Failed to update user: {:?}", e))?;
+
+    if !response.ok() {
+        return Err(api_error(response).await);
+    }
+
+    response
+        .json::<UserInfo>()
+        .await
+        .map_err(|e| format!("Failed to parse user response: {:?}", e))
+}
+
+pub async fn delete_user(username: &str) -> Result<(), String> {
+    let url = format!("{}/users/{}", API_BASE_URL, js_sys::encode_uri_component(username));
+
+    let response = Request::delete(&url)
+        .send()
+        .await
+        .map_err(|e| format!("Failed to delete user: {:?}", e))?;
+
+    if !response.ok() {
+        return Err(api_error(response).await);
+    }
+    Ok(())
+}
+
+/// The signed-in user's API tokens
+pub async fn fetch_tokens() -> Result<Vec<ApiTokenInfo>, String> {
+    let url = format!("{}/tokens", API_BASE_URL);
+
+    let response = Request::get(&url)
+        .send()
+        .await
+        .map_err(|e| format!("Failed to fetch API tokens: {:?}", e))?;
+
+    if !response.ok() {
+        return Err(api_error(response).await);
+    }
+
+    response
+        .json::<Vec<ApiTokenInfo>>()
+        .await
+        .map_err(|e| format!("Failed to parse API tokens response: {:?}", e))
+}
+
+pub async fn create_token(name: &str, expires_in_days: Option<u32>) -> Result<NewTokenResponse, String> {
+    let url = format!("{}/tokens", API_BASE_URL);
+
+    #[derive(Serialize)]
+    struct TokenPayload<'a> {
+        name: &'a str,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        expires_in_days: Option<u32>,
+    }
+
+    let response = Request::post(&url)
+        .json(&TokenPayload { name, expires_in_days })
+        .map_err(|e| format!("Failed to serialize request: {:?}", e))?
+        .send()
+        .await
+        .map_err(|e| format!("Failed to create API token: {:?}", e))?;
+
+    if !response.ok() {
+        return Err(api_error(response).await);
+    }
+
+    response
+        .json::<NewTokenResponse>()
+        .await
+        .map_err(|e| format!("Failed to parse API token response: {:?}", e))
+}
+
+pub async fn revoke_token(token_id: &str) -> Result<(), String> {
+    let url = format!("{}/tokens/{}", API_BASE_URL, js_sys::encode_uri_component(token_id));
+
+    let response = Request::delete(&url)
+        .send()
+        .await
+        .map_err(|e| format!("Failed to revoke API token: {:?}", e))?;
+
+    if !response.ok() {
+        return Err(api_error(response).await);
+    }
+    Ok(())
+}
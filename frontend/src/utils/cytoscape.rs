@@ -427,7 +427,7 @@ pub fn cytoscape(props: &CytoscapeProps) -> Html {
     }
 }
 
-/// Highlight a path in the graph
+/// Highlight a path in the graph: the edges between consecutive ids and the nodes themselves
 pub fn highlight_path(cy: &JsValue, path: &[String], color: &str) -> Result<(), JsValue> {
     let path_array = Array::new();
     for item in path {
@@ -436,15 +436,26 @@ pub fn highlight_path(cy: &JsValue, path: &[String], color: &str) -> Result<(),
     
     let js_code = format!(
         r#"
-        // Reset all edges
+        // Reset all edges and node outlines
         cy.edges().style({{
             'line-color': '#ccc',
             'target-arrow-color': '#ccc',
             'width': 2
         }});
+        cy.nodes().style({{
+            'border-width': 0
+        }});
         
-        // Highlight specific edges in the path
+        // Outline the nodes on the path
         var path = path_array;
+        for (var j = 0; j < path.length; j++) {{
+            cy.getElementById(path[j]).style({{
+                'border-width': 3,
+                'border-color': '{}'
+            }});
+        }}
+        
+        // Highlight specific edges in the path
         for (var i = 0; i < path.length - 1; i++) {{
             var source = path[i];
             var target = path[i+1];
@@ -456,6 +467,7 @@ pub fn highlight_path(cy: &JsValue, path: &[String], color: &str) -> Result<(),
         }}
         "#,
         color,
+        color,
         color
     );
     
@@ -503,6 +515,9 @@ pub fn reset_highlights(cy: &JsValue) -> Result<(), JsValue> {
             'target-arrow-color': '#ccc',
             'width': 2
         });
+        cy.nodes().style({
+            'border-width': 0
+        });
     "#;
     
     let function = Function::new_with_args("cy", js_code);
@@ -511,6 +526,19 @@ pub fn reset_highlights(cy: &JsValue) -> Result<(), JsValue> {
     Ok(())
 }
 
+/// Set a data field of a node in place. Styles keyed on the field follow; the layout is kept.
+pub fn set_node_data(cy: &JsValue, node_id: &str, key: &str, value: &JsValue) -> Result<(), JsValue> {
+    let js_code = r#"
+        cy.getElementById(nodeId).data(key, value);
+    "#;
+    
+    let function = Function::new_with_args("cy, nodeId, key, value", js_code);
+    let args = Array::of4(cy, &JsValue::from_str(node_id), &JsValue::from_str(key), value);
+    Reflect::apply(&function, &JsValue::NULL, &args)?;
+    
+    Ok(())
+}
+
 /// Center the graph
 pub fn center_graph(cy: &JsValue) -> Result<(), JsValue> {
     let js_code = r#"
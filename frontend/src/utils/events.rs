@@ -0,0 +1,67 @@
+use crate::models::dag::ServerEvent;
+use crate::utils::api;
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+use web_sys::{EventSource, MessageEvent};
+use yew::Callback;
+
+/// Server-sent event names of `/api/events`
+const EVENT_NAMES: [&str; 6] = [
+    "dag_paused",
+    "run_created",
+    "run_state_changed",
+    "task_state_changed",
+    "log_appended",
+    "resync",
+];
+
+/// An open `/api/events` stream. The browser reconnects it on its own; events sent while it was
+/// down are lost, so a reconnect is reported as `ServerEvent::Resync`. Closed when dropped.
+pub struct EventSubscription {
+    source: EventSource,
+    _listeners: Vec<Closure<dyn FnMut(MessageEvent)>>,
+}
+
+impl Drop for EventSubscription {
+    fn drop(&mut self) {
+        self.source.close();
+    }
+}
+
+/// Stream server events to `on_event`, only those of `dag_id` if given
+pub fn subscribe(dag_id: Option<&str>, on_event: Callback<ServerEvent>) -> Result<EventSubscription, String> {
+    let source = EventSource::new(&api::events_url(dag_id)).map_err(|e| format!("{:?}", e))?;
+    let mut listeners = Vec::new();
+
+    for name in EVENT_NAMES {
+        let on_event = on_event.clone();
+        let listener = Closure::wrap(Box::new(move |e: MessageEvent| {
+            let Some(data) = e.data().as_string() else {
+                return;
+            };
+            match serde_json::from_str::<ServerEvent>(&data) {
+                Ok(event) => on_event.emit(event),
+                Err(err) => log::warn!("Ignoring unreadable server event: {}", err),
+            }
+        }) as Box<dyn FnMut(MessageEvent)>);
+        let _ = source.add_event_listener_with_callback(name, listener.as_ref().unchecked_ref());
+        listeners.push(listener);
+    }
+
+    let on_open = {
+        let mut opened = false;
+        Closure::wrap(Box::new(move |_: MessageEvent| {
+            if opened {
+                on_event.emit(ServerEvent::Resync { missed: 0 });
+            }
+            opened = true;
+        }) as Box<dyn FnMut(MessageEvent)>)
+    };
+    let _ = source.add_event_listener_with_callback("open", on_open.as_ref().unchecked_ref());
+    listeners.push(on_open);
+
+    Ok(EventSubscription {
+        source,
+        _listeners: listeners,
+    })
+}
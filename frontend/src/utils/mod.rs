@@ -1,3 +1,4 @@
 pub mod api;
 pub mod cytoscape;
 pub mod aggrid;
+pub mod events;
@@ -1,495 +1,763 @@
+use std::convert::Infallible;
+use std::time::Duration;
+
 use axum::{
-    extract::{Path, Query},
-    routing::get,
+    extract::{Path, Query, State},
+    http::{header, HeaderMap, StatusCode},
+    response::{
+        sse::{Event, KeepAlive, Sse},
+        IntoResponse, Response,
+    },
+    routing::{delete, get, post, put},
     Json, Router,
 };
-use chrono::{Duration, Utc};
-use rand::{seq::SliceRandom, Rng};
+use chrono::Utc;
+use futures::Stream;
+use serde_json::json;
+use tokio::sync::broadcast::error::RecvError;
 use uuid::Uuid;
 
-use crate::models::{DAG, DAGGraph, DAGsQuery, DAGsResponse, Edge, Task, TaskStatus};
+use crate::audit;
+use crate::auth::{self, Identity};
+use crate::backfill;
+use crate::events::ServerEvent;
+use crate::models::{
+    ApiTokenInfo, AuditQuery, AuditResponse, AuthStatus, BackfillPayload, BackfillProgress, BackfillResponse,
+    ClearPayload, ClearResponse, DAG, DAGGraph, DAGPayload, DAGsQuery, DAGsResponse, DagRun, DagRunsQuery,
+    DagRunsResponse, EventsQuery, GraphQuery, ImportError, LogQuery, LoginPayload, NewTokenPayload, NewTokenResponse,
+    NewUserPayload, PausedPayload, ScheduleQuery, ScheduleResponse, TagsQuery, TagsResponse, TaskStatus,
+    TaskStatusPayload, TriggerRunPayload, UpdateUserPayload, UserInfo,
+};
+use crate::loader;
+use crate::logs::LogChunk;
+use crate::params::{self, ParamsError};
+use crate::registry::RegistryError;
+use crate::schedule::{self, ScheduleError};
+use crate::search::QueryError;
+use crate::service::{self, ServiceError};
+use crate::state::AppState;
+
+/// Where `routes` are nested in the server
+pub const PREFIX: &str = "/api";
 
-pub fn routes() -> Router {
+pub fn routes() -> Router<AppState> {
     Router::new()
-        .route("/dags", get(get_dags))
-        .route("/dags/:dag_id/graph", get(get_dag_graph))
-}
-
-async fn get_dags(Query(params): Query<DAGsQuery>) -> Json<DAGsResponse> {
-    let limit = params.limit.unwrap_or(25);
-    let page = params.page.unwrap_or(1);
-    let offset = (page - 1) * limit;
-
-    // Generate sample DAG data
-    let mut dags = generate_sample_dags();
-    let total_count = dags.len();
-
-    // Apply search filter if provided
-    if let Some(search) = &params.search {
-        dags.retain(|dag| {
-            dag.dag_id.to_lowercase().contains(&search.to_lowercase())
-                || dag.owner.to_lowercase().contains(&search.to_lowercase())
-                || dag.description
-                .as_ref()
-                .map_or(false, |desc| desc.to_lowercase().contains(&search.to_lowercase()))
-        });
+        .route("/dags", get(get_dags).post(create_dag))
+        .route("/dags/:dag_id", get(get_dag).put(update_dag).delete(delete_dag))
+        .route("/dags/:dag_id/graph", get(get_dag_graph).put(put_dag_graph))
+        .route("/dags/:dag_id/paused", post(set_dag_paused))
+        .route("/dags/:dag_id/schedule", get(get_dag_schedule))
+        .route("/dags/:dag_id/runs", get(get_dag_runs).post(trigger_dag_run))
+        .route("/dags/:dag_id/backfill", post(create_backfill))
+        .route("/dags/:dag_id/backfills/:backfill_id", get(get_backfill))
+        .route("/dags/:dag_id/runs/:run_id", get(get_dag_run))
+        .route("/dags/:dag_id/runs/:run_id/graph", get(get_dag_run_graph))
+        .route("/dags/:dag_id/runs/:run_id/tasks/:task_id/status", post(set_task_status))
+        .route("/dags/:dag_id/runs/:run_id/tasks/:task_id/clear", post(clear_task))
+        .route("/dags/:dag_id/runs/:run_id/tasks/:task_id/logs", get(get_task_logs))
+        .route("/dags/:dag_id/runs/:run_id/tasks/:task_id/logs/stream", get(stream_task_logs))
+        .route("/tags", get(get_tags))
+        .route("/import-errors", get(get_import_errors))
+        .route("/audit", get(get_audit))
+        .route("/events", get(stream_events))
+        .route("/auth/login", post(login))
+        .route("/auth/logout", post(logout))
+        .route("/auth/me", get(get_auth_status))
+        .route("/users", get(get_users).post(create_user))
+        .route("/users/:username", put(update_user).delete(delete_user))
+        .route("/tokens", get(get_tokens).post(create_token))
+        .route("/tokens/:token_id", delete(revoke_token))
+}
+
+/// Error returned by API handlers, rendered as `{"error": "..."}` with a matching status code
+#[derive(Debug)]
+pub enum ApiError {
+    BadRequest(String),
+    NotFound(String),
+    /// The request is valid but not allowed in the current state
+    Conflict(String),
+    /// No or invalid credentials
+    Unauthorized(String),
+    /// Signed in, but not allowed to do this
+    Forbidden(String),
+    /// Malformed DAG search, rendered with a `query_error` object locating the problem
+    InvalidQuery(QueryError),
+    Registry(RegistryError),
+}
+
+impl From<RegistryError> for ApiError {
+    fn from(err: RegistryError) -> Self {
+        ApiError::Registry(err)
     }
+}
 
-    // Apply status filter if provided
-    if let Some(status) = &params.status {
-        match status.as_str() {
-            "active" => dags.retain(|dag| !dag.paused),
-            "paused" => dags.retain(|dag| dag.paused),
-            "success" => dags.retain(|dag| dag.success_count > 0 && dag.failed_count == 0),
-            "failed" => dags.retain(|dag| dag.failed_count > 0),
-            "running" => dags.retain(|dag| dag.running_count > 0),
-            _ => {}
-        }
+impl From<ScheduleError> for ApiError {
+    fn from(err: ScheduleError) -> Self {
+        ApiError::BadRequest(err.to_string())
     }
+}
 
-    // Apply tag filter if provided
-    if let Some(tags) = &params.tags {
-        let tag_list: Vec<&str> = tags.split(',').collect();
-        dags.retain(|dag| {
-            tag_list
-                .iter()
-                .any(|tag| dag.tags.iter().any(|t| t.eq_ignore_ascii_case(tag)))
-        });
+impl From<ParamsError> for ApiError {
+    fn from(err: ParamsError) -> Self {
+        ApiError::BadRequest(err.to_string())
     }
+}
 
-    // Apply sorting if provided
-    if let Some(sort_by) = &params.sort_by {
-        let asc = params
-            .sort_order
-            .as_ref()
-            .map_or(true, |order| order == "asc");
-
-        match sort_by.as_str() {
-            "dag_id" => {
-                if asc {
-                    dags.sort_by(|a, b| a.dag_id.cmp(&b.dag_id));
-                } else {
-                    dags.sort_by(|a, b| b.dag_id.cmp(&a.dag_id));
-                }
-            }
-            "owner" => {
-                if asc {
-                    dags.sort_by(|a, b| a.owner.cmp(&b.owner));
-                } else {
-                    dags.sort_by(|a, b| b.owner.cmp(&a.owner));
-                }
-            }
-            "last_run" => {
-                if asc {
-                    dags.sort_by(|a, b| a.last_run.cmp(&b.last_run));
-                } else {
-                    dags.sort_by(|a, b| b.last_run.cmp(&a.last_run));
-                }
-            }
-            "next_run" => {
-                if asc {
-                    dags.sort_by(|a, b| a.next_run.cmp(&b.next_run));
-                } else {
-                    dags.sort_by(|a, b| b.next_run.cmp(&a.next_run));
-                }
-            }
-            _ => {
-                if asc {
-                    dags.sort_by(|a, b| a.dag_id.cmp(&b.dag_id));
-                } else {
-                    dags.sort_by(|a, b| b.dag_id.cmp(&a.dag_id));
-                }
-            }
+impl From<ServiceError> for ApiError {
+    fn from(err: ServiceError) -> Self {
+        match err {
+            ServiceError::Invalid(message) => ApiError::BadRequest(message),
+            ServiceError::NotFound(message) => ApiError::NotFound(message),
+            ServiceError::Conflict(message) => ApiError::Conflict(message),
+            ServiceError::Unauthorized(message) => ApiError::Unauthorized(message),
+            ServiceError::InvalidQuery(err) => ApiError::InvalidQuery(err),
+            ServiceError::Registry(err) => ApiError::Registry(err),
         }
     }
+}
 
-    // Apply pagination
-    let dags = dags
-        .into_iter()
-        .skip(offset)
-        .take(limit)
-        .collect::<Vec<_>>();
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let (status, message) = match self {
+            ApiError::BadRequest(message) => (StatusCode::BAD_REQUEST, message),
+            ApiError::NotFound(message) => (StatusCode::NOT_FOUND, message),
+            ApiError::Conflict(message) => (StatusCode::CONFLICT, message),
+            ApiError::Unauthorized(message) => (StatusCode::UNAUTHORIZED, message),
+            ApiError::Forbidden(message) => (StatusCode::FORBIDDEN, message),
+            ApiError::InvalidQuery(err) => {
+                let body = serde_json::json!({ "error": err.to_string(), "query_error": err });
+                return (StatusCode::BAD_REQUEST, Json(body)).into_response();
+            }
+            ApiError::Registry(err) => {
+                let status = match err {
+                    RegistryError::NotFound(_)
+                    | RegistryError::RunNotFound { .. }
+                    | RegistryError::UserNotFound(_)
+                    | RegistryError::TokenNotFound(_) => StatusCode::NOT_FOUND,
+                    RegistryError::AlreadyExists(_)
+                    | RegistryError::RunAlreadyExists { .. }
+                    | RegistryError::UserAlreadyExists(_) => StatusCode::CONFLICT,
+                    RegistryError::Storage(_) => StatusCode::INTERNAL_SERVER_ERROR,
+                };
+                (status, err.to_string())
+            }
+        };
+        (status, Json(serde_json::json!({ "error": message }))).into_response()
+    }
+}
 
-    Json(DAGsResponse {
-        dags,
-        total_count,
-    })
+async fn get_dags(
+    State(state): State<AppState>,
+    Query(params): Query<DAGsQuery>,
+) -> Result<Json<DAGsResponse>, ApiError> {
+    Ok(Json(service::list_dags(&state, &params)?))
 }
 
-async fn get_dag_graph(Path(dag_id): Path<String>) -> Json<DAGGraph> {
-    let graph = generate_sample_dag_graph(dag_id);
-    Json(graph)
+async fn get_dag(
+    State(state): State<AppState>,
+    Path(dag_id): Path<String>,
+) -> Result<Json<DAG>, ApiError> {
+    Ok(Json(service::get_dag(&state, &dag_id)?))
 }
 
-fn generate_sample_dag_graph(dag_id: String) -> DAGGraph {
-    let mut rng = rand::thread_rng();
+async fn create_dag(
+    State(state): State<AppState>,
+    identity: Identity,
+    Json(payload): Json<DAGPayload>,
+) -> Result<(StatusCode, Json<DAG>), ApiError> {
+    if let Some(schema) = &payload.params_schema {
+        params::check_schema(schema)?;
+    }
     let now = Utc::now();
-    
-    // Define the task types (operators) that can appear in the DAG
-    let operators = [
-        "PythonOperator", 
-        "BashOperator", 
-        "PostgresOperator", 
-        "MySqlOperator", 
-        "HttpSensor", 
-        "S3KeySensor", 
-        "EmailOperator",
-        "SlackOperator",
-        "SparkSubmitOperator", 
-        "DockerOperator"
-    ];
-    
-    // Special case handling for our test DAGs with known node counts
-    let default_min_tasks = 5;
-    let default_max_tasks = 20;
-    
-    // Check for special test DAGs first (exact matches)
-    let num_tasks = match dag_id.as_str() {
-        "tiny_dag_5" => 5,
-        "small_dag_20" => 20,
-        "medium_dag_100" => 100,
-        "large_dag_500" => 500,
-        "huge_dag_1000" => 1000,
-        _ => {
-            // For other DAGs, try to extract the number from the name
-            if let Some(cap) = dag_id.find(|c: char| c.is_digit(10)) {
-                // Try to extract number from the dag_id
-                let end = dag_id[cap..].find(|c: char| !c.is_digit(10)).unwrap_or(dag_id[cap..].len());
-                let num_str = &dag_id[cap..cap+end];
-                
-                if let Ok(num) = num_str.parse::<usize>() {
-                    // Limit to reasonable range between 5-1000
-                    num.clamp(5, 1000)
-                } else {
-                    // Fallback to random between 5-20
-                    rng.gen_range(default_min_tasks..default_max_tasks)
-                }
-            } else {
-                // No number in dag_id, use random between 5-50
-                rng.gen_range(default_min_tasks..50)
-            }
-        }
+    let mut dag = DAG {
+        id: Uuid::new_v4(),
+        dag_id: payload.dag_id,
+        description: payload.description,
+        file_path: payload.file_path,
+        owner: payload.owner,
+        paused: payload.paused,
+        last_run: None,
+        next_run: None,
+        runs_count: 0,
+        success_count: 0,
+        failed_count: 0,
+        running_count: 0,
+        schedule_interval: payload.schedule_interval,
+        timezone: payload.timezone,
+        tags: payload.tags,
+        params_schema: payload.params_schema,
+        created_at: now,
+        updated_at: now,
     };
-    
-    println!("Generating DAG with {} tasks for {}", num_tasks, dag_id);
-    
-    // Create the tasks
-    let mut tasks = Vec::with_capacity(num_tasks);
-    for i in 0..num_tasks {
-        // Generate a random status with weighted distribution
-        let status = match rng.gen_range(0..100) {
-            0..=10 => TaskStatus::PENDING,   // 10%
-            11..=60 => TaskStatus::SUCCEEDED, // 50%
-            61..=70 => TaskStatus::QUEUED,    // 10%
-            71..=85 => TaskStatus::RUNNING,   // 15%
-            86..=95 => TaskStatus::FAILED,    // 10%
-            96..=98 => TaskStatus::SKIPPED,   // 3%
-            _ => TaskStatus::PAUSED,          // 2%
-        };
-        
-        // Generate random start and end times for completed tasks
-        let (start_time, end_time, duration) = match status {
-            TaskStatus::SUCCEEDED | TaskStatus::FAILED => {
-                let start = now - Duration::minutes(rng.gen_range(30..120));
-                let duration_secs = rng.gen_range(30.0..600.0);
-                let end = start + Duration::seconds(duration_secs as i64);
-                (Some(start), Some(end), Some(duration_secs))
-            },
-            TaskStatus::RUNNING => {
-                let start = now - Duration::minutes(rng.gen_range(5..30));
-                (Some(start), None, None)
-            },
-            _ => (None, None, None)
-        };
-        
-        let operator = operators[rng.gen_range(0..operators.len())].to_string();
-        let retries = if status == TaskStatus::FAILED {
-            rng.gen_range(0..3)
-        } else {
-            0
-        };
-        
-        tasks.push(Task {
-            id: format!("task_{}", i),
-            name: format!("task_{}_{}_{}", dag_id, operator.replace("Operator", ""), i),
-            status,
-            duration,
-            start_time,
-            end_time,
-            operator,
-            retries,
-            max_retries: 3,
+    dag.next_run = schedule::next_run_for(&dag, now)?;
+    let dag = state.registry.create(dag)?;
+    audit::config_change(&state, &identity.username, &dag.dag_id, None, serde_json::to_value(&dag).ok());
+    Ok((StatusCode::CREATED, Json(dag)))
+}
+
+async fn update_dag(
+    State(state): State<AppState>,
+    identity: Identity,
+    Path(dag_id): Path<String>,
+    Json(payload): Json<DAGPayload>,
+) -> Result<Json<DAG>, ApiError> {
+    if let Some(schema) = &payload.params_schema {
+        params::check_schema(schema)?;
+    }
+    let mut dag = state
+        .registry
+        .get(&dag_id)?
+        .ok_or_else(|| RegistryError::NotFound(dag_id.clone()))?;
+
+    let before = serde_json::to_value(&dag).ok();
+    let was_paused = dag.paused;
+    // The path is authoritative; renaming a DAG is a delete plus a create
+    dag.description = payload.description;
+    dag.file_path = payload.file_path;
+    dag.owner = payload.owner;
+    dag.paused = payload.paused;
+    dag.schedule_interval = payload.schedule_interval;
+    dag.timezone = payload.timezone;
+    dag.tags = payload.tags;
+    dag.params_schema = payload.params_schema;
+    dag.updated_at = Utc::now();
+    dag.next_run = schedule::next_run_for(&dag, dag.updated_at)?;
+
+    let dag = state.registry.update(dag)?;
+    if dag.paused != was_paused {
+        state.events.publish(ServerEvent::DagPaused {
+            dag_id: dag.dag_id.clone(),
+            paused: dag.paused,
         });
     }
-    
-    // Create edges (connections between tasks)
-    // We'll create a mostly linear pipeline with some branches
-    let mut edges = Vec::new();
-    
-    // Ensure we always have a starting task that has no dependencies
-    
-    // For large graphs, we need a more structured approach
-    if num_tasks <= 50 {
-        // Small graphs: Add edges to create a DAG structure
-        for i in 1..num_tasks {
-            // Most tasks connect to the previous task
-            if rng.gen_bool(0.7) || i == 1 {
-                edges.push(Edge {
-                    source: format!("task_{}", i-1),
-                    target: format!("task_{}", i),
-                });
-            } else {
-                // Sometimes connect to a task further back to create branches
-                let source_idx = rng.gen_range(0..i-1);
-                edges.push(Edge {
-                    source: format!("task_{}", source_idx),
-                    target: format!("task_{}", i),
-                });
-            }
-            
-            // Occasionally add an extra edge to create a more complex DAG
-            if i > 2 && rng.gen_bool(0.3) {
-                let source_idx = rng.gen_range(0..i-1);
-                // Avoid duplicate edges
-                let new_edge = Edge {
-                    source: format!("task_{}", source_idx),
-                    target: format!("task_{}", i),
+    audit::config_change(&state, &identity.username, &dag_id, before, serde_json::to_value(&dag).ok());
+    Ok(Json(dag))
+}
+
+/// Pause or unpause a DAG
+async fn set_dag_paused(
+    State(state): State<AppState>,
+    identity: Identity,
+    Path(dag_id): Path<String>,
+    Json(payload): Json<PausedPayload>,
+) -> Result<Json<DAG>, ApiError> {
+    Ok(Json(service::set_paused(
+        &state,
+        &dag_id,
+        payload.is_paused,
+        payload.comment.as_deref(),
+        &identity.username,
+        Utc::now(),
+    )?))
+}
+
+async fn delete_dag(
+    State(state): State<AppState>,
+    identity: Identity,
+    Path(dag_id): Path<String>,
+) -> Result<StatusCode, ApiError> {
+    let before = state.registry.get(&dag_id)?;
+    state.registry.delete(&dag_id)?;
+    let before = before.and_then(|dag| serde_json::to_value(dag).ok());
+    audit::config_change(&state, &identity.username, &dag_id, before, None);
+    if let Err(err) = state.logs.delete_dag(&dag_id) {
+        tracing::warn!("Failed to remove logs of {}: {}", dag_id, err);
+    }
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn get_dag_graph(
+    State(state): State<AppState>,
+    Path(dag_id): Path<String>,
+    Query(params): Query<GraphQuery>,
+) -> Result<Json<DAGGraph>, ApiError> {
+    let graph = match params.version {
+        Some(version) => state.registry.get_graph_version(&dag_id, version)?,
+        None => state.registry.get_graph(&dag_id)?,
+    };
+    graph
+        .map(Json)
+        .ok_or_else(|| RegistryError::NotFound(dag_id).into())
+}
+
+async fn put_dag_graph(
+    State(state): State<AppState>,
+    identity: Identity,
+    Path(dag_id): Path<String>,
+    Json(mut graph): Json<DAGGraph>,
+) -> Result<Json<DAGGraph>, ApiError> {
+    if state.registry.get(&dag_id)?.is_none() {
+        return Err(RegistryError::NotFound(dag_id).into());
+    }
+    // Held to the same rules as DAG files; a cycle would leave its runs RUNNING forever
+    loader::validate_graph(&graph).map_err(ApiError::BadRequest)?;
+    let before = state.registry.get_graph(&dag_id)?;
+    graph.dag_id = dag_id;
+    graph.version = state.registry.put_graph(graph.clone())?;
+    if before.as_ref().map(|before| before.version) != Some(graph.version) {
+        let summary = |graph: &DAGGraph| {
+            json!({ "version": graph.version, "tasks": graph.tasks.len(), "edges": graph.edges.len() })
+        };
+        let (before, after) = (before.as_ref().map(summary), Some(summary(&graph)));
+        audit::config_change(&state, &identity.username, &graph.dag_id, before, after);
+    }
+    Ok(Json(graph))
+}
+
+async fn get_dag_schedule(
+    State(state): State<AppState>,
+    Path(dag_id): Path<String>,
+    Query(params): Query<ScheduleQuery>,
+) -> Result<Json<ScheduleResponse>, ApiError> {
+    let dag = state
+        .registry
+        .get(&dag_id)?
+        .ok_or_else(|| RegistryError::NotFound(dag_id.clone()))?;
+    let count = params.count.unwrap_or(10).min(100);
+    let next_runs = schedule::dag_schedule(&dag)?.upcoming(Utc::now(), count);
+
+    Ok(Json(ScheduleResponse {
+        dag_id,
+        schedule_interval: dag.schedule_interval,
+        timezone: dag.timezone,
+        next_runs,
+    }))
+}
+
+async fn get_dag_runs(
+    State(state): State<AppState>,
+    Path(dag_id): Path<String>,
+    Query(params): Query<DagRunsQuery>,
+) -> Result<Json<DagRunsResponse>, ApiError> {
+    Ok(Json(service::list_runs(&state, &dag_id, &params)?))
+}
+
+/// Create a manual run with the given `conf`
+async fn trigger_dag_run(
+    State(state): State<AppState>,
+    identity: Identity,
+    Path(dag_id): Path<String>,
+    Json(payload): Json<TriggerRunPayload>,
+) -> Result<(StatusCode, Json<DagRun>), ApiError> {
+    let run = service::trigger_run(&state, &dag_id, payload, &identity.username, Utc::now())?;
+    Ok((StatusCode::CREATED, Json(run)))
+}
+
+/// Queue a BACKFILL run for every schedule slot in a date range
+async fn create_backfill(
+    State(state): State<AppState>,
+    identity: Identity,
+    Path(dag_id): Path<String>,
+    Json(payload): Json<BackfillPayload>,
+) -> Result<(StatusCode, Json<BackfillResponse>), ApiError> {
+    let response = backfill::create_backfill(&state, &dag_id, payload, &identity.username, Utc::now())?;
+    let status = if response.dry_run { StatusCode::OK } else { StatusCode::CREATED };
+    Ok((status, Json(response)))
+}
+
+async fn get_backfill(
+    State(state): State<AppState>,
+    Path((dag_id, backfill_id)): Path<(String, String)>,
+) -> Result<Json<BackfillProgress>, ApiError> {
+    backfill::backfill_progress(state.runs.as_ref(), &dag_id, &backfill_id)?
+        .map(Json)
+        .ok_or_else(|| ApiError::NotFound(format!("backfill '{}' of DAG '{}' not found", backfill_id, dag_id)))
+}
+
+async fn get_dag_run(
+    State(state): State<AppState>,
+    Path((dag_id, run_id)): Path<(String, String)>,
+) -> Result<Json<DagRun>, ApiError> {
+    match state.runs.get_run(&dag_id, &run_id)? {
+        Some(run) => Ok(Json(run)),
+        None => Err(RegistryError::RunNotFound { dag_id, run_id }.into()),
+    }
+}
+
+/// The graph version a run was created from, with that run's task states, durations and tries
+async fn get_dag_run_graph(
+    State(state): State<AppState>,
+    Path((dag_id, run_id)): Path<(String, String)>,
+) -> Result<Json<DAGGraph>, ApiError> {
+    let run = state
+        .runs
+        .get_run(&dag_id, &run_id)?
+        .ok_or_else(|| RegistryError::RunNotFound {
+            dag_id: dag_id.clone(),
+            run_id,
+        })?;
+    let graph = state
+        .registry
+        .get_graph_version(&dag_id, run.graph_version)?
+        .ok_or(RegistryError::NotFound(dag_id))?;
+    Ok(Json(graph.with_run(&run)))
+}
+
+/// Change the state of a task instance by hand. Illegal transitions are rejected with 409.
+async fn set_task_status(
+    State(state): State<AppState>,
+    identity: Identity,
+    Path((dag_id, run_id, task_id)): Path<(String, String, String)>,
+    Json(payload): Json<TaskStatusPayload>,
+) -> Result<Json<DagRun>, ApiError> {
+    let run = service::set_task_status(&state, &dag_id, &run_id, &task_id, &payload, &identity.username, Utc::now())?;
+    Ok(Json(run))
+}
+
+/// Reset a task instance, and optionally related ones, to PENDING so it runs again
+async fn clear_task(
+    State(state): State<AppState>,
+    identity: Identity,
+    Path((dag_id, run_id, task_id)): Path<(String, String, String)>,
+    Json(payload): Json<ClearPayload>,
+) -> Result<Json<ClearResponse>, ApiError> {
+    let response = service::clear_task(&state, &dag_id, &run_id, &task_id, &payload, &identity.username, Utc::now())?;
+    Ok(Json(response))
+}
+
+/// Largest log slice returned by one request
+const MAX_LOG_CHUNK: u64 = 1024 * 1024;
+
+/// A byte range of one attempt's log; page through with `offset` and `next_offset`
+async fn get_task_logs(
+    State(state): State<AppState>,
+    Path((dag_id, run_id, task_id)): Path<(String, String, String)>,
+    Query(params): Query<LogQuery>,
+) -> Result<Json<LogChunk>, ApiError> {
+    let try_number = resolve_try(&state, &dag_id, &run_id, &task_id, params.try_number)?;
+    let limit = params.limit.unwrap_or(64 * 1024).min(MAX_LOG_CHUNK);
+    let chunk = state
+        .logs
+        .read(&dag_id, &run_id, &task_id, try_number, params.offset.unwrap_or(0), limit)
+        .map_err(|err| RegistryError::Storage(err.to_string()))?;
+    Ok(Json(chunk))
+}
+
+/// Tail an attempt's log as server-sent events. Each `log` event carries new output and has
+/// the next byte offset as its id; an `end` event follows once the attempt has finished.
+async fn stream_task_logs(
+    State(state): State<AppState>,
+    Path((dag_id, run_id, task_id)): Path<(String, String, String)>,
+    Query(params): Query<LogQuery>,
+) -> Result<Sse<impl Stream<Item = Result<Event, Infallible>>>, ApiError> {
+    let try_number = resolve_try(&state, &dag_id, &run_id, &task_id, params.try_number)?;
+    let offset = params.offset.unwrap_or(0);
+
+    let stream = futures::stream::unfold(Some(offset), move |offset| {
+        let state = state.clone();
+        let (dag_id, run_id, task_id) = (dag_id.clone(), run_id.clone(), task_id.clone());
+        async move {
+            let offset = offset?;
+            loop {
+                // Check before reading, so output written just before the attempt ended is sent
+                let finished = !attempt_running(&state, &dag_id, &run_id, &task_id, try_number);
+                let chunk = match state.logs.read(&dag_id, &run_id, &task_id, try_number, offset, MAX_LOG_CHUNK) {
+                    Ok(chunk) => chunk,
+                    Err(err) => {
+                        let event = Event::default().event("error").data(err.to_string());
+                        return Some((Ok(event), None));
+                    }
                 };
-                if !edges.contains(&new_edge) {
-                    edges.push(new_edge);
+                if !chunk.content.is_empty() {
+                    let event = Event::default()
+                        .event("log")
+                        .id(chunk.next_offset.to_string())
+                        .data(chunk.content);
+                    return Some((Ok(event), Some(chunk.next_offset)));
                 }
+                if finished {
+                    return Some((Ok(Event::default().event("end").data("")), None));
+                }
+                tokio::time::sleep(Duration::from_millis(500)).await;
             }
         }
-    } else {
-        // Large graphs: Create a more structured layout
-        // First, create a primary chain to ensure connectivity
-        for i in 1..num_tasks {
-            if i % 10 == 0 {
-                // Every 10th node connects to the node 10 positions before
-                edges.push(Edge {
-                    source: format!("task_{}", i-10),
-                    target: format!("task_{}", i),
-                });
-            } else {
-                // Regular progression
-                edges.push(Edge {
-                    source: format!("task_{}", i-1),
-                    target: format!("task_{}", i),
-                });
-            }
-        }
-        
-        // Add some parallel paths (for every 5th node, add 2-3 parallel branches)
-        for i in (5..num_tasks).step_by(5) {
-            let branches = rng.gen_range(2..=4);
-            
-            for _ in 0..branches {
-                // Connect to a random later node within a reasonable range
-                let target_distance = rng.gen_range(2..=10);
-                if i + target_distance < num_tasks {
-                    edges.push(Edge {
-                        source: format!("task_{}", i),
-                        target: format!("task_{}", i + target_distance),
-                    });
+    });
+
+    Ok(Sse::new(stream).keep_alive(KeepAlive::default()))
+}
+
+/// The requested attempt, or the task's latest one. Fails if the run or task does not exist.
+fn resolve_try(
+    state: &AppState,
+    dag_id: &str,
+    run_id: &str,
+    task_id: &str,
+    requested: Option<usize>,
+) -> Result<usize, ApiError> {
+    let run = state
+        .runs
+        .get_run(dag_id, run_id)?
+        .ok_or_else(|| RegistryError::RunNotFound {
+            dag_id: dag_id.to_string(),
+            run_id: run_id.to_string(),
+        })?;
+    let instance = run
+        .task_instances
+        .iter()
+        .find(|ti| ti.task_id == task_id)
+        .ok_or_else(|| ApiError::NotFound(format!("task '{}' not found in run '{}'", task_id, run_id)))?;
+    Ok(requested.unwrap_or(instance.try_number).max(1))
+}
+
+/// Whether the given attempt may still write to its log
+fn attempt_running(state: &AppState, dag_id: &str, run_id: &str, task_id: &str, try_number: usize) -> bool {
+    let Ok(Some(run)) = state.runs.get_run(dag_id, run_id) else {
+        return false;
+    };
+    run.task_instances.iter().any(|ti| {
+        ti.task_id == task_id
+            && match ti.status {
+                TaskStatus::RUNNING => ti.try_number == try_number,
+                // Not started yet: the attempt is still to come
+                TaskStatus::PENDING | TaskStatus::QUEUED | TaskStatus::UP_FOR_RETRY => {
+                    ti.try_number < try_number
                 }
+                _ => false,
             }
-        }
-        
-        // Add some cross-connections for every 20th node
-        for i in (20..num_tasks).step_by(20) {
-            // Find a distant node to connect to
-            let target_distance = rng.gen_range(15..=30);
-            if i + target_distance < num_tasks {
-                edges.push(Edge {
-                    source: format!("task_{}", i),
-                    target: format!("task_{}", i + target_distance),
-                });
+    })
+}
+
+/// Live DAG, run, task and log changes as server-sent events, optionally only those of the
+/// DAGs listed in `dag_id`. Each event is named after its `type` and carries the event as JSON.
+/// A subscriber that falls behind gets a `resync` event and should reload what it shows.
+async fn stream_events(
+    State(state): State<AppState>,
+    Query(params): Query<EventsQuery>,
+) -> Sse<impl Stream<Item = Result<Event, Infallible>>> {
+    let dag_ids: Option<Vec<String>> = params.dag_id.map(|ids| {
+        ids.split(',')
+            .map(str::trim)
+            .filter(|id| !id.is_empty())
+            .map(String::from)
+            .collect()
+    });
+    let receiver = state.events.subscribe();
+
+    let stream = futures::stream::unfold(receiver, move |mut receiver| {
+        let dag_ids = dag_ids.clone();
+        async move {
+            loop {
+                match receiver.recv().await {
+                    Ok(event) => {
+                        if dag_ids.as_ref().is_some_and(|ids| !ids.iter().any(|id| id == event.dag_id())) {
+                            continue;
+                        }
+                        let data = serde_json::to_string(&event).unwrap_or_default();
+                        return Some((Ok(Event::default().event(event.name()).data(data)), receiver));
+                    }
+                    Err(RecvError::Lagged(missed)) => {
+                        let data = serde_json::json!({ "type": "resync", "missed": missed }).to_string();
+                        return Some((Ok(Event::default().event("resync").data(data)), receiver));
+                    }
+                    Err(RecvError::Closed) => return None,
+                }
             }
         }
+    });
+
+    Sse::new(stream).keep_alive(KeepAlive::default())
+}
+
+async fn get_import_errors(State(state): State<AppState>) -> Json<Vec<ImportError>> {
+    Json(state.import_errors.read().unwrap().clone())
+}
+
+/// Tag facet of the DAG list: each tag with its DAG count under the other filters
+async fn get_tags(
+    State(state): State<AppState>,
+    Query(query): Query<TagsQuery>,
+) -> Result<Json<TagsResponse>, ApiError> {
+    Ok(Json(service::tag_counts(&state, &query)?))
+}
+
+/// The audit log, newest first, filtered by actor, action, DAG, run, task and time
+async fn get_audit(
+    State(state): State<AppState>,
+    Query(query): Query<AuditQuery>,
+) -> Result<Json<AuditResponse>, ApiError> {
+    Ok(Json(audit::list(&state, &query)?))
+}
+
+/// Sign in with a username and password; the session is kept in an HttpOnly cookie
+async fn login(
+    State(state): State<AppState>,
+    Json(payload): Json<LoginPayload>,
+) -> Result<Response, ApiError> {
+    let (secret, user) = auth::login(&state, &payload.username, &payload.password, Utc::now())?;
+    let status = AuthStatus {
+        auth_enabled: true,
+        user: Some(UserInfo::from(&user)),
+    };
+    let cookie = auth::session_cookie_header(&state.auth, Some(&secret));
+    Ok(([(header::SET_COOKIE, cookie)], Json(status)).into_response())
+}
+
+/// End the current session, if any, and clear its cookie
+async fn logout(State(state): State<AppState>, headers: HeaderMap) -> Result<Response, ApiError> {
+    if let Some(secret) = auth::session_cookie(&headers) {
+        auth::logout(&state, secret)?;
     }
-    
-    DAGGraph {
-        dag_id,
-        tasks,
-        edges,
+    let cookie = auth::session_cookie_header(&state.auth, None);
+    Ok(([(header::SET_COOKIE, cookie)], StatusCode::NO_CONTENT).into_response())
+}
+
+/// Whether auth is enabled and who, if anyone, is signed in
+async fn get_auth_status(
+    State(state): State<AppState>,
+    identity: Option<Identity>,
+) -> Result<Json<AuthStatus>, ApiError> {
+    if !state.auth.enabled {
+        return Ok(Json(AuthStatus {
+            auth_enabled: false,
+            user: None,
+        }));
     }
+    let user = match identity {
+        Some(identity) => state.users.get_user(&identity.username)?.map(|user| UserInfo::from(&user)),
+        None => None,
+    };
+    Ok(Json(AuthStatus {
+        auth_enabled: true,
+        user,
+    }))
 }
 
-fn generate_sample_dags() -> Vec<DAG> {
-    let mut rng = rand::thread_rng();
-    let now = Utc::now();
+async fn get_users(State(state): State<AppState>) -> Result<Json<Vec<UserInfo>>, ApiError> {
+    Ok(Json(state.users.list_users()?.iter().map(UserInfo::from).collect()))
+}
 
-    // Sample data for generating DAGs
-    let dag_name_prefixes = [
-        "etl_", "data_pipeline_", "process_", "transform_", "extract_", "load_", "sync_",
-        "analytics_", "report_", "backup_", "cleanup_", "validate_", "monitor_", "alert_",
-    ];
-
-    let dag_name_suffixes = [
-        "daily", "hourly", "weekly", "monthly", "sales", "inventory", "users", "events",
-        "transactions", "logs", "metrics", "alerts", "notifications", "products", "orders",
-        "shipments", "payments", "refunds", "customers", "suppliers",
-    ];
-    
-    // Add some DAGs with specific node counts for testing
-    let special_dags = [
-        ("large_dag_500", 500),
-        ("huge_dag_1000", 1000),
-        ("medium_dag_100", 100),
-        ("small_dag_20", 20),
-        ("tiny_dag_5", 5),
-    ];
-
-    let owners = [
-        "admin", "airflow", "john_doe", "jane_smith", "data_engineer", "data_scientist",
-        "data_analyst", "system_admin", "devops", "sre", "developer",
-    ];
-
-    let schedule_intervals = [
-        "* * * * *",           // Every minute
-        "*/5 * * * *",         // Every 5 minutes
-        "0 * * * *",           // Hourly
-        "0 */2 * * *",         // Every 2 hours
-        "0 0 * * *",           // Daily at midnight
-        "0 8 * * *",           // Daily at 8am
-        "0 0 * * 0",           // Weekly on Sunday
-        "0 0 1 * *",           // Monthly on the 1st
-        "0 0 1 1 *",           // Yearly on Jan 1st
-        "@hourly", "@daily", "@weekly", "@monthly", "@yearly",
-    ];
-
-    let tags = [
-        "production", "development", "staging", "testing", "data_warehouse", "data_lake",
-        "batch", "streaming", "etl", "ml", "ai", "reporting", "monitoring", "cleanup",
-        "validation", "transformation", "extraction", "loading", "high_priority", "low_priority",
-    ];
-
-    let create_dag = |i: usize| -> DAG {
-        let dag_id_prefix = dag_name_prefixes[i % dag_name_prefixes.len()];
-        let dag_id_suffix = dag_name_suffixes[i % dag_name_suffixes.len()];
-        let dag_id = format!(
-            "{}{}_{:03}",
-            dag_id_prefix,
-            dag_id_suffix,
-            i
-        );
-
-        let file_path = format!("/home/airflow/dags/{}.py", dag_id);
-
-        let owner = owners[i % owners.len()].to_string();
-        let paused = rng.gen_bool(0.2); // 20% chance of being paused
-        let created_at = now - Duration::hours(rng.gen_range(24..720));
-        let updated_at = created_at + Duration::hours(rng.gen_range(1..24));
-
-        let last_run = if rng.gen_bool(0.9) {
-            // 90% chance of having a last run
-            Some(now - Duration::hours(rng.gen_range(1..48)))
-        } else {
-            None
-        };
+async fn create_user(
+    State(state): State<AppState>,
+    identity: Identity,
+    Json(payload): Json<NewUserPayload>,
+) -> Result<(StatusCode, Json<UserInfo>), ApiError> {
+    let user = auth::create_user(&state, payload, &identity.username, Utc::now())?;
+    Ok((StatusCode::CREATED, Json(UserInfo::from(&user))))
+}
 
-        let next_run = if !paused {
-            Some(now + Duration::hours(rng.gen_range(1..48)))
-        } else {
-            None
-        };
+async fn update_user(
+    State(state): State<AppState>,
+    identity: Identity,
+    Path(username): Path<String>,
+    Json(payload): Json<UpdateUserPayload>,
+) -> Result<Json<UserInfo>, ApiError> {
+    let user = auth::update_user(&state, &username, payload, &identity.username, Utc::now())?;
+    Ok(Json(UserInfo::from(&user)))
+}
 
-        let runs_count = rng.gen_range(0..100);
-        let success_rate = rng.gen_range(0.5..0.99);
-        let success_count = (runs_count as f64 * success_rate) as usize;
-        let failed_count = runs_count - success_count;
-        let running_count = if rng.gen_bool(0.1) {
-            // 10% chance of having running tasks
-            rng.gen_range(1..5)
-        } else {
-            0
-        };
+async fn delete_user(
+    State(state): State<AppState>,
+    identity: Identity,
+    Path(username): Path<String>,
+) -> Result<StatusCode, ApiError> {
+    auth::delete_user(&state, &username, &identity.username)?;
+    Ok(StatusCode::NO_CONTENT)
+}
 
-        let schedule_interval = schedule_intervals[i % schedule_intervals.len()].to_string();
+/// The caller's own API tokens
+async fn get_tokens(State(state): State<AppState>, identity: Identity) -> Result<Json<Vec<ApiTokenInfo>>, ApiError> {
+    Ok(Json(state.users.list_tokens(&identity.username)?.iter().map(ApiTokenInfo::from).collect()))
+}
 
-        // Assign 1-3 tags randomly
-        let mut dag_tags = Vec::new();
-        let num_tags = rng.gen_range(1..=3);
-        let mut available_tags = tags.to_vec();
-        available_tags.shuffle(&mut rng);
-        for tag in available_tags.iter().take(num_tags) {
-            dag_tags.push(tag.to_string());
-        }
+async fn create_token(
+    State(state): State<AppState>,
+    identity: Identity,
+    Json(payload): Json<NewTokenPayload>,
+) -> Result<(StatusCode, Json<NewTokenResponse>), ApiError> {
+    if !state.auth.enabled {
+        return Err(ApiError::Conflict("API tokens need auth to be enabled".to_string()));
+    }
+    let response = auth::create_token(&state, &identity.username, payload, Utc::now())?;
+    Ok((StatusCode::CREATED, Json(response)))
+}
 
-        let description = if rng.gen_bool(0.8) {
-            // 80% chance of having a description
-            Some(format!("DAG for processing {} data", dag_name_suffixes[i % dag_name_suffixes.len()]))
-        } else {
-            None
-        };
+async fn revoke_token(
+    State(state): State<AppState>,
+    identity: Identity,
+    Path(token_id): Path<String>,
+) -> Result<StatusCode, ApiError> {
+    state.users.delete_token(&identity.username, &token_id)?;
+    tracing::info!("Revoked API token {} of {}", token_id, identity.username);
+    Ok(StatusCode::NO_CONTENT)
+}
 
-        DAG {
-            id: Uuid::new_v4(),
-            dag_id,
-            description,
-            file_path,
-            owner,
-            paused,
-            last_run,
-            next_run,
-            runs_count,
-            success_count,
-            failed_count,
-            running_count,
-            schedule_interval,
-            tags: dag_tags,
-            created_at,
-            updated_at,
-        }
-    };
+#[cfg(test)]
+mod tests {
+    use axum::body::{to_bytes, Body};
+    use axum::http::Request;
+    use axum::middleware;
+    use serde_json::Value;
+    use tower::ServiceExt;
 
-    // Create regular random DAGs
-    let mut dags: Vec<DAG> = (0..45).map(create_dag).collect();
-    
-    // Add special test DAGs with specific node counts
-    for (i, (name, node_count)) in special_dags.iter().enumerate() {
-        let dag_id = name.to_string();
-        let file_path = format!("/home/airflow/dags/{}.py", dag_id);
-        let owner = owners[i % owners.len()].to_string();
-        let paused = false; // Make these always active for testing
-        
-        let created_at = now - Duration::hours(rng.gen_range(24..720));
-        let updated_at = created_at + Duration::hours(rng.gen_range(1..24));
-        let last_run = Some(now - Duration::hours(rng.gen_range(1..24)));
-        let next_run = Some(now + Duration::hours(rng.gen_range(1..24)));
-        
-        let runs_count = rng.gen_range(5..20);
-        let success_count = runs_count - 1; // Almost always successful
-        let failed_count = 1; // Just one failure for testing
-        let running_count = 0; // Not running now
-        
-        let schedule_interval = "@daily".to_string();
-        
-        // Add specific tags for these test DAGs
-        let mut dag_tags = vec!["test".to_string(), "performance".to_string()];
-        dag_tags.push(format!("nodes_{}", name.split('_').last().unwrap_or("unknown")));
-        
-        let description = Some(format!("Test DAG with {} nodes", name.split('_').last().unwrap_or("many")));
-        
-        dags.push(DAG {
-            id: Uuid::new_v4(),
-            dag_id,
-            description,
-            file_path,
-            owner,
-            paused,
-            last_run,
-            next_run,
-            runs_count,
-            success_count,
-            failed_count,
-            running_count,
-            schedule_interval,
-            tags: dag_tags,
-            created_at,
-            updated_at,
-        });
+    use super::*;
+    use crate::config::AuthConfig;
+    use crate::logs::LogStore;
+    use crate::registry;
+
+    /// The API over in-memory stores, with auth disabled
+    fn app() -> Router {
+        let stores = registry::open("memory").unwrap();
+        let state = AppState::new(stores, LogStore::new(std::env::temp_dir()), AuthConfig::default());
+        Router::new()
+            .nest(
+                PREFIX,
+                routes().route_layer(middleware::from_fn_with_state(state.clone(), auth::authenticate)),
+            )
+            .with_state(state)
+    }
+
+    async fn send(app: &Router, method: &str, uri: &str, body: Value) -> (StatusCode, Value) {
+        let request = Request::builder()
+            .method(method)
+            .uri(uri)
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(Body::from(body.to_string()))
+            .unwrap();
+        let response = app.clone().oneshot(request).await.unwrap();
+        let status = response.status();
+        let bytes = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        (status, serde_json::from_slice(&bytes).unwrap_or(Value::Null))
+    }
+
+    fn task(id: &str) -> Value {
+        json!({
+            "id": id,
+            "name": id,
+            "status": "PENDING",
+            "duration": null,
+            "start_time": null,
+            "end_time": null,
+            "operator": "BashOperator",
+            "retries": 0,
+            "max_retries": 0,
+            "command": "true",
+        })
     }
-    
-    dags
-}
\ No newline at end of file
+
+    fn graph(edges: &[(&str, &str)]) -> Value {
+        json!({
+            "dag_id": "pipeline",
+            "tasks": [task("a"), task("b")],
+            "edges": edges
+                .iter()
+                .map(|(source, target)| json!({ "source": source, "target": target }))
+                .collect::<Vec<_>>(),
+        })
+    }
+
+    #[tokio::test]
+    async fn put_dag_graph_rejects_a_cycle() {
+        let app = app();
+        let dag = json!({ "dag_id": "pipeline", "owner": "me", "schedule_interval": "@daily" });
+        let (status, _) = send(&app, "POST", "/api/dags", dag).await;
+        assert_eq!(status, StatusCode::CREATED);
+
+        let (status, body) = send(&app, "PUT", "/api/dags/pipeline/graph", graph(&[("a", "b"), ("b", "a")])).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body["error"], "cycle detected between tasks: a, b");
+
+        let (status, body) = send(&app, "PUT", "/api/dags/pipeline/graph", graph(&[("a", "b")])).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["edges"].as_array().map(Vec::len), Some(1));
+    }
+}
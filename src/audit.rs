@@ -0,0 +1,53 @@
+use chrono::Utc;
+use serde_json::{Map, Value};
+
+use crate::models::{AuditAction, AuditEntry, AuditQuery, AuditResponse, DagRun};
+use crate::service::{self, ServiceError};
+use crate::state::AppState;
+
+/// Append an entry to the audit log. The action it records has already happened, so a failure
+/// to store it is logged rather than returned.
+pub fn record(state: &AppState, mut entry: AuditEntry, comment: Option<&str>) {
+    entry.comment = comment.map(str::trim).filter(|comment| !comment.is_empty()).map(str::to_string);
+    if let Err(err) = state.audit.append_audit(entry.clone()) {
+        tracing::error!(
+            "Failed to record {} of {} by {} in the audit log: {}",
+            entry.action,
+            entry.dag_id,
+            entry.actor,
+            err
+        );
+    }
+}
+
+/// Record a DAG or graph created, changed or deleted through the API
+pub fn config_change(
+    state: &AppState,
+    actor: &str,
+    dag_id: &str,
+    before: Option<Value>,
+    after: Option<Value>,
+) {
+    let mut entry = AuditEntry::new(Utc::now(), actor, AuditAction::ConfigChange, dag_id);
+    entry.before = before;
+    entry.after = after;
+    record(state, entry, None);
+}
+
+/// One page of the audit log, newest first
+pub fn list(state: &AppState, query: &AuditQuery) -> Result<AuditResponse, ServiceError> {
+    let (offset, limit) = service::page_window(query.page, query.limit, 50);
+    let (entries, total_count) = state.audit.list_audit(query, offset, limit)?;
+    Ok(AuditResponse { entries, total_count })
+}
+
+/// The states of some task instances of a run, as `{task_id: status}`
+pub fn task_states(run: &DagRun, task_ids: &[String]) -> Value {
+    let states: Map<String, Value> = run
+        .task_instances
+        .iter()
+        .filter(|ti| task_ids.contains(&ti.task_id))
+        .map(|ti| (ti.task_id.clone(), serde_json::to_value(&ti.status).unwrap_or(Value::Null)))
+        .collect();
+    Value::Object(states)
+}
@@ -0,0 +1,377 @@
+use argon2::password_hash::rand_core::{OsRng, RngCore};
+use argon2::password_hash::{PasswordHash, PasswordHasher, PasswordVerifier, SaltString};
+use argon2::Argon2;
+use axum::{
+    async_trait,
+    extract::{FromRequestParts, Request, State},
+    http::{header, request::Parts, HeaderMap},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use chrono::{DateTime, Duration, Utc};
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use uuid::Uuid;
+
+use crate::api::ApiError;
+use crate::config::AuthConfig;
+use crate::models::{
+    ApiToken, ApiTokenInfo, DagGrant, NewTokenPayload, NewTokenResponse, NewUserPayload, Role, Session,
+    UpdateUserPayload, User,
+};
+use crate::registry::{RegistryError, UserStore};
+use crate::service::ServiceError;
+use crate::state::AppState;
+use crate::utils::hex;
+
+/// Cookie holding the secret of a browser session
+pub const SESSION_COOKIE: &str = "cyclonetix_session";
+
+/// Marks API tokens, so they are easy to spot in scripts and secret scanners
+const TOKEN_PREFIX: &str = "ctx_";
+
+const MIN_PASSWORD_LENGTH: usize = 8;
+
+/// Argon2id hash, with the default parameters, of a password nobody uses. Checked against when
+/// the user is unknown or disabled, so a failed sign-in takes as long whether or not the user
+/// exists.
+const DUMMY_PASSWORD_HASH: &str =
+    "$argon2id$v=19$m=19456,t=2,p=1$Yho43tZJcVP0O3SggJD3Nw$j9i8IS8LSs5LAB5h+dfsqweGOHGFxBoi5GtfFXiXnlw";
+
+/// Routes that answer without a signed-in user
+const PUBLIC_PATHS: &[&str] = &["/auth/login", "/auth/logout", "/auth/me"];
+
+/// Who is making a request. With auth disabled every request is made by `anonymous`, who may
+/// do anything.
+#[derive(Debug, Clone)]
+pub struct Identity {
+    pub username: String,
+    pub role: Role,
+    pub grants: Vec<DagGrant>,
+}
+
+impl Identity {
+    pub fn anonymous() -> Self {
+        Self {
+            username: "anonymous".to_string(),
+            role: Role::Admin,
+            grants: Vec::new(),
+        }
+    }
+}
+
+impl From<&User> for Identity {
+    fn from(user: &User) -> Self {
+        Self {
+            username: user.username.clone(),
+            role: user.role,
+            grants: user.grants.clone(),
+        }
+    }
+}
+
+/// Set by `authenticate`; a handler asking for an identity on a public path without one is
+/// refused
+#[async_trait]
+impl<S: Send + Sync> FromRequestParts<S> for Identity {
+    type Rejection = ApiError;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        parts
+            .extensions
+            .get::<Identity>()
+            .cloned()
+            .ok_or_else(|| ApiError::Unauthorized("sign in required".to_string()))
+    }
+}
+
+/// Middleware for the API routes: works out who is calling from the session cookie or an
+/// `Authorization: Bearer` token and refuses anonymous calls to everything but `PUBLIC_PATHS`
+pub async fn authenticate(State(state): State<AppState>, mut request: Request, next: Next) -> Response {
+    if !state.auth.enabled {
+        request.extensions_mut().insert(Identity::anonymous());
+        return next.run(request).await;
+    }
+    match identify(&state, request.headers(), Utc::now()) {
+        Ok(Some(identity)) => {
+            request.extensions_mut().insert(identity);
+        }
+        Ok(None) if PUBLIC_PATHS.contains(&request.uri().path()) => {}
+        Ok(None) => return ApiError::Unauthorized("sign in required".to_string()).into_response(),
+        Err(err) => return err.into_response(),
+    }
+    next.run(request).await
+}
+
+/// The user behind a request's bearer token or session cookie. A bearer token that is not
+/// valid is an error rather than anonymous, so a script with a revoked token fails loudly.
+fn identify(state: &AppState, headers: &HeaderMap, now: DateTime<Utc>) -> Result<Option<Identity>, ApiError> {
+    if let Some(token) = bearer_token(headers) {
+        let token = state
+            .users
+            .get_token_by_digest(&digest(state, token)?)?
+            .filter(|token| token.expires_at.is_none_or(|expires_at| expires_at > now))
+            .ok_or_else(|| ApiError::Unauthorized("invalid or expired API token".to_string()))?;
+        let user = active_user(state.users.as_ref(), &token.username)?
+            .ok_or_else(|| ApiError::Unauthorized("invalid or expired API token".to_string()))?;
+        state.users.touch_token(&token.id, now)?;
+        return Ok(Some(Identity::from(&user)));
+    }
+
+    let Some(secret) = session_cookie(headers) else {
+        return Ok(None);
+    };
+    let Some(session) = state.users.get_session(&digest(state, secret)?)? else {
+        return Ok(None);
+    };
+    if session.expires_at <= now {
+        state.users.delete_session(&session.digest)?;
+        return Ok(None);
+    }
+    Ok(active_user(state.users.as_ref(), &session.username)?.map(|user| Identity::from(&user)))
+}
+
+/// A user that exists and is not disabled
+fn active_user(users: &dyn UserStore, username: &str) -> Result<Option<User>, RegistryError> {
+    Ok(users.get_user(username)?.filter(|user| !user.disabled))
+}
+
+fn bearer_token(headers: &HeaderMap) -> Option<&str> {
+    headers
+        .get(header::AUTHORIZATION)?
+        .to_str()
+        .ok()?
+        .strip_prefix("Bearer ")
+        .map(str::trim)
+}
+
+/// The value of the session cookie, if the request carries one
+pub fn session_cookie(headers: &HeaderMap) -> Option<&str> {
+    headers
+        .get_all(header::COOKIE)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(';'))
+        .find_map(|pair| pair.trim().strip_prefix(SESSION_COOKIE)?.strip_prefix('='))
+        .filter(|secret| !secret.is_empty())
+}
+
+/// `Set-Cookie` value for a new session, or one that clears the cookie when `secret` is `None`
+pub fn session_cookie_header(config: &AuthConfig, secret: Option<&str>) -> String {
+    let (secret, max_age_secs) = match secret {
+        Some(secret) => (secret, config.session_ttl_secs),
+        None => ("", 0),
+    };
+    let secure = if config.secure_cookie { "; Secure" } else { "" };
+    format!(
+        "{}={}; Path=/; HttpOnly; SameSite=Lax{}; Max-Age={}",
+        SESSION_COOKIE, secret, secure, max_age_secs
+    )
+}
+
+/// Hex HMAC-SHA256 of a session or token secret, keyed with `auth.secret_key`. Only digests are
+/// stored, so a copy of the database does not let anyone sign in. Changing the key signs
+/// everyone out and invalidates every API token.
+pub fn digest(state: &AppState, secret: &str) -> Result<String, ServiceError> {
+    let key = state
+        .auth
+        .secret_key
+        .as_deref()
+        .filter(|key| !key.is_empty())
+        .ok_or_else(|| ServiceError::Conflict("auth.secret_key must be set for sessions and API tokens".to_string()))?;
+    let mut mac = Hmac::<Sha256>::new_from_slice(key.as_bytes())
+        .map_err(|err| ServiceError::Invalid(err.to_string()))?;
+    mac.update(secret.as_bytes());
+    Ok(hex(&mac.finalize().into_bytes()))
+}
+
+/// 32 random bytes, hex encoded
+fn new_secret() -> String {
+    let mut bytes = [0u8; 32];
+    OsRng.fill_bytes(&mut bytes);
+    hex(&bytes)
+}
+
+/// Argon2id hash of a password in PHC string format
+pub fn hash_password(password: &str) -> Result<String, ServiceError> {
+    if password.chars().count() < MIN_PASSWORD_LENGTH {
+        return Err(ServiceError::Invalid(format!(
+            "password must be at least {} characters",
+            MIN_PASSWORD_LENGTH
+        )));
+    }
+    let salt = SaltString::generate(&mut OsRng);
+    Argon2::default()
+        .hash_password(password.as_bytes(), &salt)
+        .map(|hash| hash.to_string())
+        .map_err(|err| ServiceError::Invalid(format!("cannot hash password: {}", err)))
+}
+
+pub fn verify_password(password: &str, hash: &str) -> bool {
+    PasswordHash::new(hash)
+        .is_ok_and(|hash| Argon2::default().verify_password(password.as_bytes(), &hash).is_ok())
+}
+
+/// Check a username and password and start a session. Returns the session secret to hand to
+/// the browser, and the user.
+pub fn login(state: &AppState, username: &str, password: &str, now: DateTime<Utc>) -> Result<(String, User), ServiceError> {
+    if !state.auth.enabled {
+        return Err(ServiceError::Conflict("auth is disabled".to_string()));
+    }
+    let user = match active_user(state.users.as_ref(), username)? {
+        Some(user) => Some(user).filter(|user| verify_password(password, &user.password_hash)),
+        None => {
+            verify_password(password, DUMMY_PASSWORD_HASH);
+            None
+        }
+    }
+    .ok_or_else(|| ServiceError::Unauthorized("invalid username or password".to_string()))?;
+
+    let expired = state.users.delete_expired_sessions(now)?;
+    if expired > 0 {
+        tracing::debug!("Dropped {} expired sessions", expired);
+    }
+    let secret = new_secret();
+    state.users.create_session(Session {
+        digest: digest(state, &secret)?,
+        username: user.username.clone(),
+        created_at: now,
+        expires_at: now + Duration::seconds(state.auth.session_ttl_secs as i64),
+    })?;
+    let user = state.users.update_user(User {
+        last_login: Some(now),
+        ..user
+    })?;
+    tracing::info!("{} signed in", user.username);
+    Ok((secret, user))
+}
+
+/// End the session with the given secret, if there is one
+pub fn logout(state: &AppState, secret: &str) -> Result<(), ServiceError> {
+    state.users.delete_session(&digest(state, secret)?)?;
+    Ok(())
+}
+
+fn check_username(username: &str) -> Result<(), ServiceError> {
+    let valid = !username.is_empty()
+        && username.len() <= 64
+        && username
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.' | '@'));
+    if valid && username != Identity::anonymous().username {
+        Ok(())
+    } else {
+        Err(ServiceError::Invalid(format!(
+            "invalid username '{}': use letters, digits, '_', '-', '.' and '@'",
+            username
+        )))
+    }
+}
+
+pub fn create_user(state: &AppState, payload: NewUserPayload, actor: &str, now: DateTime<Utc>) -> Result<User, ServiceError> {
+    check_username(&payload.username)?;
+    let user = state.users.create_user(User {
+        username: payload.username,
+        display_name: payload.display_name.filter(|name| !name.trim().is_empty()),
+        password_hash: hash_password(&payload.password)?,
+        role: payload.role,
+        grants: payload.grants,
+        disabled: false,
+        created_at: now,
+        updated_at: now,
+        last_login: None,
+    })?;
+    tracing::info!("Created user {} for {}", user.username, actor);
+    Ok(user)
+}
+
+/// Change a user's details. Disabling a user also signs them out everywhere.
+pub fn update_user(
+    state: &AppState,
+    username: &str,
+    payload: UpdateUserPayload,
+    actor: &str,
+    now: DateTime<Utc>,
+) -> Result<User, ServiceError> {
+    let mut user = state
+        .users
+        .get_user(username)?
+        .ok_or_else(|| RegistryError::UserNotFound(username.to_string()))?;
+    if let Some(display_name) = payload.display_name {
+        user.display_name = Some(display_name).filter(|name| !name.trim().is_empty());
+    }
+    if let Some(password) = &payload.password {
+        user.password_hash = hash_password(password)?;
+    }
+    if let Some(role) = payload.role {
+        user.role = role;
+    }
+    if let Some(grants) = payload.grants {
+        user.grants = grants;
+    }
+    if let Some(disabled) = payload.disabled {
+        user.disabled = disabled;
+    }
+    if user.role != Role::Admin || user.disabled {
+        ensure_other_admin(state, username)?;
+    }
+    user.updated_at = now;
+    let user = state.users.update_user(user)?;
+    tracing::info!("Updated user {} for {}", username, actor);
+    Ok(user)
+}
+
+pub fn delete_user(state: &AppState, username: &str, actor: &str) -> Result<(), ServiceError> {
+    ensure_other_admin(state, username)?;
+    state.users.delete_user(username)?;
+    tracing::info!("Deleted user {} for {}", username, actor);
+    Ok(())
+}
+
+/// Refuse to take away the last enabled administrator, which would lock everyone out of user
+/// management
+fn ensure_other_admin(state: &AppState, username: &str) -> Result<(), ServiceError> {
+    let users = state.users.list_users()?;
+    let enabled_admin = |user: &&User| user.role == Role::Admin && !user.disabled;
+    let is_admin = users.iter().filter(enabled_admin).any(|user| user.username == username);
+    let others = users.iter().filter(enabled_admin).any(|user| user.username != username);
+    if is_admin && !others {
+        return Err(ServiceError::Conflict(format!(
+            "'{}' is the only enabled administrator",
+            username
+        )));
+    }
+    Ok(())
+}
+
+/// Mint an API token for a user. The token is returned once and only its digest is kept.
+pub fn create_token(
+    state: &AppState,
+    username: &str,
+    payload: NewTokenPayload,
+    now: DateTime<Utc>,
+) -> Result<NewTokenResponse, ServiceError> {
+    if payload.name.trim().is_empty() {
+        return Err(ServiceError::Invalid("token name must not be empty".to_string()));
+    }
+    if state.users.get_user(username)?.is_none() {
+        return Err(RegistryError::UserNotFound(username.to_string()).into());
+    }
+    let secret = format!("{}{}", TOKEN_PREFIX, new_secret());
+    let token = state.users.create_token(ApiToken {
+        id: Uuid::new_v4().to_string(),
+        username: username.to_string(),
+        name: payload.name.trim().to_string(),
+        digest: digest(state, &secret)?,
+        created_at: now,
+        expires_at: payload
+            .expires_in_days
+            .map(|days| now + Duration::days(i64::from(days))),
+        last_used: None,
+    })?;
+    tracing::info!("Created API token {} ({}) for {}", token.id, token.name, username);
+    Ok(NewTokenResponse {
+        token: secret,
+        info: ApiTokenInfo::from(&token),
+    })
+}
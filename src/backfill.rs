@@ -0,0 +1,176 @@
+use std::collections::HashSet;
+
+use chrono::{DateTime, Utc};
+use serde_json::json;
+use uuid::Uuid;
+
+use crate::audit;
+use crate::models::{
+    AuditAction, AuditEntry, BackfillInfo, BackfillPayload, BackfillProgress, BackfillResponse, RunState, RunType,
+};
+use crate::params;
+use crate::registry::{RegistryError, RunStore};
+use crate::schedule::{self, Schedule};
+use crate::scheduler;
+use crate::service::ServiceError;
+use crate::state::AppState;
+
+/// Most runs a single backfill may create
+pub const MAX_BACKFILL_RUNS: usize = 1000;
+
+/// Fire times of `schedule` between `start` and `end`, both inclusive
+pub fn logical_dates(
+    schedule: &Schedule,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+) -> Result<Vec<DateTime<Utc>>, ServiceError> {
+    if start > end {
+        return Err(ServiceError::Invalid("start_date must not be after end_date".to_string()));
+    }
+
+    let mut dates = Vec::new();
+    let mut cursor = start - chrono::Duration::seconds(1);
+    while let Some(next) = schedule.next_after(cursor) {
+        if next > end {
+            break;
+        }
+        if dates.len() == MAX_BACKFILL_RUNS {
+            return Err(ServiceError::Invalid(format!(
+                "the range covers more than {} schedule slots; split it into smaller backfills",
+                MAX_BACKFILL_RUNS
+            )));
+        }
+        dates.push(next);
+        cursor = next;
+    }
+    Ok(dates)
+}
+
+/// Create a queued BACKFILL run for every schedule slot of the DAG in the requested range. Slots
+/// the DAG already has a run for are skipped. The scheduler starts the runs oldest first, at
+/// most `max_active_runs` at a time.
+pub fn create_backfill(
+    state: &AppState,
+    dag_id: &str,
+    payload: BackfillPayload,
+    actor: &str,
+    now: DateTime<Utc>,
+) -> Result<BackfillResponse, ServiceError> {
+    if payload.max_active_runs == 0 {
+        return Err(ServiceError::Invalid("max_active_runs must be at least 1".to_string()));
+    }
+    let dag = state
+        .registry
+        .get(dag_id)?
+        .ok_or_else(|| RegistryError::NotFound(dag_id.to_string()))?;
+    let graph = state
+        .registry
+        .get_graph(dag_id)?
+        .ok_or_else(|| ServiceError::Invalid(format!("DAG '{}' has no graph to run", dag_id)))?;
+    let conf = params::resolve_conf(dag.params_schema.as_ref(), payload.conf)?;
+
+    let schedule = schedule::dag_schedule(&dag)?;
+    let existing: HashSet<DateTime<Utc>> = state
+        .runs
+        .list_runs(dag_id)?
+        .into_iter()
+        .map(|run| run.logical_date)
+        .collect();
+    let (mut skipped, logical_dates): (Vec<_>, Vec<_>) =
+        logical_dates(&schedule, payload.start_date, payload.end_date)?
+            .into_iter()
+            .partition(|date| existing.contains(date));
+
+    let backfill_id = format!("backfill_{}", Uuid::new_v4().simple());
+    let pending = |backfill_id: &str, total: usize| BackfillProgress {
+        backfill_id: backfill_id.to_string(),
+        dag_id: dag_id.to_string(),
+        total,
+        queued: total,
+        running: 0,
+        succeeded: 0,
+        failed: 0,
+    };
+    if payload.dry_run {
+        return Ok(BackfillResponse {
+            progress: pending(&backfill_id, logical_dates.len()),
+            backfill_id,
+            dry_run: true,
+            logical_dates,
+            skipped,
+        });
+    }
+
+    let mut created = Vec::new();
+    for logical_date in logical_dates {
+        let run_id = format!("backfill__{}", logical_date.to_rfc3339());
+        let mut run = scheduler::new_run(&graph, run_id, RunType::BACKFILL, logical_date, now);
+        run.conf = conf.clone();
+        run.backfill = Some(BackfillInfo {
+            backfill_id: backfill_id.clone(),
+            max_active_runs: payload.max_active_runs,
+        });
+        run.triggered_by = Some(actor.to_string());
+        match state.runs.create_run(run) {
+            Ok(_) => created.push(logical_date),
+            Err(RegistryError::RunAlreadyExists { .. }) => skipped.push(logical_date),
+            Err(err) => return Err(err.into()),
+        }
+    }
+
+    tracing::info!(
+        "Created backfill {} of {} with {} runs ({} slots skipped) for {}",
+        backfill_id,
+        dag_id,
+        created.len(),
+        skipped.len(),
+        actor
+    );
+    let mut entry = AuditEntry::new(now, actor, AuditAction::Backfill, dag_id);
+    entry.after = Some(json!({
+        "backfill_id": backfill_id,
+        "start_date": payload.start_date,
+        "end_date": payload.end_date,
+        "runs": created.len(),
+        "skipped": skipped.len(),
+    }));
+    audit::record(state, entry, payload.comment.as_deref());
+    Ok(BackfillResponse {
+        progress: pending(&backfill_id, created.len()),
+        backfill_id,
+        dry_run: false,
+        logical_dates: created,
+        skipped,
+    })
+}
+
+/// Run counts of a backfill by state, or `None` when it has no runs
+pub fn backfill_progress(
+    runs: &dyn RunStore,
+    dag_id: &str,
+    backfill_id: &str,
+) -> Result<Option<BackfillProgress>, RegistryError> {
+    let mut progress = BackfillProgress {
+        backfill_id: backfill_id.to_string(),
+        dag_id: dag_id.to_string(),
+        total: 0,
+        queued: 0,
+        running: 0,
+        succeeded: 0,
+        failed: 0,
+    };
+    let members = runs
+        .list_runs(dag_id)?
+        .into_iter()
+        .filter(|run| run.backfill.as_ref().is_some_and(|info| info.backfill_id == backfill_id));
+    for run in members {
+        progress.total += 1;
+        match run.state {
+            RunState::QUEUED => progress.queued += 1,
+            RunState::RUNNING => progress.running += 1,
+            RunState::SUCCEEDED => progress.succeeded += 1,
+            RunState::FAILED => progress.failed += 1,
+        }
+    }
+    Ok((progress.total > 0).then_some(progress))
+}
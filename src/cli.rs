@@ -0,0 +1,870 @@
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use chrono::{DateTime, NaiveDate, Utc};
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use serde::Serialize;
+use serde_json::{Map, Value};
+
+use crate::audit;
+use crate::auth;
+use crate::backfill;
+use crate::config::Config;
+use crate::loader;
+use crate::logs::LogStore;
+use crate::models::{
+    ApiTokenInfo, AuditAction, AuditEntry, AuditQuery, BackfillPayload, ClearPayload, DAGsQuery, DagGrant, DagRun,
+    DagRunsQuery, NewTokenPayload, NewUserPayload, Role, TagsMode, TagsQuery, TriggerRunPayload, UpdateUserPayload,
+    UserInfo, DAG,
+};
+use crate::registry::{self, SqliteRegistry};
+use crate::service;
+use crate::state::AppState;
+
+/// `println!` for command output. Once stdout is closed, as when piped into `head`, the command
+/// ends quietly instead of panicking.
+macro_rules! out {
+    ($($arg:tt)*) => {{
+        use std::io::Write;
+        if writeln!(std::io::stdout(), $($arg)*).is_err() {
+            std::process::exit(0);
+        }
+    }};
+}
+
+#[derive(Debug, Parser)]
+#[command(name = "cyclonetix", version, about = "Workflow scheduler and web UI")]
+pub struct Cli {
+    /// How commands print their results
+    #[arg(long, short, global = true, value_enum, default_value_t = Output::Table)]
+    pub output: Output,
+    /// Config file to use instead of `cyclonetix.toml`; also read from `CYCLONETIX_CONFIG`
+    #[arg(long, global = true)]
+    pub config: Option<PathBuf>,
+    /// Defaults to `serve`
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Output {
+    Table,
+    Json,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// Run the web server and scheduler
+    Serve,
+    /// List, pause, trigger and validate DAGs
+    #[command(subcommand)]
+    Dags(DagsCommand),
+    /// Inspect DAG runs
+    #[command(subcommand)]
+    Runs(RunsCommand),
+    /// Act on task instances
+    #[command(subcommand)]
+    Tasks(TasksCommand),
+    /// Queue a run for every schedule slot in a date range and follow its progress
+    Backfill(BackfillArgs),
+    /// Show who paused, triggered, cleared or changed what, newest first
+    Audit(AuditArgs),
+    /// Manage the database
+    #[command(subcommand)]
+    Db(DbCommand),
+    /// Manage local user accounts
+    #[command(subcommand)]
+    Users(UsersCommand),
+    /// Manage the API tokens of a user
+    #[command(subcommand)]
+    Tokens(TokensCommand),
+    /// Inspect the configuration
+    #[command(subcommand)]
+    Config(ConfigCommand),
+}
+
+#[derive(Debug, Subcommand)]
+pub enum DagsCommand {
+    /// List registered DAGs
+    List {
+        /// Search query, e.g. `owner:me tag:etl -status:paused "sales"`; also `id:`, `schedule:`, OR and `*`
+        #[arg(long)]
+        search: Option<String>,
+        /// Comma-separated tags; a DAG needs one of them, or all with `--tags-mode all`
+        #[arg(long)]
+        tags: Option<String>,
+        /// any or all
+        #[arg(long, default_value = "any")]
+        tags_mode: TagsMode,
+        /// active, paused, success, failed or running
+        #[arg(long)]
+        status: Option<String>,
+        /// Comma-separated `field` or `field:asc|desc` keys, e.g. `failed_count:desc,dag_id`
+        #[arg(long, default_value = "dag_id")]
+        sort: String,
+        /// Continue after the cursor printed by a previous listing
+        #[arg(long)]
+        cursor: Option<String>,
+        #[arg(long, default_value_t = 100)]
+        limit: usize,
+    },
+    /// Stop scheduling new runs of a DAG
+    Pause {
+        dag_id: String,
+        /// Why, for the audit log
+        #[arg(long)]
+        comment: Option<String>,
+    },
+    /// Resume scheduling a DAG from now on
+    Unpause {
+        dag_id: String,
+        /// Why, for the audit log
+        #[arg(long)]
+        comment: Option<String>,
+    },
+    /// Queue a manual run
+    Trigger {
+        dag_id: String,
+        /// JSON object handed to every task
+        #[arg(long, value_parser = parse_conf)]
+        conf: Option<Map<String, Value>>,
+        /// Defaults to now
+        #[arg(long, value_parser = parse_date)]
+        logical_date: Option<DateTime<Utc>>,
+        /// Why, for the audit log
+        #[arg(long)]
+        comment: Option<String>,
+    },
+    /// Check a DAG definition file without registering it
+    Validate { file: PathBuf },
+    /// Count the DAGs carrying each tag
+    Tags {
+        /// Only count DAGs matching this search query
+        #[arg(long)]
+        search: Option<String>,
+        /// active, paused, success, failed or running
+        #[arg(long)]
+        status: Option<String>,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+pub enum RunsCommand {
+    /// List the runs of a DAG, newest first
+    List {
+        dag_id: String,
+        /// Only runs in this state (queued, running, succeeded, failed)
+        #[arg(long)]
+        state: Option<String>,
+        #[arg(long, default_value_t = 25)]
+        limit: usize,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+pub enum TasksCommand {
+    /// Reset a task instance to pending so it runs again
+    Clear {
+        dag_id: String,
+        run_id: String,
+        task_id: String,
+        /// Also clear the tasks upstream of this one
+        #[arg(long)]
+        upstream: bool,
+        /// Also clear the tasks downstream of this one
+        #[arg(long)]
+        downstream: bool,
+        /// Only clear direct neighbours rather than everything up- or downstream
+        #[arg(long)]
+        no_recursive: bool,
+        /// Only clear related tasks that failed
+        #[arg(long)]
+        failed_only: bool,
+        /// List the tasks that would be cleared without changing anything
+        #[arg(long)]
+        dry_run: bool,
+        /// Why, for the audit log
+        #[arg(long)]
+        comment: Option<String>,
+    },
+}
+
+#[derive(Debug, Args)]
+pub struct BackfillArgs {
+    dag_id: String,
+    /// First logical date, inclusive: RFC 3339 or YYYY-MM-DD (midnight UTC)
+    #[arg(long, value_parser = parse_date)]
+    start: DateTime<Utc>,
+    /// Last logical date, inclusive: RFC 3339 or YYYY-MM-DD (midnight UTC)
+    #[arg(long, value_parser = parse_date)]
+    end: DateTime<Utc>,
+    /// Runs of the backfill allowed to be active at once
+    #[arg(long, default_value_t = 1)]
+    max_active_runs: usize,
+    /// JSON object handed to every task of every run
+    #[arg(long, value_parser = parse_conf)]
+    conf: Option<Map<String, Value>>,
+    /// Only list the logical dates that would be run
+    #[arg(long)]
+    dry_run: bool,
+    /// Exit once the runs are queued instead of following them
+    #[arg(long)]
+    no_wait: bool,
+    /// Why, for the audit log
+    #[arg(long)]
+    comment: Option<String>,
+}
+
+#[derive(Debug, Args)]
+pub struct AuditArgs {
+    #[arg(long)]
+    dag_id: Option<String>,
+    #[arg(long)]
+    run_id: Option<String>,
+    #[arg(long)]
+    task_id: Option<String>,
+    #[arg(long)]
+    actor: Option<String>,
+    /// pause, unpause, trigger, backfill, clear, force-success, skip, set-task-status or config-change
+    #[arg(long)]
+    action: Option<AuditAction>,
+    /// Entries at or after this time: RFC 3339 or YYYY-MM-DD (midnight UTC)
+    #[arg(long, value_parser = parse_date)]
+    since: Option<DateTime<Utc>>,
+    /// Entries before this time: RFC 3339 or YYYY-MM-DD (midnight UTC)
+    #[arg(long, value_parser = parse_date)]
+    until: Option<DateTime<Utc>>,
+    #[arg(long, default_value_t = 50)]
+    limit: usize,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum DbCommand {
+    /// Upgrade the data stored in the database to the format of this build
+    Migrate,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum UsersCommand {
+    /// List user accounts
+    List,
+    /// Create a user; the password is read from the first line of stdin
+    Create {
+        username: String,
+        #[arg(long)]
+        display_name: Option<String>,
+        /// viewer, operator or admin
+        #[arg(long, default_value_t = Role::Viewer)]
+        role: Role,
+        /// A role on matching DAGs, as owner:<owner>=<role> or tag:<tag>=<role>; repeatable
+        #[arg(long = "grant")]
+        grants: Vec<DagGrant>,
+    },
+    /// Change a user's details
+    Update {
+        username: String,
+        #[arg(long)]
+        display_name: Option<String>,
+        #[arg(long)]
+        role: Option<Role>,
+        /// Replace the user's grants with these; repeatable
+        #[arg(long = "grant")]
+        grants: Vec<DagGrant>,
+        /// Remove all of the user's grants
+        #[arg(long, conflicts_with = "grants")]
+        clear_grants: bool,
+        /// A disabled user cannot sign in and their tokens are refused
+        #[arg(long)]
+        disabled: Option<bool>,
+        /// Set a new password, read from the first line of stdin
+        #[arg(long)]
+        password_stdin: bool,
+    },
+    /// Delete a user with their sessions and tokens
+    Delete { username: String },
+}
+
+#[derive(Debug, Subcommand)]
+pub enum TokensCommand {
+    /// List a user's tokens
+    List { username: String },
+    /// Create a token for a user and print it; it cannot be shown again
+    Create {
+        username: String,
+        /// What the token is for
+        #[arg(long)]
+        name: String,
+        /// Never expires when not given
+        #[arg(long)]
+        expires_in_days: Option<u32>,
+    },
+    /// Revoke one of a user's tokens
+    Revoke { username: String, token_id: String },
+}
+
+#[derive(Debug, Subcommand)]
+pub enum ConfigCommand {
+    /// Print the effective settings, with secrets redacted
+    Show,
+}
+
+/// Run a command other than `serve`. Returns whether it succeeded; errors are printed by the
+/// caller.
+pub async fn run(
+    command: Command,
+    config: &Config,
+    config_file: Option<&Path>,
+    output: Output,
+) -> Result<bool, String> {
+    match command {
+        Command::Serve => Err("serve is handled by main".to_string()),
+        Command::Config(ConfigCommand::Show) => {
+            show_config(config, config_file, output);
+            Ok(true)
+        }
+        Command::Dags(command) => dags(config, command, output),
+        Command::Db(DbCommand::Migrate) => migrate(config, output),
+        Command::Users(command) => users(config, command, output),
+        Command::Tokens(command) => tokens(config, command, output),
+        Command::Runs(RunsCommand::List { dag_id, state: run_state, limit }) => {
+            let query = DagRunsQuery {
+                page: None,
+                limit: Some(limit),
+                state: run_state,
+            };
+            let response = service::list_runs(&open_state(config)?, &dag_id, &query).map_err(|err| err.to_string())?;
+            print_runs(&response.runs, output);
+            Ok(true)
+        }
+        Command::Tasks(TasksCommand::Clear {
+            dag_id,
+            run_id,
+            task_id,
+            upstream,
+            downstream,
+            no_recursive,
+            failed_only,
+            dry_run,
+            comment,
+        }) => {
+            let payload = ClearPayload {
+                upstream,
+                downstream,
+                recursive: !no_recursive,
+                failed_only,
+                dry_run,
+                comment,
+            };
+            let response = service::clear_task(&open_state(config)?, &dag_id, &run_id, &task_id, &payload, &actor(), Utc::now())
+                .map_err(|err| err.to_string())?;
+            match output {
+                Output::Json => print_json(&response),
+                Output::Table => {
+                    let verb = if response.dry_run { "would clear" } else { "cleared" };
+                    let rows = response.task_ids.iter().map(|id| vec![id.clone(), verb.to_string()]).collect();
+                    print_table(&["TASK", "ACTION"], rows);
+                }
+            }
+            Ok(true)
+        }
+        Command::Backfill(args) => backfill(open_state(config)?, args, output).await,
+        Command::Audit(args) => {
+            let query = AuditQuery {
+                actor: args.actor,
+                action: args.action,
+                dag_id: args.dag_id,
+                run_id: args.run_id,
+                task_id: args.task_id,
+                since: args.since,
+                until: args.until,
+                page: None,
+                limit: Some(args.limit),
+            };
+            let response = audit::list(&open_state(config)?, &query).map_err(|err| err.to_string())?;
+            print_audit(&response.entries, output);
+            Ok(true)
+        }
+    }
+}
+
+fn dags(config: &Config, command: DagsCommand, output: Output) -> Result<bool, String> {
+    if let DagsCommand::Validate { file } = &command {
+        return Ok(validate(file, output));
+    }
+    let state = open_state(config)?;
+    match command {
+        DagsCommand::List {
+            search,
+            tags,
+            tags_mode,
+            status,
+            sort,
+            cursor,
+            limit,
+        } => {
+            let query = DAGsQuery {
+                page: None,
+                limit: Some(limit),
+                search,
+                status,
+                tags,
+                tags_mode: Some(tags_mode),
+                sort_by: Some(sort),
+                sort_order: None,
+                cursor: Some(cursor.unwrap_or_default()),
+            };
+            let response = service::list_dags(&state, &query).map_err(|err| err.to_string())?;
+            print_dags(&response.dags, output);
+            if let (Output::Table, Some(next_cursor)) = (output, &response.next_cursor) {
+                eprintln!(
+                    "{} of {} DAGs shown; continue with --cursor {}",
+                    response.dags.len(),
+                    response.total_count,
+                    next_cursor
+                );
+            }
+        }
+        DagsCommand::Pause { dag_id, comment } => {
+            let dag = service::set_paused(&state, &dag_id, true, comment.as_deref(), &actor(), Utc::now())
+                .map_err(|err| err.to_string())?;
+            print_dags(&[dag], output);
+        }
+        DagsCommand::Unpause { dag_id, comment } => {
+            let dag = service::set_paused(&state, &dag_id, false, comment.as_deref(), &actor(), Utc::now())
+                .map_err(|err| err.to_string())?;
+            print_dags(&[dag], output);
+        }
+        DagsCommand::Trigger {
+            dag_id,
+            conf,
+            logical_date,
+            comment,
+        } => {
+            let payload = TriggerRunPayload {
+                logical_date,
+                conf: conf.unwrap_or_default(),
+                comment,
+            };
+            let run = service::trigger_run(&state, &dag_id, payload, &actor(), Utc::now()).map_err(|err| err.to_string())?;
+            print_runs(&[run], output);
+        }
+        DagsCommand::Tags { search, status } => {
+            let response = service::tag_counts(&state, &TagsQuery { search, status }).map_err(|err| err.to_string())?;
+            if output == Output::Json {
+                print_json(&response.tags);
+            } else {
+                let rows = response.tags.iter().map(|tag| vec![tag.tag.clone(), tag.count.to_string()]).collect();
+                print_table(&["TAG", "DAGS"], rows);
+            }
+        }
+        DagsCommand::Validate { .. } => unreachable!("validated above"),
+    }
+    Ok(true)
+}
+
+/// Parse a DAG file the way the folder watcher does and report the result
+fn validate(file: &std::path::Path, output: Output) -> bool {
+    let result = loader::parse_dag_file(file);
+    match output {
+        Output::Json => print_json(&match &result {
+            Ok(loaded) => serde_json::json!({
+                "valid": true,
+                "dag_id": loaded.dag.dag_id,
+                "tasks": loaded.graph.tasks.len(),
+                "edges": loaded.graph.edges.len(),
+            }),
+            Err(error) => serde_json::json!({ "valid": false, "error": error }),
+        }),
+        Output::Table => match &result {
+            Ok(loaded) => out!(
+                "{}: DAG '{}' is valid ({} tasks, {} edges)",
+                file.display(),
+                loaded.dag.dag_id,
+                loaded.graph.tasks.len(),
+                loaded.graph.edges.len()
+            ),
+            Err(error) => out!("{}: {}", file.display(), error),
+        },
+    }
+    result.is_ok()
+}
+
+fn migrate(config: &Config, output: Output) -> Result<bool, String> {
+    let database = &config.database_url;
+    if database == "memory" {
+        return Err("nothing to migrate: the database is 'memory'".to_string());
+    }
+    let report = SqliteRegistry::open(registry::sqlite_path(database))
+        .and_then(|registry| registry.migrate())
+        .map_err(|err| err.to_string())?;
+    match output {
+        Output::Json => print_json(&report),
+        Output::Table => out!(
+            "{}: migrated from version {} to {} ({} DAGs, {} graphs, {} runs, {} users rewritten)",
+            database, report.from_version, report.to_version, report.dags, report.graphs, report.runs, report.users
+        ),
+    }
+    Ok(true)
+}
+
+fn users(config: &Config, command: UsersCommand, output: Output) -> Result<bool, String> {
+    let state = open_state(config)?;
+    let actor = actor();
+    match command {
+        UsersCommand::List => {
+            let users: Vec<UserInfo> = state
+                .users
+                .list_users()
+                .map_err(|err| err.to_string())?
+                .iter()
+                .map(UserInfo::from)
+                .collect();
+            print_users(&users, output);
+        }
+        UsersCommand::Create {
+            username,
+            display_name,
+            role,
+            grants,
+        } => {
+            let payload = NewUserPayload {
+                username,
+                password: read_password()?,
+                display_name,
+                role,
+                grants,
+            };
+            let user = auth::create_user(&state, payload, &actor, Utc::now()).map_err(|err| err.to_string())?;
+            print_users(&[UserInfo::from(&user)], output);
+        }
+        UsersCommand::Update {
+            username,
+            display_name,
+            role,
+            grants,
+            clear_grants,
+            disabled,
+            password_stdin,
+        } => {
+            let payload = UpdateUserPayload {
+                display_name,
+                password: if password_stdin { Some(read_password()?) } else { None },
+                role,
+                grants: if clear_grants || !grants.is_empty() { Some(grants) } else { None },
+                disabled,
+            };
+            let user =
+                auth::update_user(&state, &username, payload, &actor, Utc::now()).map_err(|err| err.to_string())?;
+            print_users(&[UserInfo::from(&user)], output);
+        }
+        UsersCommand::Delete { username } => {
+            auth::delete_user(&state, &username, &actor).map_err(|err| err.to_string())?;
+            if output == Output::Table {
+                out!("deleted user {}", username);
+            }
+        }
+    }
+    Ok(true)
+}
+
+fn tokens(config: &Config, command: TokensCommand, output: Output) -> Result<bool, String> {
+    let state = open_state(config)?;
+    match command {
+        TokensCommand::List { username } => {
+            let tokens: Vec<ApiTokenInfo> = state
+                .users
+                .list_tokens(&username)
+                .map_err(|err| err.to_string())?
+                .iter()
+                .map(ApiTokenInfo::from)
+                .collect();
+            if output == Output::Json {
+                print_json(&tokens);
+            } else {
+                let rows = tokens
+                    .iter()
+                    .map(|token| {
+                        vec![
+                            token.id.clone(),
+                            token.name.clone(),
+                            format_time(Some(token.created_at)),
+                            format_time(token.expires_at),
+                            format_time(token.last_used),
+                        ]
+                    })
+                    .collect();
+                print_table(&["ID", "NAME", "CREATED", "EXPIRES", "LAST USED"], rows);
+            }
+        }
+        TokensCommand::Create { username, name, expires_in_days } => {
+            let payload = NewTokenPayload { name, expires_in_days };
+            let response =
+                auth::create_token(&state, &username, payload, Utc::now()).map_err(|err| err.to_string())?;
+            match output {
+                Output::Json => print_json(&response),
+                Output::Table => {
+                    out!("{}", response.token);
+                    eprintln!("token {} created; it will not be shown again", response.info.id);
+                }
+            }
+        }
+        TokensCommand::Revoke { username, token_id } => {
+            state
+                .users
+                .delete_token(&username, &token_id)
+                .map_err(|err| err.to_string())?;
+            if output == Output::Table {
+                out!("revoked token {}", token_id);
+            }
+        }
+    }
+    Ok(true)
+}
+
+/// A password from the first line of stdin, so it stays out of the shell history
+fn read_password() -> Result<String, String> {
+    let mut line = String::new();
+    std::io::stdin()
+        .read_line(&mut line)
+        .map_err(|err| format!("cannot read the password from stdin: {}", err))?;
+    Ok(line.trim_end_matches(['\r', '\n']).to_string())
+}
+
+/// Who command-line changes are attributed to: `cli:` and the OS user running the command
+fn actor() -> String {
+    let user = std::env::var("USER")
+        .or_else(|_| std::env::var("USERNAME"))
+        .unwrap_or_else(|_| "unknown".to_string());
+    format!("cli:{}", user)
+}
+
+/// Queue a backfill and, unless `--no-wait`, report its progress until every run has finished.
+/// Fails if any run failed.
+async fn backfill(state: AppState, args: BackfillArgs, output: Output) -> Result<bool, String> {
+    let payload = BackfillPayload {
+        start_date: args.start,
+        end_date: args.end,
+        max_active_runs: args.max_active_runs,
+        conf: args.conf.unwrap_or_default(),
+        dry_run: args.dry_run,
+        comment: args.comment,
+    };
+    let response = backfill::create_backfill(&state, &args.dag_id, payload, &actor(), Utc::now())
+        .map_err(|err| err.to_string())?;
+
+    // JSON output is one object per line: the response, then each change of progress
+    match output {
+        Output::Json => out!("{}", serde_json::to_string(&response).unwrap_or_default()),
+        Output::Table => {
+            for date in &response.skipped {
+                out!("skipped {} (already has a run)", date.to_rfc3339());
+            }
+            let verb = if response.dry_run { "would run" } else { "queued" };
+            for date in &response.logical_dates {
+                out!("{} {}", verb, date.to_rfc3339());
+            }
+            if !response.dry_run {
+                out!("backfill {} of {}", response.backfill_id, args.dag_id);
+            }
+        }
+    }
+    if response.dry_run || args.no_wait || response.logical_dates.is_empty() {
+        return Ok(true);
+    }
+
+    let mut last = None;
+    loop {
+        let progress = backfill::backfill_progress(state.runs.as_ref(), &args.dag_id, &response.backfill_id)
+            .map_err(|err| err.to_string())?
+            .ok_or_else(|| format!("backfill {} has no runs any more", response.backfill_id))?;
+        if last.as_ref() != Some(&progress) {
+            match output {
+                Output::Json => out!("{}", serde_json::to_string(&progress).unwrap_or_default()),
+                Output::Table => out!(
+                    "{}/{} done: {} succeeded, {} failed, {} running, {} queued",
+                    progress.succeeded + progress.failed,
+                    progress.total,
+                    progress.succeeded,
+                    progress.failed,
+                    progress.running,
+                    progress.queued
+                ),
+            }
+        }
+        if progress.is_finished() {
+            return Ok(progress.failed == 0);
+        }
+        last = Some(progress);
+        tokio::time::sleep(Duration::from_secs(2)).await;
+    }
+}
+
+/// State backed by the server's database. Commands change that database directly and a
+/// running server picks the changes up on its next scheduler tick.
+fn open_state(config: &Config) -> Result<AppState, String> {
+    if config.database_url == "memory" {
+        return Err("this command needs the server's database, but the database is 'memory'".to_string());
+    }
+    let stores = registry::open(&config.database_url).map_err(|err| err.to_string())?;
+    Ok(AppState::new(stores, LogStore::new(&config.logs_folder), config.auth.clone()))
+}
+
+fn show_config(config: &Config, config_file: Option<&Path>, output: Output) {
+    let config = config.redacted();
+    match output {
+        Output::Json => print_json(&config),
+        Output::Table => {
+            match config_file {
+                Some(file) => out!("# from {} and the environment", file.display()),
+                None => out!("# from defaults and the environment"),
+            }
+            match toml::to_string(&config) {
+                Ok(toml) => out!("{}", toml.trim_end()),
+                Err(err) => eprintln!("failed to serialize config: {}", err),
+            }
+        }
+    }
+}
+
+fn print_dags(dags: &[DAG], output: Output) {
+    if output == Output::Json {
+        return print_json(&dags);
+    }
+    let rows = dags
+        .iter()
+        .map(|dag| {
+            vec![
+                dag.dag_id.clone(),
+                dag.owner.clone(),
+                dag.schedule_interval.clone(),
+                if dag.paused { "yes" } else { "no" }.to_string(),
+                format_time(dag.next_run),
+                dag.runs_count.to_string(),
+                dag.failed_count.to_string(),
+            ]
+        })
+        .collect();
+    print_table(&["DAG", "OWNER", "SCHEDULE", "PAUSED", "NEXT RUN", "RUNS", "FAILED"], rows);
+}
+
+fn print_runs(runs: &[DagRun], output: Output) {
+    if output == Output::Json {
+        return print_json(&runs);
+    }
+    let rows = runs
+        .iter()
+        .map(|run| {
+            vec![
+                run.run_id.clone(),
+                format!("{:?}", run.state),
+                format!("{:?}", run.run_type),
+                format_time(Some(run.logical_date)),
+                format_time(run.start_date),
+                format_time(run.end_date),
+                run.triggered_by.clone().unwrap_or_else(|| "-".to_string()),
+            ]
+        })
+        .collect();
+    print_table(&["RUN", "STATE", "TYPE", "LOGICAL DATE", "STARTED", "ENDED", "BY"], rows);
+}
+
+fn print_audit(entries: &[AuditEntry], output: Output) {
+    if output == Output::Json {
+        return print_json(&entries);
+    }
+    let target = |entry: &AuditEntry| {
+        [Some(&entry.dag_id), entry.run_id.as_ref(), entry.task_id.as_ref()]
+            .into_iter()
+            .flatten()
+            .cloned()
+            .collect::<Vec<_>>()
+            .join(" / ")
+    };
+    let rows = entries
+        .iter()
+        .map(|entry| {
+            vec![
+                entry.id.to_string(),
+                format_time(Some(entry.timestamp)),
+                entry.actor.clone(),
+                entry.action.to_string(),
+                target(entry),
+                entry.comment.clone().unwrap_or_else(|| "-".to_string()),
+            ]
+        })
+        .collect();
+    print_table(&["ID", "TIME", "ACTOR", "ACTION", "TARGET", "COMMENT"], rows);
+}
+
+fn print_users(users: &[UserInfo], output: Output) {
+    if output == Output::Json {
+        return print_json(&users);
+    }
+    let rows = users
+        .iter()
+        .map(|user| {
+            vec![
+                user.username.clone(),
+                user.display_name.clone().unwrap_or_else(|| "-".to_string()),
+                user.role.to_string(),
+                if user.grants.is_empty() {
+                    "-".to_string()
+                } else {
+                    user.grants.iter().map(ToString::to_string).collect::<Vec<_>>().join(",")
+                },
+                if user.disabled { "yes" } else { "no" }.to_string(),
+                format_time(user.last_login),
+            ]
+        })
+        .collect();
+    print_table(&["USER", "NAME", "ROLE", "GRANTS", "DISABLED", "LAST LOGIN"], rows);
+}
+
+fn format_time(time: Option<DateTime<Utc>>) -> String {
+    time.map_or_else(|| "-".to_string(), |time| time.format("%Y-%m-%d %H:%M:%S").to_string())
+}
+
+fn print_json(value: &impl Serialize) {
+    match serde_json::to_string_pretty(value) {
+        Ok(json) => out!("{}", json),
+        Err(err) => eprintln!("failed to serialize output: {}", err),
+    }
+}
+
+/// Left-aligned columns separated by two spaces
+fn print_table(headers: &[&str], rows: Vec<Vec<String>>) {
+    let mut widths: Vec<usize> = headers.iter().map(|header| header.len()).collect();
+    for row in &rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+    let headers: Vec<String> = headers.iter().map(|header| header.to_string()).collect();
+    for row in std::iter::once(&headers).chain(&rows) {
+        let line: Vec<String> = row
+            .iter()
+            .zip(&widths)
+            .map(|(cell, width)| format!("{:<width$}", cell, width = width))
+            .collect();
+        out!("{}", line.join("  ").trim_end());
+    }
+}
+
+fn parse_conf(value: &str) -> Result<Map<String, Value>, String> {
+    match serde_json::from_str(value) {
+        Ok(Value::Object(conf)) => Ok(conf),
+        _ => Err("expected a JSON object".to_string()),
+    }
+}
+
+/// An RFC 3339 timestamp, or a bare date meaning midnight UTC
+fn parse_date(value: &str) -> Result<DateTime<Utc>, String> {
+    if let Ok(date) = DateTime::parse_from_rfc3339(value) {
+        return Ok(date.with_timezone(&Utc));
+    }
+    NaiveDate::parse_from_str(value, "%Y-%m-%d")
+        .ok()
+        .and_then(|date| date.and_hms_opt(0, 0, 0))
+        .map(|date| date.and_utc())
+        .ok_or_else(|| format!("'{}' is not a date (YYYY-MM-DD) or RFC 3339 timestamp", value))
+}
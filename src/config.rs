@@ -0,0 +1,283 @@
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use axum::http::HeaderValue;
+use serde::{Deserialize, Serialize};
+
+use crate::scheduler::OverlapPolicy;
+
+/// File read when no other config file is given, if it exists
+pub const DEFAULT_CONFIG_FILE: &str = "cyclonetix.toml";
+
+const REDACTED: &str = "********";
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConfigError(pub String);
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+/// Server settings: built-in defaults, overridden by `cyclonetix.toml`, overridden by
+/// `CYCLONETIX_*` environment variables
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    /// "memory", or the path of a SQLite file, optionally as `sqlite://<path>`
+    pub database_url: String,
+    pub dags_folder: PathBuf,
+    /// Where task attempt logs are written
+    pub logs_folder: PathBuf,
+    /// `tracing` filter for the server's own logging; `RUST_LOG` takes precedence
+    pub log_filter: String,
+    /// Fill an empty registry with paused sample DAGs and a made-up run history, for demos
+    pub seed_samples: bool,
+    pub server: ServerConfig,
+    pub executor: ExecutorConfig,
+    pub scheduler: SchedulerConfig,
+    pub auth: AuthConfig,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ServerConfig {
+    pub host: String,
+    pub port: u16,
+    /// Origins browsers may call the API from
+    pub cors_origins: Vec<String>,
+}
+
+/// Which executor runs task attempts
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ExecutorKind {
+    /// Child processes of the server
+    Local,
+}
+
+impl FromStr for ExecutorKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "local" => Ok(ExecutorKind::Local),
+            other => Err(format!("unknown executor '{}', expected local", other)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ExecutorConfig {
+    pub kind: ExecutorKind,
+    /// Most task attempts running at once
+    pub parallelism: usize,
+    /// Time limit of an attempt whose task sets none, in seconds
+    pub task_timeout_secs: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SchedulerConfig {
+    /// What happens when a schedule fires while an earlier run is still active
+    pub overlap: OverlapPolicy,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct AuthConfig {
+    /// Require users to sign in to the UI and API
+    pub enabled: bool,
+    /// Signs session tokens; must be set when auth is enabled
+    pub secret_key: Option<String>,
+    /// How long a sign-in lasts, in seconds
+    pub session_ttl_secs: u64,
+    /// Mark the session cookie `Secure`, so browsers only send it over HTTPS. The server speaks
+    /// plain HTTP itself; enable this when it is reached through a TLS-terminating proxy.
+    pub secure_cookie: bool,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            database_url: "cyclonetix.db".to_string(),
+            dags_folder: PathBuf::from("dags"),
+            logs_folder: PathBuf::from("logs"),
+            log_filter: "cyclonetix=info,tower_http=info".to_string(),
+            seed_samples: false,
+            server: ServerConfig::default(),
+            executor: ExecutorConfig::default(),
+            scheduler: SchedulerConfig::default(),
+            auth: AuthConfig::default(),
+        }
+    }
+}
+
+impl Default for ServerConfig {
+    fn default() -> Self {
+        Self {
+            host: "127.0.0.1".to_string(),
+            port: 3000,
+            cors_origins: vec!["http://localhost:3000".to_string()],
+        }
+    }
+}
+
+impl Default for ExecutorConfig {
+    fn default() -> Self {
+        Self {
+            kind: ExecutorKind::Local,
+            parallelism: 16,
+            task_timeout_secs: 3600,
+        }
+    }
+}
+
+impl Default for SchedulerConfig {
+    fn default() -> Self {
+        Self {
+            overlap: OverlapPolicy::Skip,
+        }
+    }
+}
+
+impl Default for AuthConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            secret_key: None,
+            session_ttl_secs: 12 * 60 * 60,
+            secure_cookie: false,
+        }
+    }
+}
+
+impl Config {
+    /// Load the effective config. `path` (or `CYCLONETIX_CONFIG`) must exist if given; otherwise
+    /// `cyclonetix.toml` is read when present. Returns the config and the file it came from.
+    pub fn load(path: Option<&Path>) -> Result<(Config, Option<PathBuf>), ConfigError> {
+        let explicit = path
+            .map(Path::to_path_buf)
+            .or_else(|| std::env::var_os("CYCLONETIX_CONFIG").map(PathBuf::from));
+        let file = match explicit {
+            Some(path) => Some(path),
+            None => Some(PathBuf::from(DEFAULT_CONFIG_FILE)).filter(|path| path.is_file()),
+        };
+
+        let mut config = match &file {
+            Some(path) => {
+                let contents = fs::read_to_string(path)
+                    .map_err(|err| ConfigError(format!("cannot read {}: {}", path.display(), err)))?;
+                toml::from_str(&contents)
+                    .map_err(|err| ConfigError(format!("invalid config file {}: {}", path.display(), err)))?
+            }
+            None => Config::default(),
+        };
+        config.apply_env()?;
+        config.validate()?;
+        Ok((config, file))
+    }
+
+    /// Override settings from `CYCLONETIX_*` variables
+    fn apply_env(&mut self) -> Result<(), ConfigError> {
+        let var = |name: &str| std::env::var(name).ok();
+        fn parse<T: FromStr>(name: &str, value: String) -> Result<T, ConfigError> {
+            value
+                .parse()
+                .map_err(|_| ConfigError(format!("{} has an invalid value '{}'", name, value)))
+        }
+
+        if let Some(value) = var("CYCLONETIX_DATABASE_URL").or_else(|| var("CYCLONETIX_DATABASE")) {
+            self.database_url = value;
+        }
+        if let Some(value) = var("CYCLONETIX_DAGS_FOLDER") {
+            self.dags_folder = PathBuf::from(value);
+        }
+        if let Some(value) = var("CYCLONETIX_LOGS_FOLDER") {
+            self.logs_folder = PathBuf::from(value);
+        }
+        if let Some(value) = var("CYCLONETIX_LOG_FILTER") {
+            self.log_filter = value;
+        }
+        if let Some(value) = var("CYCLONETIX_SEED_SAMPLES") {
+            self.seed_samples = parse("CYCLONETIX_SEED_SAMPLES", value)?;
+        }
+        if let Some(value) = var("CYCLONETIX_HOST") {
+            self.server.host = value;
+        }
+        if let Some(value) = var("CYCLONETIX_PORT") {
+            self.server.port = parse("CYCLONETIX_PORT", value)?;
+        }
+        if let Some(value) = var("CYCLONETIX_CORS_ORIGINS") {
+            self.server.cors_origins = value
+                .split(',')
+                .map(str::trim)
+                .filter(|origin| !origin.is_empty())
+                .map(String::from)
+                .collect();
+        }
+        if let Some(value) = var("CYCLONETIX_EXECUTOR") {
+            self.executor.kind = value.parse().map_err(ConfigError)?;
+        }
+        if let Some(value) = var("CYCLONETIX_PARALLELISM") {
+            self.executor.parallelism = parse("CYCLONETIX_PARALLELISM", value)?;
+        }
+        if let Some(value) = var("CYCLONETIX_TASK_TIMEOUT") {
+            self.executor.task_timeout_secs = parse("CYCLONETIX_TASK_TIMEOUT", value)?;
+        }
+        if let Some(value) = var("CYCLONETIX_OVERLAP") {
+            self.scheduler.overlap = value.parse().map_err(ConfigError)?;
+        }
+        if let Some(value) = var("CYCLONETIX_AUTH_ENABLED") {
+            self.auth.enabled = parse("CYCLONETIX_AUTH_ENABLED", value)?;
+        }
+        if let Some(value) = var("CYCLONETIX_AUTH_SECRET_KEY") {
+            self.auth.secret_key = Some(value);
+        }
+        if let Some(value) = var("CYCLONETIX_AUTH_SESSION_TTL") {
+            self.auth.session_ttl_secs = parse("CYCLONETIX_AUTH_SESSION_TTL", value)?;
+        }
+        if let Some(value) = var("CYCLONETIX_AUTH_SECURE_COOKIE") {
+            self.auth.secure_cookie = parse("CYCLONETIX_AUTH_SECURE_COOKIE", value)?;
+        }
+        Ok(())
+    }
+
+    fn validate(&self) -> Result<(), ConfigError> {
+        if self.executor.parallelism == 0 {
+            return Err(ConfigError("executor.parallelism must be at least 1".to_string()));
+        }
+        if let Some(origin) = self
+            .server
+            .cors_origins
+            .iter()
+            .find(|origin| HeaderValue::from_str(origin).is_err())
+        {
+            return Err(ConfigError(format!("server.cors_origins has an invalid origin '{}'", origin)));
+        }
+        if self.auth.enabled && self.auth.secret_key.as_deref().is_none_or(str::is_empty) {
+            return Err(ConfigError("auth.secret_key must be set when auth is enabled".to_string()));
+        }
+        Ok(())
+    }
+
+    /// `host:port` to listen on
+    pub fn bind_address(&self) -> String {
+        format!("{}:{}", self.server.host, self.server.port)
+    }
+
+    /// A copy that is safe to print, with secrets replaced
+    pub fn redacted(&self) -> Config {
+        let mut config = self.clone();
+        if config.auth.secret_key.is_some() {
+            config.auth.secret_key = Some(REDACTED.to_string());
+        }
+        config
+    }
+}
@@ -0,0 +1,249 @@
+use std::collections::HashMap;
+use std::fs;
+use std::sync::Arc;
+use std::time::Duration;
+
+use serde::Serialize;
+use tokio::sync::broadcast;
+use tokio::task::JoinHandle;
+
+use crate::models::{DagRun, RunState, TaskInstance, TaskStatus};
+use crate::registry::{RegistryError, RunCounts, RunStore};
+use crate::state::AppState;
+
+/// Events a slow subscriber may fall behind by before it is told to resync
+const EVENT_BUFFER: usize = 1024;
+
+/// A change to a DAG, run or task instance, pushed to `/api/events` subscribers
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ServerEvent {
+    DagPaused {
+        dag_id: String,
+        paused: bool,
+    },
+    RunCreated {
+        run: DagRun,
+    },
+    RunStateChanged {
+        dag_id: String,
+        run_id: String,
+        previous: RunState,
+        state: RunState,
+    },
+    /// A task instance changed status or started a new attempt
+    TaskStateChanged {
+        dag_id: String,
+        run_id: String,
+        previous: TaskStatus,
+        task: TaskInstance,
+    },
+    /// The log of a running attempt grew to `size` bytes
+    LogAppended {
+        dag_id: String,
+        run_id: String,
+        task_id: String,
+        try_number: usize,
+        size: u64,
+    },
+}
+
+impl ServerEvent {
+    pub fn dag_id(&self) -> &str {
+        match self {
+            ServerEvent::RunCreated { run } => &run.dag_id,
+            ServerEvent::DagPaused { dag_id, .. }
+            | ServerEvent::RunStateChanged { dag_id, .. }
+            | ServerEvent::TaskStateChanged { dag_id, .. }
+            | ServerEvent::LogAppended { dag_id, .. } => dag_id,
+        }
+    }
+
+    /// Name of the server-sent event, the same as the `type` field
+    pub fn name(&self) -> &'static str {
+        match self {
+            ServerEvent::DagPaused { .. } => "dag_paused",
+            ServerEvent::RunCreated { .. } => "run_created",
+            ServerEvent::RunStateChanged { .. } => "run_state_changed",
+            ServerEvent::TaskStateChanged { .. } => "task_state_changed",
+            ServerEvent::LogAppended { .. } => "log_appended",
+        }
+    }
+}
+
+/// Fans server events out to every subscriber. Publishing never blocks; a subscriber that
+/// falls too far behind loses the oldest events.
+pub struct EventBus {
+    sender: broadcast::Sender<ServerEvent>,
+}
+
+impl EventBus {
+    pub fn new() -> Self {
+        let (sender, _) = broadcast::channel(EVENT_BUFFER);
+        Self { sender }
+    }
+
+    pub fn publish(&self, event: ServerEvent) {
+        // No subscribers is not an error
+        let _ = self.sender.send(event);
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<ServerEvent> {
+        self.sender.subscribe()
+    }
+
+    pub fn has_subscribers(&self) -> bool {
+        self.sender.receiver_count() > 0
+    }
+}
+
+impl Default for EventBus {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Run store that publishes run and task instance changes made through it
+pub struct PublishingRunStore {
+    inner: Arc<dyn RunStore>,
+    events: Arc<EventBus>,
+}
+
+impl PublishingRunStore {
+    pub fn new(inner: Arc<dyn RunStore>, events: Arc<EventBus>) -> Self {
+        Self { inner, events }
+    }
+
+    fn publish_changes(&self, before: &DagRun, after: &DagRun) {
+        for ti in &after.task_instances {
+            let previous = before.task_instances.iter().find(|old| old.task_id == ti.task_id);
+            let changed = previous.is_none_or(|old| old.status != ti.status || old.try_number != ti.try_number);
+            if changed {
+                self.events.publish(ServerEvent::TaskStateChanged {
+                    dag_id: after.dag_id.clone(),
+                    run_id: after.run_id.clone(),
+                    previous: previous.map_or(TaskStatus::PENDING, |old| old.status.clone()),
+                    task: ti.clone(),
+                });
+            }
+        }
+        // After the tasks, so the run finishing is the last thing subscribers see
+        if before.state != after.state {
+            self.events.publish(ServerEvent::RunStateChanged {
+                dag_id: after.dag_id.clone(),
+                run_id: after.run_id.clone(),
+                previous: before.state,
+                state: after.state,
+            });
+        }
+    }
+}
+
+impl RunStore for PublishingRunStore {
+    fn create_run(&self, run: DagRun) -> Result<DagRun, RegistryError> {
+        let run = self.inner.create_run(run)?;
+        self.events.publish(ServerEvent::RunCreated { run: run.clone() });
+        Ok(run)
+    }
+
+    fn update_run(&self, run: DagRun) -> Result<DagRun, RegistryError> {
+        let before = self.inner.get_run(&run.dag_id, &run.run_id)?;
+        let run = self.inner.update_run(run)?;
+        if let Some(before) = before {
+            self.publish_changes(&before, &run);
+        }
+        Ok(run)
+    }
+
+    fn modify_run(
+        &self,
+        dag_id: &str,
+        run_id: &str,
+        modify: &mut dyn FnMut(&mut DagRun),
+    ) -> Result<DagRun, RegistryError> {
+        let mut before = None;
+        let run = self.inner.modify_run(dag_id, run_id, &mut |run| {
+            before = Some(run.clone());
+            modify(run);
+        })?;
+        if let Some(before) = before {
+            self.publish_changes(&before, &run);
+        }
+        Ok(run)
+    }
+
+    fn get_run(&self, dag_id: &str, run_id: &str) -> Result<Option<DagRun>, RegistryError> {
+        self.inner.get_run(dag_id, run_id)
+    }
+
+    fn list_runs(&self, dag_id: &str) -> Result<Vec<DagRun>, RegistryError> {
+        self.inner.list_runs(dag_id)
+    }
+
+    fn active_runs(&self) -> Result<Vec<DagRun>, RegistryError> {
+        self.inner.active_runs()
+    }
+
+    fn run_counts(&self) -> Result<HashMap<String, RunCounts>, RegistryError> {
+        self.inner.run_counts()
+    }
+}
+
+/// Publishes `LogAppended` as the logs of running attempts grow. Executors write logs
+/// straight to disk, so the files are polled; nothing is read while no one is subscribed.
+pub struct LogTailer {
+    state: AppState,
+    /// Last seen size of each running attempt's log
+    sizes: HashMap<(String, String, String, usize), u64>,
+}
+
+impl LogTailer {
+    pub fn new(state: AppState) -> Self {
+        Self {
+            state,
+            sizes: HashMap::new(),
+        }
+    }
+
+    pub fn spawn(mut self, interval: Duration) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            loop {
+                tokio::time::sleep(interval).await;
+                if !self.state.events.has_subscribers() {
+                    self.sizes.clear();
+                    continue;
+                }
+                if let Err(err) = self.poll() {
+                    tracing::warn!("Failed to check task logs for new output: {}", err);
+                }
+            }
+        })
+    }
+
+    fn poll(&mut self) -> Result<(), RegistryError> {
+        let mut sizes = HashMap::new();
+        for run in self.state.runs.active_runs()? {
+            for ti in &run.task_instances {
+                if ti.status != TaskStatus::RUNNING {
+                    continue;
+                }
+                let path = self.state.logs.path(&run.dag_id, &run.run_id, &ti.task_id, ti.try_number);
+                let size = fs::metadata(&path).map_or(0, |meta| meta.len());
+                let key = (run.dag_id.clone(), run.run_id.clone(), ti.task_id.clone(), ti.try_number);
+                if size > self.sizes.get(&key).copied().unwrap_or(0) {
+                    self.state.events.publish(ServerEvent::LogAppended {
+                        dag_id: run.dag_id.clone(),
+                        run_id: run.run_id.clone(),
+                        task_id: ti.task_id.clone(),
+                        try_number: ti.try_number,
+                        size,
+                    });
+                }
+                sizes.insert(key, size);
+            }
+        }
+        // Attempts that finished are dropped
+        self.sizes = sizes;
+        Ok(())
+    }
+}
@@ -0,0 +1,222 @@
+use std::fs::{self, File};
+use std::future::Future;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::pin::Pin;
+use std::process::Stdio;
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use serde_json::{Map, Value};
+use tokio::process::Command;
+use tokio::sync::Semaphore;
+
+use crate::models::TaskStatus;
+
+/// Everything an executor needs to run one attempt of a task
+#[derive(Debug, Clone)]
+pub struct TaskRequest {
+    pub dag_id: String,
+    pub run_id: String,
+    pub task_id: String,
+    pub try_number: usize,
+    pub operator: String,
+    pub command: Option<String>,
+    /// Parameters of the run, exposed to the process as JSON in `CYCLONETIX_CONF`
+    pub conf: Map<String, Value>,
+    /// Overrides the executor's default time limit
+    pub timeout: Option<Duration>,
+    /// Where the attempt's stdout and stderr go
+    pub log_path: PathBuf,
+}
+
+/// How an attempt ended
+#[derive(Debug, Clone, PartialEq)]
+pub struct TaskOutcome {
+    /// `SUCCEEDED` or `FAILED`
+    pub status: TaskStatus,
+    /// Exit code of the process, if it ran to completion
+    pub exit_code: Option<i32>,
+    /// Why the attempt failed without an exit code (timeout, unsupported operator, ...)
+    pub error: Option<String>,
+    /// When the attempt actually started, after waiting for the executor; None if it never did
+    pub started_at: Option<DateTime<Utc>>,
+}
+
+impl TaskOutcome {
+    fn failed(error: String) -> Self {
+        Self {
+            status: TaskStatus::FAILED,
+            exit_code: None,
+            error: Some(error),
+            started_at: None,
+        }
+    }
+}
+
+pub type ExecutionFuture = Pin<Box<dyn Future<Output = TaskOutcome> + Send>>;
+
+/// Told when an attempt stops waiting for the executor and actually starts
+pub type OnStart = Box<dyn FnOnce(DateTime<Utc>) + Send>;
+
+/// Runs task attempts handed over by the scheduler. Implementations decide where and how the
+/// work happens; the scheduler only records the start and the outcome.
+pub trait Executor: Send + Sync {
+    fn execute(&self, request: TaskRequest, on_start: OnStart) -> ExecutionFuture;
+}
+
+/// Runs tasks as child processes of the server, at most `parallelism` at a time
+pub struct LocalExecutor {
+    default_timeout: Duration,
+    slots: Arc<Semaphore>,
+}
+
+impl LocalExecutor {
+    pub fn new(default_timeout: Duration, parallelism: usize) -> Self {
+        Self {
+            default_timeout,
+            slots: Arc::new(Semaphore::new(parallelism.max(1))),
+        }
+    }
+}
+
+impl Executor for LocalExecutor {
+    fn execute(&self, request: TaskRequest, on_start: OnStart) -> ExecutionFuture {
+        let timeout = request.timeout.unwrap_or(self.default_timeout);
+        let slots = self.slots.clone();
+        Box::pin(async move {
+            // The time limit starts once a slot is free
+            let Ok(_slot) = slots.acquire_owned().await else {
+                return TaskOutcome::failed("executor is shutting down".to_string());
+            };
+            let started_at = Utc::now();
+            on_start(started_at);
+            let started = |outcome: TaskOutcome| TaskOutcome {
+                started_at: Some(started_at),
+                ..outcome
+            };
+            let mut log = match open_log(&request.log_path) {
+                Ok(log) => log,
+                Err(err) => {
+                    return started(TaskOutcome::failed(format!(
+                        "cannot open log file {}: {}",
+                        request.log_path.display(),
+                        err
+                    )))
+                }
+            };
+
+            let outcome = match request.operator.as_str() {
+                "BashOperator" => run_bash(&request, timeout, &mut log).await,
+                other => TaskOutcome::failed(format!(
+                    "operator '{}' is not supported by the local executor",
+                    other
+                )),
+            };
+
+            let _ = match (&outcome.error, outcome.exit_code) {
+                (Some(error), _) => writeln!(log, "*** Task failed: {}", error),
+                (None, Some(code)) => writeln!(log, "*** Process exited with code {}", code),
+                (None, None) => Ok(()),
+            };
+            started(outcome)
+        })
+    }
+}
+
+fn open_log(path: &Path) -> std::io::Result<File> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    File::create(path)
+}
+
+/// Run the command with stdout and stderr both appended to `log`
+async fn run_bash(request: &TaskRequest, timeout: Duration, log: &mut File) -> TaskOutcome {
+    let Some(command) = request.command.as_deref() else {
+        return TaskOutcome::failed("BashOperator task has no command".to_string());
+    };
+
+    let _ = writeln!(
+        log,
+        "*** Running {} of {} / {} (try {}): {}",
+        request.task_id, request.dag_id, request.run_id, request.try_number, command
+    );
+    let (stdout, stderr) = match (log.try_clone(), log.try_clone()) {
+        (Ok(stdout), Ok(stderr)) => (stdout, stderr),
+        (Err(err), _) | (_, Err(err)) => {
+            return TaskOutcome::failed(format!("cannot share log file with the process: {}", err))
+        }
+    };
+
+    let child = Command::new("bash")
+        .arg("-c")
+        .arg(command)
+        .env("CYCLONETIX_DAG_ID", &request.dag_id)
+        .env("CYCLONETIX_RUN_ID", &request.run_id)
+        .env("CYCLONETIX_TASK_ID", &request.task_id)
+        .env("CYCLONETIX_TRY_NUMBER", request.try_number.to_string())
+        .env("CYCLONETIX_CONF", Value::Object(request.conf.clone()).to_string())
+        .stdin(Stdio::null())
+        .stdout(Stdio::from(stdout))
+        .stderr(Stdio::from(stderr))
+        // A group of its own, so that everything the command starts can be stopped with it
+        .process_group(0)
+        .kill_on_drop(true)
+        .spawn();
+    let mut child = match child {
+        Ok(child) => child,
+        Err(err) => return TaskOutcome::failed(format!("failed to start bash: {}", err)),
+    };
+    let mut group = ProcessGroup(child.id());
+
+    let result = tokio::time::timeout(timeout, child.wait()).await;
+    if result.is_ok() {
+        group.release();
+    }
+    match result {
+        Ok(Ok(status)) => TaskOutcome {
+            status: if status.success() {
+                TaskStatus::SUCCEEDED
+            } else {
+                TaskStatus::FAILED
+            },
+            exit_code: status.code(),
+            error: None,
+            started_at: None,
+        },
+        Ok(Err(err)) => TaskOutcome::failed(format!("failed to wait for process: {}", err)),
+        Err(_) => {
+            group.kill();
+            let _ = child.kill().await;
+            TaskOutcome::failed(format!("timed out after {}s", timeout.as_secs()))
+        }
+    }
+}
+
+/// The process group of a running command. Killed when dropped, so an attempt that is abandoned
+/// leaves no pipelines or background jobs behind.
+struct ProcessGroup(Option<u32>);
+
+impl ProcessGroup {
+    fn kill(&mut self) {
+        if let Some(pgid) = self.0.take() {
+            // SAFETY: killpg only sends a signal; the group was created for this command
+            unsafe {
+                libc::killpg(pgid as libc::pid_t, libc::SIGKILL);
+            }
+        }
+    }
+
+    /// The command has exited by itself; leave whatever is left of its group alone
+    fn release(&mut self) {
+        self.0 = None;
+    }
+}
+
+impl Drop for ProcessGroup {
+    fn drop(&mut self) {
+        self.kill();
+    }
+}
@@ -0,0 +1,355 @@
+use std::cmp::Ordering;
+use std::fmt;
+use std::str::FromStr;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::models::DAG;
+use crate::utils::{hex, unhex};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ListingError(pub String);
+
+impl fmt::Display for ListingError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl std::error::Error for ListingError {}
+
+/// A DAG field the DAG list can be sorted on
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SortField {
+    DagId,
+    Owner,
+    ScheduleInterval,
+    Paused,
+    LastRun,
+    NextRun,
+    RunsCount,
+    SuccessCount,
+    FailedCount,
+    RunningCount,
+    CreatedAt,
+    UpdatedAt,
+}
+
+impl SortField {
+    pub const ALL: [SortField; 12] = [
+        SortField::DagId,
+        SortField::Owner,
+        SortField::ScheduleInterval,
+        SortField::Paused,
+        SortField::LastRun,
+        SortField::NextRun,
+        SortField::RunsCount,
+        SortField::SuccessCount,
+        SortField::FailedCount,
+        SortField::RunningCount,
+        SortField::CreatedAt,
+        SortField::UpdatedAt,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SortField::DagId => "dag_id",
+            SortField::Owner => "owner",
+            SortField::ScheduleInterval => "schedule_interval",
+            SortField::Paused => "paused",
+            SortField::LastRun => "last_run",
+            SortField::NextRun => "next_run",
+            SortField::RunsCount => "runs_count",
+            SortField::SuccessCount => "success_count",
+            SortField::FailedCount => "failed_count",
+            SortField::RunningCount => "running_count",
+            SortField::CreatedAt => "created_at",
+            SortField::UpdatedAt => "updated_at",
+        }
+    }
+
+    fn value(&self, dag: &DAG) -> SortValue {
+        match self {
+            SortField::DagId => SortValue::Text(dag.dag_id.clone()),
+            SortField::Owner => SortValue::Text(dag.owner.clone()),
+            SortField::ScheduleInterval => SortValue::Text(dag.schedule_interval.clone()),
+            SortField::Paused => SortValue::Flag(dag.paused),
+            SortField::LastRun => SortValue::Time(dag.last_run),
+            SortField::NextRun => SortValue::Time(dag.next_run),
+            SortField::RunsCount => SortValue::Count(dag.runs_count),
+            SortField::SuccessCount => SortValue::Count(dag.success_count),
+            SortField::FailedCount => SortValue::Count(dag.failed_count),
+            SortField::RunningCount => SortValue::Count(dag.running_count),
+            SortField::CreatedAt => SortValue::Time(Some(dag.created_at)),
+            SortField::UpdatedAt => SortValue::Time(Some(dag.updated_at)),
+        }
+    }
+}
+
+impl FromStr for SortField {
+    type Err = ListingError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        SortField::ALL.into_iter().find(|field| field.as_str() == s).ok_or_else(|| {
+            let known: Vec<&str> = SortField::ALL.iter().map(SortField::as_str).collect();
+            ListingError(format!("cannot sort DAGs by '{}', expected one of {}", s, known.join(", ")))
+        })
+    }
+}
+
+/// The value a DAG has for one sort field. Missing times sort before any time.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum SortValue {
+    Flag(bool),
+    Count(usize),
+    Time(Option<DateTime<Utc>>),
+    Text(String),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SortKey {
+    pub field: SortField,
+    pub descending: bool,
+}
+
+impl fmt::Display for SortKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.field.as_str(), if self.descending { "desc" } else { "asc" })
+    }
+}
+
+/// Ordering of the DAG list, most significant key first. Always ends on `dag_id` so that the
+/// order is total and a cursor names exactly one position.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SortSpec(Vec<SortKey>);
+
+impl SortSpec {
+    /// Parse `sort_by` as a comma-separated list of `field` or `field:asc|desc`. Keys without a
+    /// direction take `sort_order`, which defaults to ascending.
+    pub fn parse(sort_by: Option<&str>, sort_order: Option<&str>) -> Result<SortSpec, ListingError> {
+        let default_descending = parse_direction(sort_order.unwrap_or("asc"))?;
+        let mut keys: Vec<SortKey> = Vec::new();
+        for part in sort_by.unwrap_or("").split(',').map(str::trim).filter(|part| !part.is_empty()) {
+            let (field, descending) = match part.split_once(':') {
+                Some((field, direction)) => (field.trim(), parse_direction(direction.trim())?),
+                None => (part, default_descending),
+            };
+            let field: SortField = field.parse()?;
+            if keys.iter().any(|key| key.field == field) {
+                return Err(ListingError(format!("'{}' appears more than once in sort_by", field.as_str())));
+            }
+            keys.push(SortKey { field, descending });
+        }
+        if !keys.iter().any(|key| key.field == SortField::DagId) {
+            keys.push(SortKey {
+                field: SortField::DagId,
+                descending: false,
+            });
+        }
+        Ok(SortSpec(keys))
+    }
+
+    pub fn sort(&self, dags: &mut [DAG]) {
+        dags.sort_by(|a, b| self.compare(&self.values(a), &self.values(b)));
+    }
+
+    fn values(&self, dag: &DAG) -> Vec<SortValue> {
+        self.0.iter().map(|key| key.field.value(dag)).collect()
+    }
+
+    fn compare(&self, a: &[SortValue], b: &[SortValue]) -> Ordering {
+        self.0
+            .iter()
+            .zip(a.iter().zip(b))
+            .map(|(key, (a, b))| if key.descending { b.cmp(a) } else { a.cmp(b) })
+            .find(|ordering| ordering.is_ne())
+            .unwrap_or(Ordering::Equal)
+    }
+
+    /// Opaque cursor for the position just after `dag` in this order
+    pub fn cursor_after(&self, dag: &DAG) -> String {
+        let cursor = Cursor {
+            sort: self.to_string(),
+            after: self.values(dag),
+        };
+        hex(&serde_json::to_vec(&cursor).expect("cursor serializes"))
+    }
+
+    /// Index of the first of the sorted `dags` after `cursor`. An empty cursor is the start of
+    /// the list.
+    pub fn position_after(&self, dags: &[DAG], cursor: &str) -> Result<usize, ListingError> {
+        if cursor.is_empty() {
+            return Ok(0);
+        }
+        let invalid = || ListingError(format!("invalid cursor '{}'", cursor));
+        let cursor: Cursor = unhex(cursor)
+            .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+            .ok_or_else(invalid)?;
+        if cursor.sort != self.to_string() {
+            return Err(ListingError(format!(
+                "cursor was issued for sort '{}', not '{}'",
+                cursor.sort, self
+            )));
+        }
+        if cursor.after.len() != self.0.len() {
+            return Err(invalid());
+        }
+        Ok(dags.partition_point(|dag| self.compare(&self.values(dag), &cursor.after).is_le()))
+    }
+}
+
+impl fmt::Display for SortSpec {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let keys: Vec<String> = self.0.iter().map(SortKey::to_string).collect();
+        write!(f, "{}", keys.join(","))
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct Cursor {
+    sort: String,
+    after: Vec<SortValue>,
+}
+
+fn parse_direction(direction: &str) -> Result<bool, ListingError> {
+    match direction {
+        "asc" => Ok(false),
+        "desc" => Ok(true),
+        other => Err(ListingError(format!("sort direction must be 'asc' or 'desc', not '{}'", other))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+    use uuid::Uuid;
+
+    use super::*;
+
+    fn dag(dag_id: &str, owner: &str, runs_count: usize, last_run_day: Option<u32>) -> DAG {
+        let created_at = Utc.with_ymd_and_hms(2026, 1, 1, 0, 0, 0).unwrap();
+        DAG {
+            id: Uuid::new_v4(),
+            dag_id: dag_id.to_string(),
+            description: None,
+            file_path: String::new(),
+            owner: owner.to_string(),
+            paused: false,
+            last_run: last_run_day.map(|day| Utc.with_ymd_and_hms(2026, 3, day, 0, 0, 0).unwrap()),
+            next_run: None,
+            runs_count,
+            success_count: runs_count,
+            failed_count: 0,
+            running_count: 0,
+            schedule_interval: "@daily".to_string(),
+            timezone: "UTC".to_string(),
+            tags: Vec::new(),
+            params_schema: None,
+            created_at,
+            updated_at: created_at,
+        }
+    }
+
+    fn sample() -> Vec<DAG> {
+        vec![
+            dag("e", "bob", 3, Some(2)),
+            dag("a", "bob", 1, None),
+            dag("d", "alice", 3, Some(5)),
+            dag("b", "carol", 2, Some(1)),
+            dag("c", "alice", 3, None),
+        ]
+    }
+
+    fn ids(dags: &[DAG]) -> Vec<&str> {
+        dags.iter().map(|dag| dag.dag_id.as_str()).collect()
+    }
+
+    #[test]
+    fn parse_fills_in_directions_and_ends_on_dag_id() {
+        let sort = SortSpec::parse(Some("owner:desc, runs_count"), Some("desc")).unwrap();
+        assert_eq!(sort.to_string(), "owner:desc,runs_count:desc,dag_id:asc");
+        assert_eq!(SortSpec::parse(None, None).unwrap().to_string(), "dag_id:asc");
+        assert_eq!(SortSpec::parse(Some("dag_id:desc"), None).unwrap().to_string(), "dag_id:desc");
+    }
+
+    #[test]
+    fn parse_rejects_bad_keys() {
+        assert!(SortSpec::parse(Some("color"), None).is_err());
+        assert!(SortSpec::parse(Some("owner:up"), None).is_err());
+        assert!(SortSpec::parse(None, Some("sideways")).is_err());
+        assert!(SortSpec::parse(Some("owner,owner:desc"), None).is_err());
+    }
+
+    #[test]
+    fn sort_breaks_ties_with_later_keys() {
+        let mut dags = sample();
+        SortSpec::parse(Some("runs_count:desc,owner"), None).unwrap().sort(&mut dags);
+        assert_eq!(ids(&dags), ["c", "d", "e", "b", "a"]);
+    }
+
+    #[test]
+    fn missing_times_sort_first() {
+        let mut dags = sample();
+        SortSpec::parse(Some("last_run"), None).unwrap().sort(&mut dags);
+        assert_eq!(ids(&dags), ["a", "c", "b", "e", "d"]);
+    }
+
+    #[test]
+    fn cursors_page_through_every_dag_once() {
+        let sort = SortSpec::parse(Some("runs_count:desc,owner"), None).unwrap();
+        let mut dags = sample();
+        sort.sort(&mut dags);
+
+        let mut seen = Vec::new();
+        let mut cursor = String::new();
+        loop {
+            let start = sort.position_after(&dags, &cursor).unwrap();
+            let page = &dags[start..(start + 2).min(dags.len())];
+            if page.is_empty() {
+                break;
+            }
+            seen.extend(ids(page));
+            cursor = sort.cursor_after(page.last().unwrap());
+        }
+        assert_eq!(seen, ["c", "d", "e", "b", "a"]);
+    }
+
+    #[test]
+    fn a_cursor_survives_removal_of_the_dag_it_names() {
+        let sort = SortSpec::parse(None, None).unwrap();
+        let mut dags = sample();
+        sort.sort(&mut dags);
+        let cursor = sort.cursor_after(&dags[1]);
+        dags.remove(1);
+        let start = sort.position_after(&dags, &cursor).unwrap();
+        assert_eq!(ids(&dags[start..]), ["c", "d", "e"]);
+    }
+
+    #[test]
+    fn a_cursor_is_rejected_when_the_sort_changes() {
+        let by_owner = SortSpec::parse(Some("owner"), None).unwrap();
+        let by_runs = SortSpec::parse(Some("runs_count"), None).unwrap();
+        let mut dags = sample();
+        by_owner.sort(&mut dags);
+        let cursor = by_owner.cursor_after(&dags[0]);
+
+        let err = by_runs.position_after(&dags, &cursor).unwrap_err();
+        assert_eq!(
+            err.0,
+            "cursor was issued for sort 'owner:asc,dag_id:asc', not 'runs_count:asc,dag_id:asc'"
+        );
+        let reversed = SortSpec::parse(Some("owner:desc"), None).unwrap();
+        assert!(reversed.position_after(&dags, &cursor).is_err());
+    }
+
+    #[test]
+    fn malformed_cursors_are_rejected() {
+        let sort = SortSpec::parse(None, None).unwrap();
+        let dags = sample();
+        assert_eq!(sort.position_after(&dags, "").unwrap(), 0);
+        assert!(sort.position_after(&dags, "not hex").is_err());
+        assert!(sort.position_after(&dags, &hex(b"{}")).is_err());
+    }
+}
@@ -0,0 +1,276 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fs;
+use std::path::Path;
+
+use chrono::Utc;
+use serde::Deserialize;
+use uuid::Uuid;
+
+use crate::models::{default_timezone, DAGGraph, Edge, ImportError, RetryPolicy, Task, TaskStatus, DAG};
+use crate::params;
+use crate::registry::{DagRegistry, RegistryError};
+use crate::schedule;
+
+/// File extensions the loader understands
+const DAG_FILE_EXTENSIONS: [&str; 4] = ["yaml", "yml", "toml", "json"];
+
+/// Declarative DAG definition as written in a YAML, TOML or JSON file. Unknown keys are
+/// rejected, so a misspelt one is reported instead of silently ignored.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct DagDefinition {
+    /// Defaults to the file name without its extension
+    pub dag_id: Option<String>,
+    #[serde(default)]
+    pub description: Option<String>,
+    pub owner: String,
+    pub schedule_interval: String,
+    #[serde(default = "default_timezone")]
+    pub timezone: String,
+    /// Only applied when the DAG is first registered
+    #[serde(default)]
+    pub paused: bool,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// JSON schema for the `conf` of manually triggered runs
+    #[serde(default)]
+    pub params_schema: Option<serde_json::Value>,
+    pub tasks: Vec<TaskDefinition>,
+    #[serde(default)]
+    pub edges: Vec<Edge>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TaskDefinition {
+    pub id: String,
+    pub name: Option<String>,
+    pub operator: String,
+    #[serde(default)]
+    pub max_retries: usize,
+    /// Shell command, required for `BashOperator` tasks
+    #[serde(default)]
+    pub command: Option<String>,
+    /// Time limit for one attempt, in seconds
+    #[serde(default)]
+    pub timeout: Option<u64>,
+    /// Delay, backoff and jitter between attempts
+    #[serde(default)]
+    pub retry: RetryPolicy,
+}
+
+/// A parsed and validated DAG file
+#[derive(Debug, Clone)]
+pub struct LoadedDag {
+    pub dag: DAG,
+    pub graph: DAGGraph,
+}
+
+/// Whether a path looks like a DAG definition file
+pub fn is_dag_file(path: &Path) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| DAG_FILE_EXTENSIONS.contains(&ext.to_ascii_lowercase().as_str()))
+}
+
+/// Read, parse and validate a single DAG file
+pub fn parse_dag_file(path: &Path) -> Result<LoadedDag, String> {
+    let contents = fs::read_to_string(path).map_err(|e| format!("failed to read file: {}", e))?;
+    let extension = path
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .unwrap_or_default()
+        .to_ascii_lowercase();
+
+    let definition: DagDefinition = match extension.as_str() {
+        "yaml" | "yml" => serde_yaml::from_str(&contents).map_err(|e| e.to_string())?,
+        "toml" => toml::from_str(&contents).map_err(|e| e.to_string())?,
+        "json" => serde_json::from_str(&contents).map_err(|e| e.to_string())?,
+        other => return Err(format!("unsupported file extension '{}'", other)),
+    };
+
+    let fallback_id = path
+        .file_stem()
+        .and_then(|stem| stem.to_str())
+        .unwrap_or_default()
+        .to_string();
+
+    build_dag(definition, fallback_id, path)
+}
+
+fn build_dag(definition: DagDefinition, fallback_id: String, path: &Path) -> Result<LoadedDag, String> {
+    let dag_id = definition.dag_id.unwrap_or(fallback_id);
+    if dag_id.trim().is_empty() {
+        return Err("dag_id must not be empty".to_string());
+    }
+
+    if let Some(task) = definition.tasks.iter().find(|task| {
+        task.operator == "BashOperator" && task.command.as_deref().is_none_or(|c| c.trim().is_empty())
+    }) {
+        return Err(format!("BashOperator task '{}' has no command", task.id));
+    }
+    if let Some(task) = definition
+        .tasks
+        .iter()
+        .find(|task| !(0.0..=1.0).contains(&task.retry.jitter))
+    {
+        return Err(format!("retry jitter of task '{}' must be between 0 and 1", task.id));
+    }
+    if let Some(schema) = &definition.params_schema {
+        params::check_schema(schema).map_err(|e| e.to_string())?;
+    }
+
+    let tasks: Vec<Task> = definition
+        .tasks
+        .into_iter()
+        .map(|task| Task {
+            name: task.name.unwrap_or_else(|| task.id.clone()),
+            id: task.id,
+            status: TaskStatus::PENDING,
+            duration: None,
+            start_time: None,
+            end_time: None,
+            operator: task.operator,
+            retries: 0,
+            max_retries: task.max_retries,
+            try_number: 0,
+            command: task.command,
+            timeout_secs: task.timeout,
+            retry: task.retry,
+        })
+        .collect();
+
+    let graph = DAGGraph {
+        dag_id: dag_id.clone(),
+        version: 0,
+        tasks,
+        edges: definition.edges,
+    };
+    validate_graph(&graph)?;
+
+    let now = Utc::now();
+    let file_path = path
+        .canonicalize()
+        .unwrap_or_else(|_| path.to_path_buf())
+        .display()
+        .to_string();
+
+    let mut dag = DAG {
+        id: Uuid::new_v4(),
+        dag_id,
+        description: definition.description,
+        file_path,
+        owner: definition.owner,
+        paused: definition.paused,
+        last_run: None,
+        next_run: None,
+        runs_count: 0,
+        success_count: 0,
+        failed_count: 0,
+        running_count: 0,
+        schedule_interval: definition.schedule_interval,
+        timezone: definition.timezone,
+        tags: definition.tags,
+        params_schema: definition.params_schema,
+        created_at: now,
+        updated_at: now,
+    };
+    dag.next_run = schedule::next_run_for(&dag, now).map_err(|e| e.to_string())?;
+
+    Ok(LoadedDag { dag, graph })
+}
+
+/// Check a graph for duplicate task ids, dangling edge endpoints and cycles
+pub fn validate_graph(graph: &DAGGraph) -> Result<(), String> {
+    if graph.tasks.is_empty() {
+        return Err("a DAG must define at least one task".to_string());
+    }
+
+    let mut ids = HashSet::new();
+    for task in &graph.tasks {
+        if !ids.insert(task.id.as_str()) {
+            return Err(format!("duplicate task id '{}'", task.id));
+        }
+    }
+
+    for edge in &graph.edges {
+        for endpoint in [&edge.source, &edge.target] {
+            if !ids.contains(endpoint.as_str()) {
+                return Err(format!(
+                    "edge {} -> {} references unknown task '{}'",
+                    edge.source, edge.target, endpoint
+                ));
+            }
+        }
+    }
+
+    // Kahn's algorithm: anything left unvisited sits on a cycle
+    let mut in_degree: HashMap<&str, usize> = ids.iter().map(|id| (*id, 0)).collect();
+    let mut downstream: HashMap<&str, Vec<&str>> = HashMap::new();
+    for edge in &graph.edges {
+        *in_degree.get_mut(edge.target.as_str()).unwrap() += 1;
+        downstream.entry(edge.source.as_str()).or_default().push(edge.target.as_str());
+    }
+
+    let mut ready: VecDeque<&str> = in_degree
+        .iter()
+        .filter(|(_, degree)| **degree == 0)
+        .map(|(id, _)| *id)
+        .collect();
+    let mut visited = 0;
+    while let Some(id) = ready.pop_front() {
+        visited += 1;
+        for next in downstream.get(id).into_iter().flatten() {
+            let degree = in_degree.get_mut(next).unwrap();
+            *degree -= 1;
+            if *degree == 0 {
+                ready.push_back(next);
+            }
+        }
+    }
+
+    if visited < ids.len() {
+        let mut cyclic: Vec<&str> = in_degree
+            .into_iter()
+            .filter(|(_, degree)| *degree > 0)
+            .map(|(id, _)| id)
+            .collect();
+        cyclic.sort();
+        return Err(format!("cycle detected between tasks: {}", cyclic.join(", ")));
+    }
+
+    Ok(())
+}
+
+/// Register a loaded DAG, keeping the identity, counters and pause flag of an existing record.
+/// The record and its graph are written atomically.
+pub fn register_loaded_dag(registry: &dyn DagRegistry, loaded: LoadedDag) -> Result<DAG, RegistryError> {
+    let LoadedDag { dag, graph } = loaded;
+    let dag_id = dag.dag_id.clone();
+
+    let merge = move |existing: Option<DAG>| match existing {
+        Some(mut existing) => {
+            existing.description = dag.description.clone();
+            existing.file_path = dag.file_path.clone();
+            existing.owner = dag.owner.clone();
+            existing.schedule_interval = dag.schedule_interval.clone();
+            existing.timezone = dag.timezone.clone();
+            existing.tags = dag.tags.clone();
+            existing.params_schema = dag.params_schema.clone();
+            existing.updated_at = dag.updated_at;
+            // The schedule may have changed; the pause flag is whatever the registry holds
+            existing.next_run = schedule::next_run_for(&existing, dag.updated_at).unwrap_or(None);
+            existing
+        }
+        None => dag.clone(),
+    };
+    registry.upsert_with_graph(&dag_id, &merge, graph)
+}
+
+pub fn import_error(path: &Path, error: String) -> ImportError {
+    ImportError {
+        file_path: path.display().to_string(),
+        error,
+        timestamp: Utc::now(),
+    }
+}
@@ -0,0 +1,149 @@
+use std::fs::{self, File};
+use std::io::{self, Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+
+use serde::Serialize;
+
+/// Task attempt logs on the local filesystem, one file per attempt at
+/// `<root>/<dag_id>/<run_id>/<task_id>/<try_number>.log`
+#[derive(Debug, Clone)]
+pub struct LogStore {
+    root: PathBuf,
+}
+
+/// A slice of a log file, addressed by byte offsets
+#[derive(Debug, Clone, Serialize)]
+pub struct LogChunk {
+    pub try_number: usize,
+    /// Byte offset the content starts at
+    pub offset: u64,
+    /// Offset to request next to continue reading
+    pub next_offset: u64,
+    /// Size of the whole log file in bytes
+    pub size: u64,
+    pub content: String,
+}
+
+impl LogStore {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    pub fn path(&self, dag_id: &str, run_id: &str, task_id: &str, try_number: usize) -> PathBuf {
+        self.root
+            .join(path_component(dag_id))
+            .join(path_component(run_id))
+            .join(path_component(task_id))
+            .join(format!("{}.log", try_number))
+    }
+
+    /// Read up to `limit` bytes starting at `offset`. A missing file reads as empty, since an
+    /// attempt that has not started yet has no log.
+    pub fn read(
+        &self,
+        dag_id: &str,
+        run_id: &str,
+        task_id: &str,
+        try_number: usize,
+        offset: u64,
+        limit: u64,
+    ) -> io::Result<LogChunk> {
+        let path = self.path(dag_id, run_id, task_id, try_number);
+        read_chunk(&path, try_number, offset, limit)
+    }
+
+    /// Remove every log of a DAG
+    pub fn delete_dag(&self, dag_id: &str) -> io::Result<()> {
+        match fs::remove_dir_all(self.root.join(path_component(dag_id))) {
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(()),
+            other => other,
+        }
+    }
+}
+
+fn read_chunk(path: &Path, try_number: usize, offset: u64, limit: u64) -> io::Result<LogChunk> {
+    let mut file = match File::open(path) {
+        Ok(file) => file,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => {
+            return Ok(LogChunk {
+                try_number,
+                offset,
+                next_offset: offset,
+                size: 0,
+                content: String::new(),
+            })
+        }
+        Err(err) => return Err(err),
+    };
+
+    let size = file.metadata()?.len();
+    let mut start = offset.min(size);
+    file.seek(SeekFrom::Start(start))?;
+    let mut bytes = Vec::new();
+    (&mut file).take(limit).read_to_end(&mut bytes)?;
+
+    // Keep the content to whole characters: an offset inside a character moves on to the next
+    // one, and a character cut off at the end is left for the next read
+    let skipped = bytes.iter().take(3).take_while(|byte| is_continuation(**byte)).count();
+    bytes.drain(..skipped);
+    start += skipped as u64;
+    let mut cut = incomplete_tail(&bytes);
+    if cut > 0 && cut == bytes.len() {
+        // The limit does not fit a single character; read on to its end so the chunk is not empty
+        (&mut file).take(3).read_to_end(&mut bytes)?;
+        cut = incomplete_tail(&bytes);
+    }
+    if cut < bytes.len() {
+        bytes.truncate(bytes.len() - cut);
+    }
+
+    Ok(LogChunk {
+        try_number,
+        offset: start,
+        next_offset: start + bytes.len() as u64,
+        size,
+        content: String::from_utf8_lossy(&bytes).into_owned(),
+    })
+}
+
+fn is_continuation(byte: u8) -> bool {
+    byte & 0xC0 == 0x80
+}
+
+/// Number of bytes at the end of `bytes` that start a UTF-8 character without finishing it
+fn incomplete_tail(bytes: &[u8]) -> usize {
+    for back in 1..=bytes.len().min(4) {
+        let byte = bytes[bytes.len() - back];
+        if is_continuation(byte) {
+            continue;
+        }
+        let width = match byte {
+            0xC0..=0xDF => 2,
+            0xE0..=0xEF => 3,
+            0xF0..=0xF7 => 4,
+            _ => 1,
+        };
+        return if width > back { back } else { 0 };
+    }
+    0
+}
+
+/// Make an id safe to use as a single path component. Other characters, including `%` itself,
+/// are percent-encoded so that different ids never share a directory.
+fn path_component(id: &str) -> String {
+    match id {
+        "" => return "%".to_string(),
+        "." => return "%2E".to_string(),
+        ".." => return "%2E%2E".to_string(),
+        _ => {}
+    }
+    let mut safe = String::with_capacity(id.len());
+    for byte in id.bytes() {
+        if byte.is_ascii_alphanumeric() || matches!(byte, b'-' | b'_' | b'.' | b'+' | b':') {
+            safe.push(byte as char);
+        } else {
+            safe.push_str(&format!("%{:02X}", byte));
+        }
+    }
+    safe
+}
@@ -1,19 +1,42 @@
 pub mod api;
+pub mod audit;
+pub mod auth;
+pub mod backfill;
+pub mod cli;
+pub mod config;
+pub mod events;
+pub mod executor;
+pub mod loader;
+pub mod listing;
+pub mod logs;
 pub mod models;
+pub mod params;
+pub mod permissions;
+pub mod registry;
+pub mod schedule;
+pub mod scheduler;
+pub mod search;
+pub mod service;
+pub mod state;
 pub mod utils;
+pub mod watcher;
 
 use axum::{
     extract::Path,
-    http::{header, HeaderValue, Method, StatusCode, Uri},
+    http::{header, HeaderValue, Method, StatusCode},
     response::{IntoResponse, Response},
+    middleware,
     routing::get,
     Router,
 };
+use clap::Parser;
+use config::{Config, ExecutorKind};
 use rust_embed::RustEmbed;
-use std::net::SocketAddr;
-use tower_http::cors::{Any, CorsLayer};
+use state::AppState;
+use std::sync::Arc;
+use tower_http::cors::{AllowOrigin, Any, CorsLayer};
 use tower_http::trace::TraceLayer;
-use tower_http::compression::{CompressionLayer, predicate::SizeAbove};
+use tower_http::compression::{CompressionLayer, predicate::{NotForContentType, Predicate, SizeAbove}};
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 
 // Define the embedded frontend assets
@@ -23,14 +46,48 @@ struct FrontendAssets;
 
 #[tokio::main]
 async fn main() {
-    // Initialize logging
+    let cli = cli::Cli::parse();
+    let command = cli.command.unwrap_or(cli::Command::Serve);
+    let (config, config_file) = match Config::load(cli.config.as_deref()) {
+        Ok(loaded) => loaded,
+        Err(err) => {
+            eprintln!("error: {}", err);
+            std::process::exit(2);
+        }
+    };
+
+    // Initialize logging; commands other than serve keep stdout for their output
+    let filter = tracing_subscriber::EnvFilter::new(
+        std::env::var("RUST_LOG").unwrap_or_else(|_| config.log_filter.clone()),
+    );
+    if matches!(command, cli::Command::Serve) {
+        tracing_subscriber::registry()
+            .with(filter)
+            .with(tracing_subscriber::fmt::layer())
+            .init();
+        if let Some(file) = &config_file {
+            tracing::info!("Loaded config from {}", file.display());
+        }
+        serve(config).await;
+        return;
+    }
     tracing_subscriber::registry()
-        .with(tracing_subscriber::EnvFilter::new(
-            std::env::var("RUST_LOG").unwrap_or_else(|_| "backend=debug,tower_http=debug".into()),
-        ))
-        .with(tracing_subscriber::fmt::layer())
+        .with(filter)
+        .with(tracing_subscriber::fmt::layer().with_writer(std::io::stderr))
         .init();
 
+    match cli::run(command, &config, config_file.as_deref(), cli.output).await {
+        Ok(true) => {}
+        Ok(false) => std::process::exit(1),
+        Err(err) => {
+            eprintln!("error: {}", err);
+            std::process::exit(2);
+        }
+    }
+}
+
+/// Run the web server, scheduler and DAG folder watcher until the process is stopped
+async fn serve(config: Config) {
     // Log the list of embedded files for debugging
     tracing::info!("Listing embedded files:");
     for file in FrontendAssets::iter() {
@@ -45,35 +102,103 @@ async fn main() {
         }
     }
 
-    // CORS configuration
+    // Open the DAG registry; "memory" keeps everything in-process, anything else is a SQLite file
+    let stores = registry::open(&config.database_url).expect("failed to open DAG database");
+    tracing::info!("Using DAG registry: {}", config.database_url);
+
+    let (registry, runs) = (stores.registry.clone(), stores.runs.clone());
+    let state = AppState::new(stores, logs::LogStore::new(&config.logs_folder), config.auth.clone());
+    if config.auth.enabled {
+        match state.users.list_users() {
+            Ok(users) if users.is_empty() => {
+                tracing::warn!("Auth is enabled but there are no users; create one with `cyclonetix users create`")
+            }
+            Ok(_) => {}
+            Err(err) => tracing::error!("Failed to list users: {}", err),
+        }
+    } else {
+        tracing::warn!("Auth is disabled; every request is allowed and acts as 'anonymous'");
+    }
+
+    // Load declarative DAG definitions, then keep watching the folder for changes
+    let mut dag_watcher = watcher::DagFolderWatcher::new(&config.dags_folder, state.clone());
+    let loaded = dag_watcher.scan();
+    tracing::info!(
+        "Loaded {} DAG files from {} ({} import errors)",
+        loaded,
+        config.dags_folder.display(),
+        state.import_errors.read().unwrap().len()
+    );
+
+    if config.seed_samples {
+        match utils::seed_sample_dags(registry.as_ref(), runs.as_ref()) {
+            Ok(0) => {}
+            Ok(count) => tracing::info!("Seeded registry with {} paused sample DAGs", count),
+            Err(err) => tracing::error!("Failed to seed sample DAGs: {}", err),
+        }
+    }
+
+    dag_watcher.spawn(std::time::Duration::from_secs(2));
+
+    // Start the scheduler
+    let executor: Arc<dyn executor::Executor> = match config.executor.kind {
+        ExecutorKind::Local => Arc::new(executor::LocalExecutor::new(
+            std::time::Duration::from_secs(config.executor.task_timeout_secs),
+            config.executor.parallelism,
+        )),
+    };
+    scheduler::Scheduler::new(state.clone(), config.scheduler.overlap, executor).spawn();
+
+    // Tell event subscribers about new task output
+    events::LogTailer::new(state.clone()).spawn(std::time::Duration::from_secs(1));
+
+    // CORS configuration; "*" allows any origin
+    let allow_origin = if config.server.cors_origins.iter().any(|origin| origin == "*") {
+        AllowOrigin::any()
+    } else {
+        let origins = config
+            .server
+            .cors_origins
+            .iter()
+            .map(|origin| origin.parse::<HeaderValue>().expect("invalid CORS origin"))
+            .collect::<Vec<_>>();
+        AllowOrigin::list(origins)
+    };
     let cors = CorsLayer::new()
-        .allow_origin("http://localhost:3000".parse::<HeaderValue>().unwrap())
-        .allow_methods([Method::GET, Method::POST])
+        .allow_origin(allow_origin)
+        .allow_methods([Method::GET, Method::POST, Method::PUT, Method::DELETE])
         .allow_headers(Any);
 
     tracing::info!("Starting server with embedded assets");
 
-    // Configure compression - will compress responses larger than 1024 bytes
+    // Configure compression - will compress responses larger than 1024 bytes. Event streams
+    // are left alone, since compressing them holds events back until a block fills.
     let compression_layer = CompressionLayer::new()
-        .compress_when(SizeAbove::new(1024));
+        .compress_when(SizeAbove::new(1024).and(NotForContentType::const_new("text/event-stream")));
         
     tracing::info!("Compression middleware enabled");
 
     // Build our application with routes
     let app = Router::new()
         .route("/health", get(|| async { "OK" }))
-        .nest("/api", api::routes())
+        .nest(
+            api::PREFIX,
+            api::routes()
+                .route_layer(middleware::from_fn_with_state(state.clone(), permissions::authorize))
+                .route_layer(middleware::from_fn_with_state(state.clone(), auth::authenticate)),
+        )
         .route("/", get(serve_index))  // Explicit route for index
         .route("/*file", get(serve_embedded_file))
+        .with_state(state)
         .layer(cors)
         .layer(compression_layer)  // Add compression before tracing so we can see it in the logs
         .layer(TraceLayer::new_for_http());
 
     // Run the server
-    let addr = SocketAddr::from(([127, 0, 0, 1], 3000));
+    let addr = config.bind_address();
     tracing::info!("listening on {}", addr);
 
-    let listener = tokio::net::TcpListener::bind(&addr).await.unwrap();
+    let listener = tokio::net::TcpListener::bind(&addr).await.expect("failed to bind the server address");
     tracing::info!("Server started, press Ctrl+C to stop");
     axum::serve(listener, app).await.unwrap();
 }
@@ -98,7 +223,6 @@ async fn serve_index() -> impl IntoResponse {
 
 /// Serve static files from the embedded assets
 async fn serve_embedded_file(
-    uri: Uri,
     path: Path<String>,
 ) -> impl IntoResponse {
     let path = path.as_str();
@@ -1,5 +1,10 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fmt;
+use std::str::FromStr;
+
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
 use uuid::Uuid;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -17,18 +22,177 @@ pub struct DAG {
     pub failed_count: usize,
     pub running_count: usize,
     pub schedule_interval: String,
+    /// IANA timezone the schedule is evaluated in
+    #[serde(default = "default_timezone")]
+    pub timezone: String,
     pub tags: Vec<String>,
+    /// JSON schema the `conf` of a manually triggered run must satisfy
+    #[serde(default)]
+    pub params_schema: Option<Value>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
 
+/// Body accepted when creating or updating a DAG; server-managed fields are filled in by the API
+#[derive(Debug, Clone, Deserialize)]
+pub struct DAGPayload {
+    pub dag_id: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub file_path: String,
+    pub owner: String,
+    #[serde(default)]
+    pub paused: bool,
+    pub schedule_interval: String,
+    #[serde(default = "default_timezone")]
+    pub timezone: String,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default)]
+    pub params_schema: Option<Value>,
+}
+
+/// Body of `POST /api/dags/:dag_id/paused`
+#[derive(Debug, Clone, Deserialize)]
+pub struct PausedPayload {
+    pub is_paused: bool,
+    /// Why, for the audit log
+    #[serde(default)]
+    pub comment: Option<String>,
+}
+
+/// Body of `POST /api/dags/:dag_id/runs/:run_id/tasks/:task_id/status`
+#[derive(Debug, Clone, Deserialize)]
+pub struct TaskStatusPayload {
+    pub status: TaskStatus,
+    /// Also change every task upstream of this one
+    #[serde(default)]
+    pub upstream: bool,
+    /// Also change every task downstream of this one
+    #[serde(default)]
+    pub downstream: bool,
+    /// Why, for the audit log
+    #[serde(default)]
+    pub comment: Option<String>,
+}
+
+/// Body of `POST /api/dags/:dag_id/runs/:run_id/tasks/:task_id/clear`
+#[derive(Debug, Clone, Deserialize)]
+pub struct ClearPayload {
+    #[serde(default)]
+    pub upstream: bool,
+    #[serde(default)]
+    pub downstream: bool,
+    /// Follow upstream/downstream transitively rather than only direct neighbours
+    #[serde(default = "default_true")]
+    pub recursive: bool,
+    /// Only clear related tasks that failed; the selected task is always cleared
+    #[serde(default)]
+    pub failed_only: bool,
+    /// List the tasks that would be cleared without changing anything
+    #[serde(default)]
+    pub dry_run: bool,
+    /// Why, for the audit log
+    #[serde(default)]
+    pub comment: Option<String>,
+}
+
+/// Tasks a clear touched (or would touch, on a dry run) and the run afterwards
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ClearResponse {
+    pub task_ids: Vec<String>,
+    pub dry_run: bool,
+    pub run: DagRun,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+/// Body of `POST /api/dags/:dag_id/backfill`
+#[derive(Debug, Clone, Deserialize)]
+pub struct BackfillPayload {
+    /// First logical date to cover, inclusive
+    pub start_date: DateTime<Utc>,
+    /// Last logical date to cover, inclusive
+    pub end_date: DateTime<Utc>,
+    #[serde(default = "default_max_active_runs")]
+    pub max_active_runs: usize,
+    /// Parameters handed to every task of every run
+    #[serde(default)]
+    pub conf: Map<String, Value>,
+    /// Only report the logical dates that would be run
+    #[serde(default)]
+    pub dry_run: bool,
+    /// Why, for the audit log
+    #[serde(default)]
+    pub comment: Option<String>,
+}
+
+fn default_max_active_runs() -> usize {
+    1
+}
+
+/// Result of creating a backfill
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackfillResponse {
+    pub backfill_id: String,
+    pub dry_run: bool,
+    /// Schedule fire times in the requested range that got (or would get) a run
+    pub logical_dates: Vec<DateTime<Utc>>,
+    /// Fire times left alone because the DAG already has a run for them
+    pub skipped: Vec<DateTime<Utc>>,
+    pub progress: BackfillProgress,
+}
+
+/// How far the runs of a backfill have got
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct BackfillProgress {
+    pub backfill_id: String,
+    pub dag_id: String,
+    pub total: usize,
+    pub queued: usize,
+    pub running: usize,
+    pub succeeded: usize,
+    pub failed: usize,
+}
+
+impl BackfillProgress {
+    pub fn is_finished(&self) -> bool {
+        self.queued == 0 && self.running == 0
+    }
+}
+
+/// Body of `POST /api/dags/:dag_id/runs`
+#[derive(Debug, Clone, Deserialize)]
+pub struct TriggerRunPayload {
+    /// Defaults to the time of the request
+    #[serde(default)]
+    pub logical_date: Option<DateTime<Utc>>,
+    /// Parameters handed to every task of the run
+    #[serde(default)]
+    pub conf: Map<String, Value>,
+    /// Why, for the audit log
+    #[serde(default)]
+    pub comment: Option<String>,
+}
+
+pub fn default_timezone() -> String {
+    "UTC".to_string()
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct DAGsResponse {
     pub dags: Vec<DAG>,
+    /// DAGs matching the filters, across all pages
     pub total_count: usize,
+    /// Cursor for the page after this one, if there is one
+    pub next_cursor: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[allow(non_camel_case_types)]
 pub enum TaskStatus {
     PENDING,
     SUCCEEDED,
@@ -37,9 +201,79 @@ pub enum TaskStatus {
     FAILED,
     SKIPPED,
     PAUSED,
+    /// Failed, waiting for its retry delay before being queued again
+    UP_FOR_RETRY,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+impl TaskStatus {
+    /// Statuses an operator may move a task instance to this status from. RUNNING and
+    /// UP_FOR_RETRY are only ever set by the scheduler.
+    fn manual_sources(&self) -> &'static [TaskStatus] {
+        use TaskStatus::*;
+        match self {
+            SUCCEEDED => &[PENDING, QUEUED, FAILED, UP_FOR_RETRY, SKIPPED, PAUSED],
+            FAILED => &[PENDING, QUEUED, SUCCEEDED, UP_FOR_RETRY, SKIPPED, PAUSED],
+            SKIPPED | PAUSED => &[PENDING, QUEUED],
+            QUEUED => &[FAILED, UP_FOR_RETRY],
+            PENDING => &[PAUSED],
+            RUNNING | UP_FOR_RETRY => &[],
+        }
+    }
+
+    /// Check that an operator may change a task instance from this status to `target`,
+    /// explaining why not otherwise
+    pub fn check_transition(&self, target: &TaskStatus) -> Result<(), String> {
+        let sources = target.manual_sources();
+        if sources.contains(self) {
+            return Ok(());
+        }
+        let reason = match sources {
+            [] => format!("{:?} is only set by the scheduler", target),
+            sources => format!(
+                "{:?} is only allowed from {}",
+                target,
+                sources.iter().map(|s| format!("{:?}", s)).collect::<Vec<_>>().join(" or ")
+            ),
+        };
+        Err(format!("cannot change {:?} to {:?}: {}", self, target, reason))
+    }
+}
+
+/// How the delay before a retry grows with each attempt
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Backoff {
+    /// Wait `delay_secs` before every retry
+    #[default]
+    Fixed,
+    /// Double the wait after every failed attempt, up to `max_delay_secs`
+    Exponential,
+}
+
+/// When a failed task is retried. The number of retries comes from `Task::max_retries`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct RetryPolicy {
+    pub delay_secs: u64,
+    pub backoff: Backoff,
+    pub max_delay_secs: u64,
+    /// Fraction of the delay that is randomised, from 0.0 (none) to 1.0 (anywhere between zero
+    /// and the full delay), so retries of many tasks do not all land at once
+    pub jitter: f64,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            delay_secs: 30,
+            backoff: Backoff::Fixed,
+            max_delay_secs: 3600,
+            jitter: 0.0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct Task {
     pub id: String,
     pub name: String,
@@ -50,6 +284,111 @@ pub struct Task {
     pub operator: String,
     pub retries: usize,
     pub max_retries: usize,
+    /// Attempt number within a run; only meaningful on a per-run graph
+    #[serde(default)]
+    pub try_number: usize,
+    /// Shell command run by `BashOperator` tasks
+    #[serde(default)]
+    pub command: Option<String>,
+    /// Per-attempt time limit; the executor's default applies when unset
+    #[serde(default)]
+    pub timeout_secs: Option<u64>,
+    #[serde(default)]
+    pub retry: RetryPolicy,
+}
+
+/// Lifecycle of a single DAG run
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum RunState {
+    /// Created but waiting for an earlier run of the same DAG, or a slot in its backfill, to free up
+    QUEUED,
+    RUNNING,
+    SUCCEEDED,
+    FAILED,
+}
+
+impl RunState {
+    pub fn is_active(&self) -> bool {
+        matches!(self, RunState::QUEUED | RunState::RUNNING)
+    }
+}
+
+/// What caused a DAG run to be created
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum RunType {
+    SCHEDULED,
+    MANUAL,
+    BACKFILL,
+}
+
+/// State of one task within one DAG run
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct TaskInstance {
+    pub task_id: String,
+    pub status: TaskStatus,
+    /// 1-based attempt counter; 0 until the task first starts
+    pub try_number: usize,
+    pub start_time: Option<DateTime<Utc>>,
+    pub end_time: Option<DateTime<Utc>>,
+    pub duration: Option<f64>,
+    /// Exit code of the last attempt, when the process ran to completion
+    #[serde(default)]
+    pub exit_code: Option<i32>,
+    /// When an `UP_FOR_RETRY` task is queued again
+    #[serde(default)]
+    pub next_retry_at: Option<DateTime<Utc>>,
+    /// Every finished attempt, oldest first
+    #[serde(default)]
+    pub attempts: Vec<TaskAttempt>,
+    /// Attempts made before the task was last cleared; retries are counted from here
+    #[serde(default)]
+    pub cleared_tries: usize,
+}
+
+/// One finished attempt of a task instance
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct TaskAttempt {
+    pub try_number: usize,
+    pub status: TaskStatus,
+    pub start_time: Option<DateTime<Utc>>,
+    pub end_time: Option<DateTime<Utc>>,
+    pub duration: Option<f64>,
+    pub exit_code: Option<i32>,
+    /// Why the attempt failed without an exit code (timeout, lost executor, ...)
+    pub error: Option<String>,
+}
+
+/// One execution of a DAG for a logical date
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct DagRun {
+    pub run_id: String,
+    pub dag_id: String,
+    /// The schedule slot this run covers
+    pub logical_date: DateTime<Utc>,
+    pub run_type: RunType,
+    pub state: RunState,
+    /// Graph version the task instances were created from
+    pub graph_version: u32,
+    pub queued_at: DateTime<Utc>,
+    pub start_date: Option<DateTime<Utc>>,
+    pub end_date: Option<DateTime<Utc>>,
+    /// Parameters the run was triggered with; empty for scheduled runs
+    #[serde(default)]
+    pub conf: Map<String, Value>,
+    /// Set when the run was created by a backfill
+    #[serde(default)]
+    pub backfill: Option<BackfillInfo>,
+    /// Who asked for a manual or backfill run; `None` for scheduled runs
+    #[serde(default)]
+    pub triggered_by: Option<String>,
+    pub task_instances: Vec<TaskInstance>,
+}
+
+/// The backfill a run belongs to; the scheduler keeps at most `max_active_runs` of its runs going
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct BackfillInfo {
+    pub backfill_id: String,
+    pub max_active_runs: usize,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -58,20 +397,606 @@ pub struct Edge {
     pub target: String,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct DAGGraph {
     pub dag_id: String,
+    /// Incremented by the registry each time the task/edge structure changes
+    #[serde(default)]
+    pub version: u32,
     pub tasks: Vec<Task>,
     pub edges: Vec<Edge>,
 }
 
+impl DAGGraph {
+    /// Overlay the task-instance states of `run` onto this graph's tasks. Tasks the run does
+    /// not know about are shown as PENDING.
+    pub fn with_run(mut self, run: &DagRun) -> Self {
+        for task in self.tasks.iter_mut() {
+            let instance = run.task_instances.iter().find(|ti| ti.task_id == task.id);
+            task.status = instance.map_or(TaskStatus::PENDING, |ti| ti.status.clone());
+            task.try_number = instance.map_or(0, |ti| ti.try_number);
+            task.retries = task.try_number.saturating_sub(1);
+            task.start_time = instance.and_then(|ti| ti.start_time);
+            task.end_time = instance.and_then(|ti| ti.end_time);
+            task.duration = instance.and_then(|ti| ti.duration);
+        }
+        self
+    }
+
+    /// Ids of every task that depends on `task_id`, directly or transitively, nearest first
+    pub fn downstream_of(&self, task_id: &str) -> Vec<String> {
+        self.reachable(task_id, |edge| (edge.source.as_str(), edge.target.as_str()))
+    }
+
+    /// Ids of every task `task_id` depends on, directly or transitively, nearest first
+    pub fn upstream_of(&self, task_id: &str) -> Vec<String> {
+        self.reachable(task_id, |edge| (edge.target.as_str(), edge.source.as_str()))
+    }
+
+    /// Breadth-first walk over the edges, with `direction` picking the (from, to) end of each
+    fn reachable<'a>(&'a self, task_id: &'a str, direction: fn(&'a Edge) -> (&'a str, &'a str)) -> Vec<String> {
+        let mut adjacent: HashMap<&str, Vec<&str>> = HashMap::new();
+        for edge in &self.edges {
+            let (from, to) = direction(edge);
+            adjacent.entry(from).or_default().push(to);
+        }
+
+        let mut seen: HashSet<&str> = HashSet::from([task_id]);
+        let mut queue = VecDeque::from([task_id]);
+        let mut found = Vec::new();
+        while let Some(id) = queue.pop_front() {
+            for next in adjacent.get(id).into_iter().flatten() {
+                if seen.insert(next) {
+                    found.push(next.to_string());
+                    queue.push_back(next);
+                }
+            }
+        }
+        found
+    }
+}
+
+/// A DAG definition file that could not be loaded
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImportError {
+    pub file_path: String,
+    pub error: String,
+    pub timestamp: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct GraphQuery {
+    pub version: Option<u32>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DagRunsQuery {
+    pub page: Option<usize>,
+    pub limit: Option<usize>,
+    /// One of the `RunState` names, case-insensitive
+    pub state: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DagRunsResponse {
+    pub runs: Vec<DagRun>,
+    pub total_count: usize,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ScheduleQuery {
+    pub count: Option<usize>,
+}
+
+/// Which events `/api/events` streams
+#[derive(Debug, Deserialize)]
+pub struct EventsQuery {
+    /// Comma-separated DAG ids; every DAG when absent
+    pub dag_id: Option<String>,
+}
+
+/// Which attempt's log to read and which bytes of it
+#[derive(Debug, Deserialize)]
+pub struct LogQuery {
+    /// Defaults to the latest attempt
+    #[serde(rename = "try")]
+    pub try_number: Option<usize>,
+    pub offset: Option<u64>,
+    pub limit: Option<u64>,
+}
+
+/// Upcoming fire times of a DAG's schedule
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ScheduleResponse {
+    pub dag_id: String,
+    pub schedule_interval: String,
+    pub timezone: String,
+    pub next_runs: Vec<DateTime<Utc>>,
+}
+
 #[derive(Debug, Deserialize)]
 pub struct DAGsQuery {
     pub page: Option<usize>,
     pub limit: Option<usize>,
     pub search: Option<String>,
     pub status: Option<String>,
+    /// Comma-separated tags
     pub tags: Option<String>,
+    /// Whether a DAG needs all of `tags` or any of them (the default)
+    pub tags_mode: Option<TagsMode>,
+    /// Comma-separated `field` or `field:asc|desc` keys, most significant first
     pub sort_by: Option<String>,
+    /// Direction of `sort_by` keys that do not name one
     pub sort_order: Option<String>,
-}
\ No newline at end of file
+    /// Page after this cursor instead of by `page`; empty for the first page
+    pub cursor: Option<String>,
+}
+
+/// How the `tags` filter of the DAG list combines several tags
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TagsMode {
+    /// A DAG needs one of the tags
+    #[default]
+    Any,
+    /// A DAG needs every tag
+    All,
+}
+
+impl FromStr for TagsMode {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "any" => Ok(TagsMode::Any),
+            "all" => Ok(TagsMode::All),
+            other => Err(format!("tags mode must be 'all' or 'any', not '{}'", other)),
+        }
+    }
+}
+
+/// Filters the tag counts are taken under: those of the DAG list except the tags themselves
+#[derive(Debug, Default, Deserialize)]
+pub struct TagsQuery {
+    pub search: Option<String>,
+    pub status: Option<String>,
+}
+
+/// A tag and how many DAGs carry it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TagCount {
+    pub tag: String,
+    pub count: usize,
+}
+
+/// Every tag of the DAGs matching a [`TagsQuery`], most used first
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TagsResponse {
+    pub tags: Vec<TagCount>,
+}
+
+/// What a user may do. Each role may do everything the ones before it may.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Role {
+    /// Read-only access
+    #[default]
+    Viewer,
+    /// May pause, trigger, backfill and clear DAGs and set task states
+    Operator,
+    /// May also create, change and delete DAGs and manage users
+    Admin,
+}
+
+impl fmt::Display for Role {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Role::Viewer => write!(f, "viewer"),
+            Role::Operator => write!(f, "operator"),
+            Role::Admin => write!(f, "admin"),
+        }
+    }
+}
+
+impl FromStr for Role {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "viewer" => Ok(Role::Viewer),
+            "operator" => Ok(Role::Operator),
+            "admin" => Ok(Role::Admin),
+            other => Err(format!("unknown role '{}', expected viewer, operator or admin", other)),
+        }
+    }
+}
+
+/// Which DAGs a grant covers
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum GrantScope {
+    /// DAGs whose `owner` is this
+    Owner(String),
+    /// DAGs carrying this tag
+    Tag(String),
+}
+
+/// Raises a user's role on the DAGs in its scope, e.g. `{"tag": "production", "role": "operator"}`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DagGrant {
+    #[serde(flatten)]
+    pub scope: GrantScope,
+    pub role: Role,
+}
+
+impl DagGrant {
+    pub fn matches(&self, dag: &DAG) -> bool {
+        match &self.scope {
+            GrantScope::Owner(owner) => dag.owner == *owner,
+            // Tags compare case-insensitively, as in the DAG list's tag filter
+            GrantScope::Tag(tag) => dag.tags.iter().any(|dag_tag| dag_tag.eq_ignore_ascii_case(tag)),
+        }
+    }
+}
+
+impl fmt::Display for DagGrant {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.scope {
+            GrantScope::Owner(owner) => write!(f, "owner:{}={}", owner, self.role),
+            GrantScope::Tag(tag) => write!(f, "tag:{}={}", tag, self.role),
+        }
+    }
+}
+
+/// `owner:<owner>=<role>` or `tag:<tag>=<role>`
+impl FromStr for DagGrant {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("invalid grant '{}', expected owner:<owner>=<role> or tag:<tag>=<role>", s);
+        let (scope, role) = s.rsplit_once('=').ok_or_else(invalid)?;
+        let scope = match scope.split_once(':') {
+            Some(("owner", owner)) if !owner.is_empty() => GrantScope::Owner(owner.to_string()),
+            Some(("tag", tag)) if !tag.is_empty() => GrantScope::Tag(tag.to_string()),
+            _ => return Err(invalid()),
+        };
+        Ok(DagGrant {
+            scope,
+            role: role.parse()?,
+        })
+    }
+}
+
+/// A local user account, as stored
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct User {
+    pub username: String,
+    #[serde(default)]
+    pub display_name: Option<String>,
+    /// Argon2 hash in PHC string format
+    pub password_hash: String,
+    /// Role on every DAG
+    #[serde(default)]
+    pub role: Role,
+    /// Higher roles on some DAGs
+    #[serde(default)]
+    pub grants: Vec<DagGrant>,
+    /// Cannot sign in, and existing sessions and tokens are refused
+    #[serde(default)]
+    pub disabled: bool,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    #[serde(default)]
+    pub last_login: Option<DateTime<Utc>>,
+}
+
+/// A user as returned by the API, without the password hash
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UserInfo {
+    pub username: String,
+    pub display_name: Option<String>,
+    pub role: Role,
+    pub grants: Vec<DagGrant>,
+    pub disabled: bool,
+    pub created_at: DateTime<Utc>,
+    pub last_login: Option<DateTime<Utc>>,
+}
+
+impl From<&User> for UserInfo {
+    fn from(user: &User) -> Self {
+        Self {
+            username: user.username.clone(),
+            display_name: user.display_name.clone(),
+            role: user.role,
+            grants: user.grants.clone(),
+            disabled: user.disabled,
+            created_at: user.created_at,
+            last_login: user.last_login,
+        }
+    }
+}
+
+/// A signed-in browser session. Only a digest of the cookie value is stored.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Session {
+    pub digest: String,
+    pub username: String,
+    pub created_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+}
+
+/// A long-lived bearer token for scripts and the CLI. Only a digest of the token is stored.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApiToken {
+    pub id: String,
+    pub username: String,
+    /// What the token is for, chosen by its owner
+    pub name: String,
+    pub digest: String,
+    pub created_at: DateTime<Utc>,
+    #[serde(default)]
+    pub expires_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub last_used: Option<DateTime<Utc>>,
+}
+
+/// An API token as listed by the API, without its digest
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApiTokenInfo {
+    pub id: String,
+    pub name: String,
+    pub created_at: DateTime<Utc>,
+    pub expires_at: Option<DateTime<Utc>>,
+    pub last_used: Option<DateTime<Utc>>,
+}
+
+impl From<&ApiToken> for ApiTokenInfo {
+    fn from(token: &ApiToken) -> Self {
+        Self {
+            id: token.id.clone(),
+            name: token.name.clone(),
+            created_at: token.created_at,
+            expires_at: token.expires_at,
+            last_used: token.last_used,
+        }
+    }
+}
+
+/// Body of `POST /api/auth/login`
+#[derive(Debug, Clone, Deserialize)]
+pub struct LoginPayload {
+    pub username: String,
+    pub password: String,
+}
+
+/// Response of `GET /api/auth/me`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuthStatus {
+    /// When false every request is allowed and `user` is `None`
+    pub auth_enabled: bool,
+    pub user: Option<UserInfo>,
+}
+
+/// Body of `POST /api/users`
+#[derive(Debug, Clone, Deserialize)]
+pub struct NewUserPayload {
+    pub username: String,
+    pub password: String,
+    #[serde(default)]
+    pub display_name: Option<String>,
+    #[serde(default)]
+    pub role: Role,
+    #[serde(default)]
+    pub grants: Vec<DagGrant>,
+}
+
+/// Body of `PUT /api/users/:username`; absent fields are left alone
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct UpdateUserPayload {
+    #[serde(default)]
+    pub display_name: Option<String>,
+    #[serde(default)]
+    pub password: Option<String>,
+    #[serde(default)]
+    pub role: Option<Role>,
+    /// Replaces every grant of the user
+    #[serde(default)]
+    pub grants: Option<Vec<DagGrant>>,
+    #[serde(default)]
+    pub disabled: Option<bool>,
+}
+
+/// Body of `POST /api/tokens`
+#[derive(Debug, Clone, Deserialize)]
+pub struct NewTokenPayload {
+    pub name: String,
+    /// Never expires when absent
+    #[serde(default)]
+    pub expires_in_days: Option<u32>,
+}
+
+/// Response of `POST /api/tokens`; the token itself is only ever shown here
+#[derive(Debug, Clone, Serialize)]
+pub struct NewTokenResponse {
+    pub token: String,
+    #[serde(flatten)]
+    pub info: ApiTokenInfo,
+}
+
+/// Kind of change recorded in the audit log
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum AuditAction {
+    Pause,
+    Unpause,
+    Trigger,
+    Backfill,
+    Clear,
+    ForceSuccess,
+    Skip,
+    /// Any other manual task state
+    SetTaskStatus,
+    /// A DAG or its graph created, changed or deleted through the API
+    ConfigChange,
+}
+
+impl AuditAction {
+    pub const ALL: [AuditAction; 9] = [
+        AuditAction::Pause,
+        AuditAction::Unpause,
+        AuditAction::Trigger,
+        AuditAction::Backfill,
+        AuditAction::Clear,
+        AuditAction::ForceSuccess,
+        AuditAction::Skip,
+        AuditAction::SetTaskStatus,
+        AuditAction::ConfigChange,
+    ];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            AuditAction::Pause => "pause",
+            AuditAction::Unpause => "unpause",
+            AuditAction::Trigger => "trigger",
+            AuditAction::Backfill => "backfill",
+            AuditAction::Clear => "clear",
+            AuditAction::ForceSuccess => "force-success",
+            AuditAction::Skip => "skip",
+            AuditAction::SetTaskStatus => "set-task-status",
+            AuditAction::ConfigChange => "config-change",
+        }
+    }
+
+    /// The action of setting a task instance to `status` by hand
+    pub fn for_task_status(status: &TaskStatus) -> Self {
+        match status {
+            TaskStatus::SUCCEEDED => AuditAction::ForceSuccess,
+            TaskStatus::SKIPPED => AuditAction::Skip,
+            _ => AuditAction::SetTaskStatus,
+        }
+    }
+}
+
+impl fmt::Display for AuditAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+impl FromStr for AuditAction {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        AuditAction::ALL
+            .into_iter()
+            .find(|action| action.as_str() == value)
+            .ok_or_else(|| format!("unknown audit action '{}'", value))
+    }
+}
+
+/// One entry of the append-only audit log: who changed what, when and why
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuditEntry {
+    /// Assigned by the store in the order entries are appended
+    #[serde(default)]
+    pub id: u64,
+    pub timestamp: DateTime<Utc>,
+    pub actor: String,
+    pub action: AuditAction,
+    pub dag_id: String,
+    #[serde(default)]
+    pub run_id: Option<String>,
+    #[serde(default)]
+    pub task_id: Option<String>,
+    /// The state the action changed, before and after it
+    #[serde(default)]
+    pub before: Option<Value>,
+    #[serde(default)]
+    pub after: Option<Value>,
+    #[serde(default)]
+    pub comment: Option<String>,
+}
+
+impl AuditEntry {
+    pub fn new(timestamp: DateTime<Utc>, actor: &str, action: AuditAction, dag_id: &str) -> Self {
+        Self {
+            id: 0,
+            timestamp,
+            actor: actor.to_string(),
+            action,
+            dag_id: dag_id.to_string(),
+            run_id: None,
+            task_id: None,
+            before: None,
+            after: None,
+            comment: None,
+        }
+    }
+}
+
+/// Filters of `GET /api/audit`; every one given must match
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct AuditQuery {
+    pub actor: Option<String>,
+    pub action: Option<AuditAction>,
+    pub dag_id: Option<String>,
+    pub run_id: Option<String>,
+    pub task_id: Option<String>,
+    /// Entries at or after this time
+    pub since: Option<DateTime<Utc>>,
+    /// Entries before this time
+    pub until: Option<DateTime<Utc>>,
+    pub page: Option<usize>,
+    pub limit: Option<usize>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AuditResponse {
+    /// Newest first
+    pub entries: Vec<AuditEntry>,
+    pub total_count: usize,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dag(owner: &str, tags: &[&str]) -> DAG {
+        let now = Utc::now();
+        DAG {
+            id: Uuid::new_v4(),
+            dag_id: "pipeline".to_string(),
+            description: None,
+            file_path: String::new(),
+            owner: owner.to_string(),
+            paused: false,
+            last_run: None,
+            next_run: None,
+            runs_count: 0,
+            success_count: 0,
+            failed_count: 0,
+            running_count: 0,
+            schedule_interval: "@daily".to_string(),
+            timezone: default_timezone(),
+            tags: tags.iter().map(|tag| tag.to_string()).collect(),
+            params_schema: None,
+            created_at: now,
+            updated_at: now,
+        }
+    }
+
+    #[test]
+    fn tag_grants_ignore_case() {
+        let grant: DagGrant = "tag:Production=operator".parse().unwrap();
+        assert!(grant.matches(&dag("me", &["etl", "production"])));
+        assert!(grant.matches(&dag("me", &["PRODUCTION"])));
+        assert!(!grant.matches(&dag("me", &["prod"])));
+    }
+
+    #[test]
+    fn owner_grants_match_the_owner() {
+        let grant: DagGrant = "owner:data_team=admin".parse().unwrap();
+        assert!(grant.matches(&dag("data_team", &[])));
+        assert!(!grant.matches(&dag("someone_else", &["data_team"])));
+    }
+}
@@ -0,0 +1,63 @@
+use std::fmt;
+
+use jsonschema::JSONSchema;
+use serde_json::{Map, Value};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParamsError(pub String);
+
+impl fmt::Display for ParamsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl std::error::Error for ParamsError {}
+
+/// Check that a DAG's `params_schema` is a usable JSON schema for an object
+pub fn check_schema(schema: &Value) -> Result<(), ParamsError> {
+    if !schema.is_object() {
+        return Err(ParamsError("params_schema must be a JSON object".to_string()));
+    }
+    JSONSchema::compile(schema)
+        .map(|_| ())
+        .map_err(|err| ParamsError(format!("invalid params_schema: {}", err)))
+}
+
+/// The `conf` a run is created with: top-level properties missing from `conf` take their schema
+/// `default`, then the result is validated against the schema. Without a schema any object is
+/// accepted as is.
+pub fn resolve_conf(
+    schema: Option<&Value>,
+    mut conf: Map<String, Value>,
+) -> Result<Map<String, Value>, ParamsError> {
+    let Some(schema) = schema else {
+        return Ok(conf);
+    };
+
+    if let Some(properties) = schema.get("properties").and_then(Value::as_object) {
+        for (name, property) in properties {
+            if let Some(default) = property.get("default") {
+                conf.entry(name.clone()).or_insert_with(|| default.clone());
+            }
+        }
+    }
+
+    let compiled = JSONSchema::compile(schema)
+        .map_err(|err| ParamsError(format!("invalid params_schema: {}", err)))?;
+    let instance = Value::Object(conf.clone());
+    if let Err(errors) = compiled.validate(&instance) {
+        let messages: Vec<String> = errors
+            .map(|err| {
+                let path = err.instance_path.to_string();
+                if path.is_empty() {
+                    err.to_string()
+                } else {
+                    format!("{}: {}", path, err)
+                }
+            })
+            .collect();
+        return Err(ParamsError(format!("conf does not match params_schema: {}", messages.join("; "))));
+    }
+    Ok(conf)
+}
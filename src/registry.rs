@@ -0,0 +1,1262 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::path::Path;
+use std::sync::{Arc, Mutex, RwLock};
+
+use chrono::{DateTime, SecondsFormat, Utc};
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use crate::models::{ApiToken, AuditEntry, AuditQuery, DAGGraph, DagRun, RunState, Session, User, DAG};
+
+/// Errors raised by a DAG registry backend
+#[derive(Debug)]
+pub enum RegistryError {
+    NotFound(String),
+    AlreadyExists(String),
+    RunNotFound { dag_id: String, run_id: String },
+    RunAlreadyExists { dag_id: String, run_id: String },
+    UserNotFound(String),
+    UserAlreadyExists(String),
+    TokenNotFound(String),
+    Storage(String),
+}
+
+impl fmt::Display for RegistryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RegistryError::NotFound(id) => write!(f, "DAG '{}' not found", id),
+            RegistryError::AlreadyExists(id) => write!(f, "DAG '{}' already exists", id),
+            RegistryError::RunNotFound { dag_id, run_id } => {
+                write!(f, "run '{}' of DAG '{}' not found", run_id, dag_id)
+            }
+            RegistryError::RunAlreadyExists { dag_id, run_id } => {
+                write!(f, "run '{}' of DAG '{}' already exists", run_id, dag_id)
+            }
+            RegistryError::UserNotFound(username) => write!(f, "user '{}' not found", username),
+            RegistryError::UserAlreadyExists(username) => write!(f, "user '{}' already exists", username),
+            RegistryError::TokenNotFound(id) => write!(f, "API token '{}' not found", id),
+            RegistryError::Storage(msg) => write!(f, "storage error: {}", msg),
+        }
+    }
+}
+
+impl std::error::Error for RegistryError {}
+
+impl From<rusqlite::Error> for RegistryError {
+    fn from(err: rusqlite::Error) -> Self {
+        RegistryError::Storage(err.to_string())
+    }
+}
+
+impl From<serde_json::Error> for RegistryError {
+    fn from(err: serde_json::Error) -> Self {
+        RegistryError::Storage(err.to_string())
+    }
+}
+
+/// Storage for DAG records, keyed by `dag_id`
+pub trait DagRegistry: Send + Sync {
+    /// All DAGs, ordered by `dag_id`
+    fn list(&self) -> Result<Vec<DAG>, RegistryError>;
+    fn get(&self, dag_id: &str) -> Result<Option<DAG>, RegistryError>;
+    fn create(&self, dag: DAG) -> Result<DAG, RegistryError>;
+    fn update(&self, dag: DAG) -> Result<DAG, RegistryError>;
+    /// Read, change and write back a DAG as one atomic step, so a change to some fields does not
+    /// overwrite concurrent changes to others (the scheduler's `next_run` and a pause, say)
+    fn modify(&self, dag_id: &str, modify: &mut dyn FnMut(&mut DAG)) -> Result<DAG, RegistryError>;
+    /// Remove a DAG together with every stored graph version
+    fn delete(&self, dag_id: &str) -> Result<(), RegistryError>;
+
+    /// Latest stored graph version for a DAG
+    fn get_graph(&self, dag_id: &str) -> Result<Option<DAGGraph>, RegistryError>;
+    fn get_graph_version(&self, dag_id: &str, version: u32) -> Result<Option<DAGGraph>, RegistryError>;
+    /// Store a graph as a new version and return its number. Storing a graph identical to the
+    /// latest version is a no-op that returns the existing version.
+    fn put_graph(&self, graph: DAGGraph) -> Result<u32, RegistryError>;
+
+    /// Create or replace a DAG and store its graph in a single atomic step. `merge` receives the
+    /// currently stored record, if any, and returns the record to save.
+    fn upsert_with_graph(
+        &self,
+        dag_id: &str,
+        merge: &dyn Fn(Option<DAG>) -> DAG,
+        graph: DAGGraph,
+    ) -> Result<DAG, RegistryError>;
+}
+
+/// Storage for DAG runs, keyed by `(dag_id, run_id)`
+pub trait RunStore: Send + Sync {
+    fn create_run(&self, run: DagRun) -> Result<DagRun, RegistryError>;
+    fn update_run(&self, run: DagRun) -> Result<DagRun, RegistryError>;
+    /// Read, change and write back a run as one atomic step, so concurrent writers (the
+    /// scheduler and finishing tasks) do not overwrite each other's changes
+    fn modify_run(
+        &self,
+        dag_id: &str,
+        run_id: &str,
+        modify: &mut dyn FnMut(&mut DagRun),
+    ) -> Result<DagRun, RegistryError>;
+    fn get_run(&self, dag_id: &str, run_id: &str) -> Result<Option<DagRun>, RegistryError>;
+    /// Runs of one DAG, newest logical date first
+    fn list_runs(&self, dag_id: &str) -> Result<Vec<DagRun>, RegistryError>;
+    /// Queued and running runs across every DAG, oldest logical date first
+    fn active_runs(&self) -> Result<Vec<DagRun>, RegistryError>;
+    /// Per-DAG run totals, keyed by `dag_id`
+    fn run_counts(&self) -> Result<HashMap<String, RunCounts>, RegistryError>;
+}
+
+/// Storage for user accounts with their sessions and API tokens. Sessions and tokens are
+/// looked up by the digest of their secret, never the secret itself.
+pub trait UserStore: Send + Sync {
+    /// All users, ordered by username
+    fn list_users(&self) -> Result<Vec<User>, RegistryError>;
+    fn get_user(&self, username: &str) -> Result<Option<User>, RegistryError>;
+    fn create_user(&self, user: User) -> Result<User, RegistryError>;
+    fn update_user(&self, user: User) -> Result<User, RegistryError>;
+    /// Remove a user together with their sessions and tokens
+    fn delete_user(&self, username: &str) -> Result<(), RegistryError>;
+
+    fn create_session(&self, session: Session) -> Result<Session, RegistryError>;
+    fn get_session(&self, digest: &str) -> Result<Option<Session>, RegistryError>;
+    /// Deleting a session that does not exist is not an error
+    fn delete_session(&self, digest: &str) -> Result<(), RegistryError>;
+    /// Drop every session that expired before `now`. Returns how many were dropped.
+    fn delete_expired_sessions(&self, now: DateTime<Utc>) -> Result<usize, RegistryError>;
+
+    fn create_token(&self, token: ApiToken) -> Result<ApiToken, RegistryError>;
+    /// Tokens of one user, oldest first
+    fn list_tokens(&self, username: &str) -> Result<Vec<ApiToken>, RegistryError>;
+    fn get_token_by_digest(&self, digest: &str) -> Result<Option<ApiToken>, RegistryError>;
+    /// Revoke one of a user's tokens
+    fn delete_token(&self, username: &str, id: &str) -> Result<(), RegistryError>;
+    fn touch_token(&self, id: &str, used_at: DateTime<Utc>) -> Result<(), RegistryError>;
+}
+
+/// Append-only log of operator actions. Entries are never changed or removed.
+pub trait AuditStore: Send + Sync {
+    /// Store an entry, giving it the next id
+    fn append_audit(&self, entry: AuditEntry) -> Result<AuditEntry, RegistryError>;
+    /// The entries matching the filters of `query`, newest first, skipping `offset` and returning at
+    /// most `limit`, with how many matched in all. The page fields of `query` are ignored.
+    fn list_audit(
+        &self,
+        query: &AuditQuery,
+        offset: usize,
+        limit: usize,
+    ) -> Result<(Vec<AuditEntry>, usize), RegistryError>;
+}
+
+/// Aggregate run statistics for one DAG
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RunCounts {
+    pub total: usize,
+    pub succeeded: usize,
+    pub failed: usize,
+    /// Queued and running runs
+    pub active: usize,
+    pub last_logical_date: Option<DateTime<Utc>>,
+}
+
+impl RunCounts {
+    fn add(&mut self, state: RunState, count: usize, logical_date: DateTime<Utc>) {
+        self.total += count;
+        match state {
+            RunState::SUCCEEDED => self.succeeded += count,
+            RunState::FAILED => self.failed += count,
+            RunState::QUEUED | RunState::RUNNING => self.active += count,
+        }
+        if self.last_logical_date.is_none_or(|last| logical_date > last) {
+            self.last_logical_date = Some(logical_date);
+        }
+    }
+}
+
+/// Whether two graphs have the same structure, ignoring their version numbers
+fn same_structure(a: &DAGGraph, b: &DAGGraph) -> bool {
+    a.tasks == b.tasks && a.edges == b.edges
+}
+
+/// Registry that lives only for the lifetime of the process
+#[derive(Default)]
+pub struct InMemoryRegistry {
+    dags: RwLock<BTreeMap<String, DAG>>,
+    graphs: RwLock<BTreeMap<String, Vec<DAGGraph>>>,
+    runs: RwLock<BTreeMap<(String, String), DagRun>>,
+    users: RwLock<BTreeMap<String, User>>,
+    sessions: RwLock<HashMap<String, Session>>,
+    tokens: RwLock<BTreeMap<String, ApiToken>>,
+    audit: RwLock<Vec<AuditEntry>>,
+}
+
+impl InMemoryRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl DagRegistry for InMemoryRegistry {
+    fn list(&self) -> Result<Vec<DAG>, RegistryError> {
+        let dags = self.dags.read().unwrap();
+        Ok(dags.values().cloned().collect())
+    }
+
+    fn get(&self, dag_id: &str) -> Result<Option<DAG>, RegistryError> {
+        let dags = self.dags.read().unwrap();
+        Ok(dags.get(dag_id).cloned())
+    }
+
+    fn create(&self, dag: DAG) -> Result<DAG, RegistryError> {
+        let mut dags = self.dags.write().unwrap();
+        if dags.contains_key(&dag.dag_id) {
+            return Err(RegistryError::AlreadyExists(dag.dag_id));
+        }
+        dags.insert(dag.dag_id.clone(), dag.clone());
+        Ok(dag)
+    }
+
+    fn update(&self, dag: DAG) -> Result<DAG, RegistryError> {
+        let mut dags = self.dags.write().unwrap();
+        match dags.get_mut(&dag.dag_id) {
+            Some(existing) => {
+                *existing = dag.clone();
+                Ok(dag)
+            }
+            None => Err(RegistryError::NotFound(dag.dag_id)),
+        }
+    }
+
+    fn modify(&self, dag_id: &str, modify: &mut dyn FnMut(&mut DAG)) -> Result<DAG, RegistryError> {
+        let mut dags = self.dags.write().unwrap();
+        match dags.get_mut(dag_id) {
+            Some(dag) => {
+                modify(dag);
+                Ok(dag.clone())
+            }
+            None => Err(RegistryError::NotFound(dag_id.to_string())),
+        }
+    }
+
+    fn delete(&self, dag_id: &str) -> Result<(), RegistryError> {
+        let mut dags = self.dags.write().unwrap();
+        match dags.remove(dag_id) {
+            Some(_) => {
+                self.graphs.write().unwrap().remove(dag_id);
+                self.runs.write().unwrap().retain(|(id, _), _| id != dag_id);
+                Ok(())
+            }
+            None => Err(RegistryError::NotFound(dag_id.to_string())),
+        }
+    }
+
+    fn get_graph(&self, dag_id: &str) -> Result<Option<DAGGraph>, RegistryError> {
+        let graphs = self.graphs.read().unwrap();
+        Ok(graphs.get(dag_id).and_then(|versions| versions.last().cloned()))
+    }
+
+    fn get_graph_version(&self, dag_id: &str, version: u32) -> Result<Option<DAGGraph>, RegistryError> {
+        let graphs = self.graphs.read().unwrap();
+        Ok(graphs
+            .get(dag_id)
+            .and_then(|versions| versions.iter().find(|g| g.version == version).cloned()))
+    }
+
+    fn put_graph(&self, graph: DAGGraph) -> Result<u32, RegistryError> {
+        let mut graphs = self.graphs.write().unwrap();
+        Ok(push_graph_version(&mut graphs, graph))
+    }
+
+    fn upsert_with_graph(
+        &self,
+        dag_id: &str,
+        merge: &dyn Fn(Option<DAG>) -> DAG,
+        graph: DAGGraph,
+    ) -> Result<DAG, RegistryError> {
+        let mut dags = self.dags.write().unwrap();
+        let mut graphs = self.graphs.write().unwrap();
+        let dag = merge(dags.get(dag_id).cloned());
+        dags.insert(dag.dag_id.clone(), dag.clone());
+        push_graph_version(&mut graphs, graph);
+        Ok(dag)
+    }
+}
+
+impl RunStore for InMemoryRegistry {
+    fn create_run(&self, run: DagRun) -> Result<DagRun, RegistryError> {
+        let mut runs = self.runs.write().unwrap();
+        let key = (run.dag_id.clone(), run.run_id.clone());
+        if runs.contains_key(&key) {
+            return Err(RegistryError::RunAlreadyExists {
+                dag_id: run.dag_id,
+                run_id: run.run_id,
+            });
+        }
+        runs.insert(key, run.clone());
+        Ok(run)
+    }
+
+    fn update_run(&self, run: DagRun) -> Result<DagRun, RegistryError> {
+        let mut runs = self.runs.write().unwrap();
+        match runs.get_mut(&(run.dag_id.clone(), run.run_id.clone())) {
+            Some(existing) => {
+                *existing = run.clone();
+                Ok(run)
+            }
+            None => Err(RegistryError::RunNotFound {
+                dag_id: run.dag_id,
+                run_id: run.run_id,
+            }),
+        }
+    }
+
+    fn modify_run(
+        &self,
+        dag_id: &str,
+        run_id: &str,
+        modify: &mut dyn FnMut(&mut DagRun),
+    ) -> Result<DagRun, RegistryError> {
+        let mut runs = self.runs.write().unwrap();
+        match runs.get_mut(&(dag_id.to_string(), run_id.to_string())) {
+            Some(run) => {
+                modify(run);
+                Ok(run.clone())
+            }
+            None => Err(RegistryError::RunNotFound {
+                dag_id: dag_id.to_string(),
+                run_id: run_id.to_string(),
+            }),
+        }
+    }
+
+    fn get_run(&self, dag_id: &str, run_id: &str) -> Result<Option<DagRun>, RegistryError> {
+        let runs = self.runs.read().unwrap();
+        Ok(runs.get(&(dag_id.to_string(), run_id.to_string())).cloned())
+    }
+
+    fn list_runs(&self, dag_id: &str) -> Result<Vec<DagRun>, RegistryError> {
+        let runs = self.runs.read().unwrap();
+        let mut list: Vec<DagRun> = runs
+            .values()
+            .filter(|run| run.dag_id == dag_id)
+            .cloned()
+            .collect();
+        list.sort_by_key(|run| std::cmp::Reverse(run.logical_date));
+        Ok(list)
+    }
+
+    fn active_runs(&self) -> Result<Vec<DagRun>, RegistryError> {
+        let runs = self.runs.read().unwrap();
+        let mut list: Vec<DagRun> = runs
+            .values()
+            .filter(|run| run.state.is_active())
+            .cloned()
+            .collect();
+        list.sort_by_key(|run| run.logical_date);
+        Ok(list)
+    }
+
+    fn run_counts(&self) -> Result<HashMap<String, RunCounts>, RegistryError> {
+        let runs = self.runs.read().unwrap();
+        let mut counts: HashMap<String, RunCounts> = HashMap::new();
+        for run in runs.values() {
+            counts
+                .entry(run.dag_id.clone())
+                .or_default()
+                .add(run.state, 1, run.logical_date);
+        }
+        Ok(counts)
+    }
+}
+
+impl UserStore for InMemoryRegistry {
+    fn list_users(&self) -> Result<Vec<User>, RegistryError> {
+        let users = self.users.read().unwrap();
+        Ok(users.values().cloned().collect())
+    }
+
+    fn get_user(&self, username: &str) -> Result<Option<User>, RegistryError> {
+        let users = self.users.read().unwrap();
+        Ok(users.get(username).cloned())
+    }
+
+    fn create_user(&self, user: User) -> Result<User, RegistryError> {
+        let mut users = self.users.write().unwrap();
+        if users.contains_key(&user.username) {
+            return Err(RegistryError::UserAlreadyExists(user.username));
+        }
+        users.insert(user.username.clone(), user.clone());
+        Ok(user)
+    }
+
+    fn update_user(&self, user: User) -> Result<User, RegistryError> {
+        let mut users = self.users.write().unwrap();
+        match users.get_mut(&user.username) {
+            Some(existing) => {
+                *existing = user.clone();
+                Ok(user)
+            }
+            None => Err(RegistryError::UserNotFound(user.username)),
+        }
+    }
+
+    fn delete_user(&self, username: &str) -> Result<(), RegistryError> {
+        let mut users = self.users.write().unwrap();
+        match users.remove(username) {
+            Some(_) => {
+                self.sessions.write().unwrap().retain(|_, session| session.username != username);
+                self.tokens.write().unwrap().retain(|_, token| token.username != username);
+                Ok(())
+            }
+            None => Err(RegistryError::UserNotFound(username.to_string())),
+        }
+    }
+
+    fn create_session(&self, session: Session) -> Result<Session, RegistryError> {
+        let mut sessions = self.sessions.write().unwrap();
+        sessions.insert(session.digest.clone(), session.clone());
+        Ok(session)
+    }
+
+    fn get_session(&self, digest: &str) -> Result<Option<Session>, RegistryError> {
+        let sessions = self.sessions.read().unwrap();
+        Ok(sessions.get(digest).cloned())
+    }
+
+    fn delete_session(&self, digest: &str) -> Result<(), RegistryError> {
+        self.sessions.write().unwrap().remove(digest);
+        Ok(())
+    }
+
+    fn delete_expired_sessions(&self, now: DateTime<Utc>) -> Result<usize, RegistryError> {
+        let mut sessions = self.sessions.write().unwrap();
+        let before = sessions.len();
+        sessions.retain(|_, session| session.expires_at > now);
+        Ok(before - sessions.len())
+    }
+
+    fn create_token(&self, token: ApiToken) -> Result<ApiToken, RegistryError> {
+        let mut tokens = self.tokens.write().unwrap();
+        tokens.insert(token.id.clone(), token.clone());
+        Ok(token)
+    }
+
+    fn list_tokens(&self, username: &str) -> Result<Vec<ApiToken>, RegistryError> {
+        let tokens = self.tokens.read().unwrap();
+        let mut list: Vec<ApiToken> = tokens
+            .values()
+            .filter(|token| token.username == username)
+            .cloned()
+            .collect();
+        list.sort_by_key(|token| token.created_at);
+        Ok(list)
+    }
+
+    fn get_token_by_digest(&self, digest: &str) -> Result<Option<ApiToken>, RegistryError> {
+        let tokens = self.tokens.read().unwrap();
+        Ok(tokens.values().find(|token| token.digest == digest).cloned())
+    }
+
+    fn delete_token(&self, username: &str, id: &str) -> Result<(), RegistryError> {
+        let mut tokens = self.tokens.write().unwrap();
+        match tokens.get(id) {
+            Some(token) if token.username == username => {
+                tokens.remove(id);
+                Ok(())
+            }
+            _ => Err(RegistryError::TokenNotFound(id.to_string())),
+        }
+    }
+
+    fn touch_token(&self, id: &str, used_at: DateTime<Utc>) -> Result<(), RegistryError> {
+        let mut tokens = self.tokens.write().unwrap();
+        if let Some(token) = tokens.get_mut(id) {
+            token.last_used = Some(used_at);
+        }
+        Ok(())
+    }
+}
+
+impl AuditStore for InMemoryRegistry {
+    fn append_audit(&self, mut entry: AuditEntry) -> Result<AuditEntry, RegistryError> {
+        let mut audit = self.audit.write().unwrap();
+        entry.id = audit.len() as u64 + 1;
+        audit.push(entry.clone());
+        Ok(entry)
+    }
+
+    fn list_audit(
+        &self,
+        query: &AuditQuery,
+        offset: usize,
+        limit: usize,
+    ) -> Result<(Vec<AuditEntry>, usize), RegistryError> {
+        let audit = self.audit.read().unwrap();
+        let matching: Vec<&AuditEntry> = audit.iter().rev().filter(|entry| audit_matches(entry, query)).collect();
+        let total = matching.len();
+        Ok((matching.into_iter().skip(offset).take(limit).cloned().collect(), total))
+    }
+}
+
+fn audit_matches(entry: &AuditEntry, query: &AuditQuery) -> bool {
+    let same = |wanted: &Option<String>, value: Option<&String>| {
+        wanted.as_ref().is_none_or(|wanted| Some(wanted) == value)
+    };
+    same(&query.actor, Some(&entry.actor))
+        && query.action.is_none_or(|action| action == entry.action)
+        && same(&query.dag_id, Some(&entry.dag_id))
+        && same(&query.run_id, entry.run_id.as_ref())
+        && same(&query.task_id, entry.task_id.as_ref())
+        && query.since.is_none_or(|since| entry.timestamp >= since)
+        && query.until.is_none_or(|until| entry.timestamp < until)
+}
+
+fn push_graph_version(graphs: &mut BTreeMap<String, Vec<DAGGraph>>, mut graph: DAGGraph) -> u32 {
+    let versions = graphs.entry(graph.dag_id.clone()).or_default();
+    if let Some(latest) = versions.last() {
+        if same_structure(latest, &graph) {
+            return latest.version;
+        }
+    }
+    graph.version = versions.last().map_or(1, |latest| latest.version + 1);
+    versions.push(graph);
+    versions.last().unwrap().version
+}
+
+/// A DAG registry with the run, user and audit stores that go with it
+pub struct Stores {
+    pub registry: Arc<dyn DagRegistry>,
+    pub runs: Arc<dyn RunStore>,
+    pub users: Arc<dyn UserStore>,
+    pub audit: Arc<dyn AuditStore>,
+}
+
+impl Stores {
+    fn from_store<S: DagRegistry + RunStore + UserStore + AuditStore + 'static>(store: S) -> Self {
+        let store = Arc::new(store);
+        Self {
+            registry: store.clone(),
+            runs: store.clone(),
+            users: store.clone(),
+            audit: store,
+        }
+    }
+}
+
+/// The stores for a database URL: "memory" keeps everything in-process, anything else is a
+/// SQLite file, given as a path or `sqlite://<path>`
+pub fn open(database_url: &str) -> Result<Stores, RegistryError> {
+    if database_url == "memory" {
+        Ok(Stores::from_store(InMemoryRegistry::new()))
+    } else {
+        Ok(Stores::from_store(SqliteRegistry::open(sqlite_path(database_url))?))
+    }
+}
+
+/// The file a SQLite database URL points at
+pub fn sqlite_path(database_url: &str) -> &str {
+    database_url.strip_prefix("sqlite://").unwrap_or(database_url)
+}
+
+/// Registry backed by an embedded SQLite database file
+pub struct SqliteRegistry {
+    conn: Mutex<Connection>,
+}
+
+impl SqliteRegistry {
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, RegistryError> {
+        Self::init(Connection::open(path)?)
+    }
+
+    pub fn open_in_memory() -> Result<Self, RegistryError> {
+        Self::init(Connection::open_in_memory()?)
+    }
+
+    fn init(conn: Connection) -> Result<Self, RegistryError> {
+        // The server and CLI commands may have the same file open
+        conn.busy_timeout(std::time::Duration::from_secs(5))?;
+        conn.execute_batch(
+            "CREATE TABLE IF NOT EXISTS dags (
+                dag_id TEXT PRIMARY KEY,
+                body   TEXT NOT NULL
+            );
+            CREATE TABLE IF NOT EXISTS dag_graphs (
+                dag_id  TEXT NOT NULL,
+                version INTEGER NOT NULL,
+                body    TEXT NOT NULL,
+                PRIMARY KEY (dag_id, version)
+            );
+            CREATE TABLE IF NOT EXISTS dag_runs (
+                dag_id       TEXT NOT NULL,
+                run_id       TEXT NOT NULL,
+                logical_date TEXT NOT NULL,
+                state        TEXT NOT NULL,
+                body         TEXT NOT NULL,
+                PRIMARY KEY (dag_id, run_id)
+            );
+            CREATE TABLE IF NOT EXISTS users (
+                username TEXT PRIMARY KEY,
+                body     TEXT NOT NULL
+            );
+            CREATE TABLE IF NOT EXISTS sessions (
+                digest     TEXT PRIMARY KEY,
+                username   TEXT NOT NULL,
+                expires_at TEXT NOT NULL,
+                body       TEXT NOT NULL
+            );
+            CREATE TABLE IF NOT EXISTS api_tokens (
+                id       TEXT PRIMARY KEY,
+                username TEXT NOT NULL,
+                digest   TEXT NOT NULL UNIQUE,
+                body     TEXT NOT NULL
+            );
+            CREATE TABLE IF NOT EXISTS audit_log (
+                id        INTEGER PRIMARY KEY AUTOINCREMENT,
+                timestamp TEXT NOT NULL,
+                actor     TEXT NOT NULL,
+                action    TEXT NOT NULL,
+                dag_id    TEXT NOT NULL,
+                run_id    TEXT,
+                task_id   TEXT,
+                body      TEXT NOT NULL
+            );
+            CREATE INDEX IF NOT EXISTS audit_log_dag ON audit_log (dag_id, id);
+            CREATE TRIGGER IF NOT EXISTS audit_log_no_update BEFORE UPDATE ON audit_log
+            BEGIN
+                SELECT RAISE(ABORT, 'the audit log is append-only');
+            END;
+            CREATE TRIGGER IF NOT EXISTS audit_log_no_delete BEFORE DELETE ON audit_log
+            BEGIN
+                SELECT RAISE(ABORT, 'the audit log is append-only');
+            END;",
+        )?;
+        Ok(Self {
+            conn: Mutex::new(conn),
+        })
+    }
+
+    /// Bring the database up to `SCHEMA_VERSION`: rewrite every stored DAG, graph, run and user in the
+    /// current format so fields added since they were written are filled in
+    pub fn migrate(&self) -> Result<MigrationReport, RegistryError> {
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn.transaction()?;
+        let from_version: i64 = tx.query_row("PRAGMA user_version", [], |row| row.get(0))?;
+        if from_version > SCHEMA_VERSION {
+            return Err(RegistryError::Storage(format!(
+                "database schema version {} is newer than this build ({})",
+                from_version, SCHEMA_VERSION
+            )));
+        }
+
+        let dags = rewrite_bodies::<DAG>(&tx, "dags")?;
+        let graphs = rewrite_bodies::<DAGGraph>(&tx, "dag_graphs")?;
+        let runs = rewrite_bodies::<DagRun>(&tx, "dag_runs")?;
+        if from_version < 3 {
+            // Version 2 users had an admin flag where they now have a role
+            tx.execute_batch(
+                "UPDATE users SET body = json_set(json_remove(body, '$.admin'), '$.role', 'admin')
+                 WHERE json_extract(body, '$.admin') = 1",
+            )?;
+        }
+        let users = rewrite_bodies::<User>(&tx, "users")?;
+        tx.execute_batch(&format!("PRAGMA user_version = {}", SCHEMA_VERSION))?;
+        tx.commit()?;
+
+        Ok(MigrationReport {
+            from_version,
+            to_version: SCHEMA_VERSION,
+            dags,
+            graphs,
+            runs,
+            users,
+        })
+    }
+}
+
+/// Version of the stored data written by this build, kept in SQLite's `user_version`
+pub const SCHEMA_VERSION: i64 = 3;
+
+/// What `SqliteRegistry::migrate` did
+#[derive(Debug, Clone, Serialize)]
+pub struct MigrationReport {
+    pub from_version: i64,
+    pub to_version: i64,
+    pub dags: usize,
+    pub graphs: usize,
+    pub runs: usize,
+    pub users: usize,
+}
+
+/// Re-serialize every JSON body of a table through its model type. Returns the rows rewritten.
+fn rewrite_bodies<T: DeserializeOwned + Serialize>(conn: &Connection, table: &str) -> Result<usize, RegistryError> {
+    let rows: Vec<(i64, String)> = conn
+        .prepare(&format!("SELECT rowid, body FROM {}", table))?
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+        .collect::<Result<_, _>>()?;
+    for (rowid, body) in &rows {
+        let value: T = serde_json::from_str(body)?;
+        conn.execute(
+            &format!("UPDATE {} SET body = ?1 WHERE rowid = ?2", table),
+            params![serde_json::to_string(&value)?, rowid],
+        )?;
+    }
+    Ok(rows.len())
+}
+
+impl DagRegistry for SqliteRegistry {
+    fn list(&self) -> Result<Vec<DAG>, RegistryError> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare("SELECT body FROM dags ORDER BY dag_id")?;
+        let rows = stmt.query_map([], |row| row.get::<_, String>(0))?;
+
+        let mut dags = Vec::new();
+        for body in rows {
+            dags.push(serde_json::from_str(&body?)?);
+        }
+        Ok(dags)
+    }
+
+    fn get(&self, dag_id: &str) -> Result<Option<DAG>, RegistryError> {
+        let conn = self.conn.lock().unwrap();
+        let body: Option<String> = conn
+            .query_row("SELECT body FROM dags WHERE dag_id = ?1", params![dag_id], |row| {
+                row.get(0)
+            })
+            .optional()?;
+        match body {
+            Some(body) => Ok(Some(serde_json::from_str(&body)?)),
+            None => Ok(None),
+        }
+    }
+
+    fn create(&self, dag: DAG) -> Result<DAG, RegistryError> {
+        let conn = self.conn.lock().unwrap();
+        let body = serde_json::to_string(&dag)?;
+        let inserted = conn.execute(
+            "INSERT OR IGNORE INTO dags (dag_id, body) VALUES (?1, ?2)",
+            params![dag.dag_id, body],
+        )?;
+        if inserted == 0 {
+            return Err(RegistryError::AlreadyExists(dag.dag_id));
+        }
+        Ok(dag)
+    }
+
+    fn update(&self, dag: DAG) -> Result<DAG, RegistryError> {
+        let conn = self.conn.lock().unwrap();
+        let body = serde_json::to_string(&dag)?;
+        let updated = conn.execute(
+            "UPDATE dags SET body = ?2 WHERE dag_id = ?1",
+            params![dag.dag_id, body],
+        )?;
+        if updated == 0 {
+            return Err(RegistryError::NotFound(dag.dag_id));
+        }
+        Ok(dag)
+    }
+
+    fn modify(&self, dag_id: &str, modify: &mut dyn FnMut(&mut DAG)) -> Result<DAG, RegistryError> {
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn.transaction()?;
+        let body: Option<String> = tx
+            .query_row("SELECT body FROM dags WHERE dag_id = ?1", params![dag_id], |row| {
+                row.get(0)
+            })
+            .optional()?;
+        let Some(body) = body else {
+            return Err(RegistryError::NotFound(dag_id.to_string()));
+        };
+        let mut dag: DAG = serde_json::from_str(&body)?;
+        modify(&mut dag);
+        tx.execute(
+            "UPDATE dags SET body = ?2 WHERE dag_id = ?1",
+            params![dag_id, serde_json::to_string(&dag)?],
+        )?;
+        tx.commit()?;
+        Ok(dag)
+    }
+
+    fn delete(&self, dag_id: &str) -> Result<(), RegistryError> {
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn.transaction()?;
+        let deleted = tx.execute("DELETE FROM dags WHERE dag_id = ?1", params![dag_id])?;
+        if deleted == 0 {
+            return Err(RegistryError::NotFound(dag_id.to_string()));
+        }
+        tx.execute("DELETE FROM dag_graphs WHERE dag_id = ?1", params![dag_id])?;
+        tx.execute("DELETE FROM dag_runs WHERE dag_id = ?1", params![dag_id])?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    fn get_graph(&self, dag_id: &str) -> Result<Option<DAGGraph>, RegistryError> {
+        let conn = self.conn.lock().unwrap();
+        latest_graph(&conn, dag_id)
+    }
+
+    fn get_graph_version(&self, dag_id: &str, version: u32) -> Result<Option<DAGGraph>, RegistryError> {
+        let conn = self.conn.lock().unwrap();
+        let body: Option<String> = conn
+            .query_row(
+                "SELECT body FROM dag_graphs WHERE dag_id = ?1 AND version = ?2",
+                params![dag_id, version],
+                |row| row.get(0),
+            )
+            .optional()?;
+        match body {
+            Some(body) => Ok(Some(serde_json::from_str(&body)?)),
+            None => Ok(None),
+        }
+    }
+
+    fn put_graph(&self, graph: DAGGraph) -> Result<u32, RegistryError> {
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn.transaction()?;
+        let version = insert_graph_version(&tx, graph)?;
+        tx.commit()?;
+        Ok(version)
+    }
+
+    fn upsert_with_graph(
+        &self,
+        dag_id: &str,
+        merge: &dyn Fn(Option<DAG>) -> DAG,
+        graph: DAGGraph,
+    ) -> Result<DAG, RegistryError> {
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn.transaction()?;
+        let existing: Option<String> = tx
+            .query_row("SELECT body FROM dags WHERE dag_id = ?1", params![dag_id], |row| {
+                row.get(0)
+            })
+            .optional()?;
+        let existing = match existing {
+            Some(body) => Some(serde_json::from_str(&body)?),
+            None => None,
+        };
+        let dag = merge(existing);
+        tx.execute(
+            "INSERT OR REPLACE INTO dags (dag_id, body) VALUES (?1, ?2)",
+            params![dag.dag_id, serde_json::to_string(&dag)?],
+        )?;
+        insert_graph_version(&tx, graph)?;
+        tx.commit()?;
+        Ok(dag)
+    }
+}
+
+impl RunStore for SqliteRegistry {
+    fn create_run(&self, run: DagRun) -> Result<DagRun, RegistryError> {
+        let conn = self.conn.lock().unwrap();
+        let inserted = conn.execute(
+            "INSERT OR IGNORE INTO dag_runs (dag_id, run_id, logical_date, state, body)
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![
+                run.dag_id,
+                run.run_id,
+                run.logical_date.to_rfc3339_opts(SecondsFormat::Micros, true),
+                run_state_key(&run)?,
+                serde_json::to_string(&run)?
+            ],
+        )?;
+        if inserted == 0 {
+            return Err(RegistryError::RunAlreadyExists {
+                dag_id: run.dag_id,
+                run_id: run.run_id,
+            });
+        }
+        Ok(run)
+    }
+
+    fn update_run(&self, run: DagRun) -> Result<DagRun, RegistryError> {
+        let conn = self.conn.lock().unwrap();
+        let updated = conn.execute(
+            "UPDATE dag_runs SET state = ?3, body = ?4 WHERE dag_id = ?1 AND run_id = ?2",
+            params![run.dag_id, run.run_id, run_state_key(&run)?, serde_json::to_string(&run)?],
+        )?;
+        if updated == 0 {
+            return Err(RegistryError::RunNotFound {
+                dag_id: run.dag_id,
+                run_id: run.run_id,
+            });
+        }
+        Ok(run)
+    }
+
+    fn modify_run(
+        &self,
+        dag_id: &str,
+        run_id: &str,
+        modify: &mut dyn FnMut(&mut DagRun),
+    ) -> Result<DagRun, RegistryError> {
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn.transaction()?;
+        let body: Option<String> = tx
+            .query_row(
+                "SELECT body FROM dag_runs WHERE dag_id = ?1 AND run_id = ?2",
+                params![dag_id, run_id],
+                |row| row.get(0),
+            )
+            .optional()?;
+        let Some(body) = body else {
+            return Err(RegistryError::RunNotFound {
+                dag_id: dag_id.to_string(),
+                run_id: run_id.to_string(),
+            });
+        };
+        let mut run: DagRun = serde_json::from_str(&body)?;
+        modify(&mut run);
+        tx.execute(
+            "UPDATE dag_runs SET state = ?3, body = ?4 WHERE dag_id = ?1 AND run_id = ?2",
+            params![dag_id, run_id, run_state_key(&run)?, serde_json::to_string(&run)?],
+        )?;
+        tx.commit()?;
+        Ok(run)
+    }
+
+    fn get_run(&self, dag_id: &str, run_id: &str) -> Result<Option<DagRun>, RegistryError> {
+        let conn = self.conn.lock().unwrap();
+        let body: Option<String> = conn
+            .query_row(
+                "SELECT body FROM dag_runs WHERE dag_id = ?1 AND run_id = ?2",
+                params![dag_id, run_id],
+                |row| row.get(0),
+            )
+            .optional()?;
+        match body {
+            Some(body) => Ok(Some(serde_json::from_str(&body)?)),
+            None => Ok(None),
+        }
+    }
+
+    fn list_runs(&self, dag_id: &str) -> Result<Vec<DagRun>, RegistryError> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt =
+            conn.prepare("SELECT body FROM dag_runs WHERE dag_id = ?1 ORDER BY logical_date DESC")?;
+        let rows = stmt.query_map(params![dag_id], |row| row.get::<_, String>(0))?;
+
+        let mut runs = Vec::new();
+        for body in rows {
+            runs.push(serde_json::from_str(&body?)?);
+        }
+        Ok(runs)
+    }
+
+    fn active_runs(&self) -> Result<Vec<DagRun>, RegistryError> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(
+            "SELECT body FROM dag_runs WHERE state IN ('QUEUED', 'RUNNING') ORDER BY logical_date",
+        )?;
+        let rows = stmt.query_map([], |row| row.get::<_, String>(0))?;
+
+        let mut runs = Vec::new();
+        for body in rows {
+            runs.push(serde_json::from_str(&body?)?);
+        }
+        Ok(runs)
+    }
+
+    fn run_counts(&self) -> Result<HashMap<String, RunCounts>, RegistryError> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(
+            "SELECT dag_id, state, COUNT(*), MAX(logical_date) FROM dag_runs GROUP BY dag_id, state",
+        )?;
+        let rows = stmt.query_map([], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, usize>(2)?,
+                row.get::<_, String>(3)?,
+            ))
+        })?;
+
+        let mut counts: HashMap<String, RunCounts> = HashMap::new();
+        for row in rows {
+            let (dag_id, state, count, logical_date) = row?;
+            let state: RunState = serde_json::from_value(serde_json::Value::String(state))?;
+            let logical_date = DateTime::parse_from_rfc3339(&logical_date)
+                .map_err(|e| RegistryError::Storage(e.to_string()))?
+                .with_timezone(&Utc);
+            counts.entry(dag_id).or_default().add(state, count, logical_date);
+        }
+        Ok(counts)
+    }
+}
+
+impl UserStore for SqliteRegistry {
+    fn list_users(&self) -> Result<Vec<User>, RegistryError> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare("SELECT body FROM users ORDER BY username")?;
+        let rows = stmt.query_map([], |row| row.get::<_, String>(0))?;
+
+        let mut users = Vec::new();
+        for body in rows {
+            users.push(serde_json::from_str(&body?)?);
+        }
+        Ok(users)
+    }
+
+    fn get_user(&self, username: &str) -> Result<Option<User>, RegistryError> {
+        let conn = self.conn.lock().unwrap();
+        let body: Option<String> = conn
+            .query_row("SELECT body FROM users WHERE username = ?1", params![username], |row| {
+                row.get(0)
+            })
+            .optional()?;
+        match body {
+            Some(body) => Ok(Some(serde_json::from_str(&body)?)),
+            None => Ok(None),
+        }
+    }
+
+    fn create_user(&self, user: User) -> Result<User, RegistryError> {
+        let conn = self.conn.lock().unwrap();
+        let inserted = conn.execute(
+            "INSERT OR IGNORE INTO users (username, body) VALUES (?1, ?2)",
+            params![user.username, serde_json::to_string(&user)?],
+        )?;
+        if inserted == 0 {
+            return Err(RegistryError::UserAlreadyExists(user.username));
+        }
+        Ok(user)
+    }
+
+    fn update_user(&self, user: User) -> Result<User, RegistryError> {
+        let conn = self.conn.lock().unwrap();
+        let updated = conn.execute(
+            "UPDATE users SET body = ?2 WHERE username = ?1",
+            params![user.username, serde_json::to_string(&user)?],
+        )?;
+        if updated == 0 {
+            return Err(RegistryError::UserNotFound(user.username));
+        }
+        Ok(user)
+    }
+
+    fn delete_user(&self, username: &str) -> Result<(), RegistryError> {
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn.transaction()?;
+        let deleted = tx.execute("DELETE FROM users WHERE username = ?1", params![username])?;
+        if deleted == 0 {
+            return Err(RegistryError::UserNotFound(username.to_string()));
+        }
+        tx.execute("DELETE FROM sessions WHERE username = ?1", params![username])?;
+        tx.execute("DELETE FROM api_tokens WHERE username = ?1", params![username])?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    fn create_session(&self, session: Session) -> Result<Session, RegistryError> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "INSERT OR REPLACE INTO sessions (digest, username, expires_at, body) VALUES (?1, ?2, ?3, ?4)",
+            params![
+                session.digest,
+                session.username,
+                session.expires_at.to_rfc3339_opts(SecondsFormat::Micros, true),
+                serde_json::to_string(&session)?
+            ],
+        )?;
+        Ok(session)
+    }
+
+    fn get_session(&self, digest: &str) -> Result<Option<Session>, RegistryError> {
+        let conn = self.conn.lock().unwrap();
+        let body: Option<String> = conn
+            .query_row("SELECT body FROM sessions WHERE digest = ?1", params![digest], |row| {
+                row.get(0)
+            })
+            .optional()?;
+        match body {
+            Some(body) => Ok(Some(serde_json::from_str(&body)?)),
+            None => Ok(None),
+        }
+    }
+
+    fn delete_session(&self, digest: &str) -> Result<(), RegistryError> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute("DELETE FROM sessions WHERE digest = ?1", params![digest])?;
+        Ok(())
+    }
+
+    fn delete_expired_sessions(&self, now: DateTime<Utc>) -> Result<usize, RegistryError> {
+        let conn = self.conn.lock().unwrap();
+        let deleted = conn.execute(
+            "DELETE FROM sessions WHERE expires_at <= ?1",
+            params![now.to_rfc3339_opts(SecondsFormat::Micros, true)],
+        )?;
+        Ok(deleted)
+    }
+
+    fn create_token(&self, token: ApiToken) -> Result<ApiToken, RegistryError> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "INSERT INTO api_tokens (id, username, digest, body) VALUES (?1, ?2, ?3, ?4)",
+            params![token.id, token.username, token.digest, serde_json::to_string(&token)?],
+        )?;
+        Ok(token)
+    }
+
+    fn list_tokens(&self, username: &str) -> Result<Vec<ApiToken>, RegistryError> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare("SELECT body FROM api_tokens WHERE username = ?1")?;
+        let rows = stmt.query_map(params![username], |row| row.get::<_, String>(0))?;
+
+        let mut tokens = Vec::new();
+        for body in rows {
+            tokens.push(serde_json::from_str::<ApiToken>(&body?)?);
+        }
+        tokens.sort_by_key(|token| token.created_at);
+        Ok(tokens)
+    }
+
+    fn get_token_by_digest(&self, digest: &str) -> Result<Option<ApiToken>, RegistryError> {
+        let conn = self.conn.lock().unwrap();
+        let body: Option<String> = conn
+            .query_row("SELECT body FROM api_tokens WHERE digest = ?1", params![digest], |row| {
+                row.get(0)
+            })
+            .optional()?;
+        match body {
+            Some(body) => Ok(Some(serde_json::from_str(&body)?)),
+            None => Ok(None),
+        }
+    }
+
+    fn delete_token(&self, username: &str, id: &str) -> Result<(), RegistryError> {
+        let conn = self.conn.lock().unwrap();
+        let deleted = conn.execute(
+            "DELETE FROM api_tokens WHERE id = ?1 AND username = ?2",
+            params![id, username],
+        )?;
+        if deleted == 0 {
+            return Err(RegistryError::TokenNotFound(id.to_string()));
+        }
+        Ok(())
+    }
+
+    fn touch_token(&self, id: &str, used_at: DateTime<Utc>) -> Result<(), RegistryError> {
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn.transaction()?;
+        let body: Option<String> = tx
+            .query_row("SELECT body FROM api_tokens WHERE id = ?1", params![id], |row| row.get(0))
+            .optional()?;
+        if let Some(body) = body {
+            let mut token: ApiToken = serde_json::from_str(&body)?;
+            token.last_used = Some(used_at);
+            tx.execute(
+                "UPDATE api_tokens SET body = ?2 WHERE id = ?1",
+                params![id, serde_json::to_string(&token)?],
+            )?;
+        }
+        tx.commit()?;
+        Ok(())
+    }
+}
+
+impl AuditStore for SqliteRegistry {
+    fn append_audit(&self, mut entry: AuditEntry) -> Result<AuditEntry, RegistryError> {
+        let conn = self.conn.lock().unwrap();
+        // The id lives in its own column; the body is stored without it
+        entry.id = 0;
+        conn.execute(
+            "INSERT INTO audit_log (timestamp, actor, action, dag_id, run_id, task_id, body)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            params![
+                entry.timestamp.to_rfc3339_opts(SecondsFormat::Micros, true),
+                entry.actor,
+                entry.action.as_str(),
+                entry.dag_id,
+                entry.run_id,
+                entry.task_id,
+                serde_json::to_string(&entry)?
+            ],
+        )?;
+        entry.id = conn.last_insert_rowid() as u64;
+        Ok(entry)
+    }
+
+    fn list_audit(
+        &self,
+        query: &AuditQuery,
+        offset: usize,
+        limit: usize,
+    ) -> Result<(Vec<AuditEntry>, usize), RegistryError> {
+        const FILTER: &str = "(?1 IS NULL OR actor = ?1)
+            AND (?2 IS NULL OR action = ?2)
+            AND (?3 IS NULL OR dag_id = ?3)
+            AND (?4 IS NULL OR run_id = ?4)
+            AND (?5 IS NULL OR task_id = ?5)
+            AND (?6 IS NULL OR timestamp >= ?6)
+            AND (?7 IS NULL OR timestamp < ?7)";
+        let time = |time: Option<DateTime<Utc>>| time.map(|time| time.to_rfc3339_opts(SecondsFormat::Micros, true));
+        let filter = params![
+            query.actor,
+            query.action.map(|action| action.as_str()),
+            query.dag_id,
+            query.run_id,
+            query.task_id,
+            time(query.since),
+            time(query.until)
+        ];
+
+        let conn = self.conn.lock().unwrap();
+        let total: i64 = conn.query_row(&format!("SELECT COUNT(*) FROM audit_log WHERE {}", FILTER), filter, |row| {
+            row.get(0)
+        })?;
+        let mut stmt = conn.prepare(&format!(
+            "SELECT id, body FROM audit_log WHERE {} ORDER BY id DESC LIMIT {} OFFSET {}",
+            FILTER,
+            limit,
+            // SQLite takes a signed 64-bit offset
+            offset.min(i64::MAX as usize)
+        ))?;
+        let rows = stmt.query_map(filter, |row| Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?)))?;
+
+        let mut entries = Vec::new();
+        for row in rows {
+            let (id, body) = row?;
+            let mut entry: AuditEntry = serde_json::from_str(&body)?;
+            entry.id = id as u64;
+            entries.push(entry);
+        }
+        Ok((entries, total as usize))
+    }
+}
+
+/// The serialized form of a run's state, stored in its own column so active runs can be queried
+fn run_state_key(run: &DagRun) -> Result<String, RegistryError> {
+    match serde_json::to_value(run.state)? {
+        serde_json::Value::String(state) => Ok(state),
+        other => Err(RegistryError::Storage(format!("unexpected run state {}", other))),
+    }
+}
+
+fn insert_graph_version(conn: &Connection, mut graph: DAGGraph) -> Result<u32, RegistryError> {
+    let latest = latest_graph(conn, &graph.dag_id)?;
+    if let Some(latest) = &latest {
+        if same_structure(latest, &graph) {
+            return Ok(latest.version);
+        }
+    }
+    graph.version = latest.map_or(1, |latest| latest.version + 1);
+    conn.execute(
+        "INSERT INTO dag_graphs (dag_id, version, body) VALUES (?1, ?2, ?3)",
+        params![graph.dag_id, graph.version, serde_json::to_string(&graph)?],
+    )?;
+    Ok(graph.version)
+}
+
+fn latest_graph(conn: &Connection, dag_id: &str) -> Result<Option<DAGGraph>, RegistryError> {
+    let body: Option<String> = conn
+        .query_row(
+            "SELECT body FROM dag_graphs WHERE dag_id = ?1 ORDER BY version DESC LIMIT 1",
+            params![dag_id],
+            |row| row.get(0),
+        )
+        .optional()?;
+    match body {
+        Some(body) => Ok(Some(serde_json::from_str(&body)?)),
+        None => Ok(None),
+    }
+}
@@ -0,0 +1,310 @@
+use std::fmt;
+
+use chrono::{DateTime, Datelike, Duration, NaiveDate, NaiveDateTime, TimeZone, Timelike, Utc};
+use chrono_tz::Tz;
+
+use crate::models::DAG;
+
+/// How far ahead to search before declaring that an expression never fires (e.g. `0 0 30 2 *`)
+const MAX_SEARCH_DAYS: i64 = 366 * 5;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScheduleError(pub String);
+
+impl fmt::Display for ScheduleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl std::error::Error for ScheduleError {}
+
+/// A parsed five-field cron expression evaluated in a specific timezone
+#[derive(Debug, Clone)]
+pub struct Schedule {
+    minutes: u64,
+    hours: u64,
+    days_of_month: u64,
+    months: u64,
+    days_of_week: u64,
+    /// Cron ORs day-of-month and day-of-week when both are restricted
+    dom_restricted: bool,
+    dow_restricted: bool,
+    timezone: Tz,
+}
+
+impl Schedule {
+    /// Parse a cron expression (`*/5 * * * *`) or one of the `@hourly`, `@daily`, `@weekly`,
+    /// `@monthly` and `@yearly` presets
+    pub fn parse(expression: &str, timezone: Tz) -> Result<Self, ScheduleError> {
+        let expanded = match expression.trim() {
+            "@hourly" => "0 * * * *",
+            "@daily" | "@midnight" => "0 0 * * *",
+            "@weekly" => "0 0 * * 0",
+            "@monthly" => "0 0 1 * *",
+            "@yearly" | "@annually" => "0 0 1 1 *",
+            other if other.starts_with('@') => {
+                return Err(ScheduleError(format!("unknown schedule preset '{}'", other)))
+            }
+            other => other,
+        };
+
+        let fields: Vec<&str> = expanded.split_whitespace().collect();
+        if fields.len() != 5 {
+            return Err(ScheduleError(format!(
+                "expected 5 cron fields but found {} in '{}'",
+                fields.len(),
+                expression
+            )));
+        }
+
+        let days_of_week = parse_field(fields[4], 0, 7, &WEEKDAY_NAMES, "day of week")?;
+        // Both 0 and 7 mean Sunday
+        let days_of_week = if days_of_week & (1 << 7) != 0 {
+            (days_of_week | 1) & !(1 << 7)
+        } else {
+            days_of_week
+        };
+
+        Ok(Self {
+            minutes: parse_field(fields[0], 0, 59, &[], "minute")?,
+            hours: parse_field(fields[1], 0, 23, &[], "hour")?,
+            days_of_month: parse_field(fields[2], 1, 31, &[], "day of month")?,
+            months: parse_field(fields[3], 1, 12, &MONTH_NAMES, "month")?,
+            days_of_week,
+            dom_restricted: !fields[2].starts_with('*'),
+            dow_restricted: !fields[4].starts_with('*'),
+            timezone,
+        })
+    }
+
+    /// First fire time strictly after `after`
+    pub fn next_after(&self, after: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        let start = after.with_timezone(&self.timezone).naive_local();
+        let mut candidate = start.with_second(0)?.with_nanosecond(0)? + Duration::minutes(1);
+        let limit = start + Duration::days(MAX_SEARCH_DAYS);
+
+        while candidate <= limit {
+            if !bit(self.months, candidate.month()) {
+                candidate = first_of_next_month(candidate)?;
+                continue;
+            }
+            if !self.day_matches(candidate.date()) {
+                candidate = candidate.date().succ_opt()?.and_hms_opt(0, 0, 0)?;
+                continue;
+            }
+            if !bit(self.hours, candidate.hour()) {
+                candidate = candidate.with_minute(0)? + Duration::hours(1);
+                continue;
+            }
+            if !bit(self.minutes, candidate.minute()) {
+                candidate += Duration::minutes(1);
+                continue;
+            }
+
+            // Local times skipped by a DST change never fire; ambiguous ones fire once
+            if let Some(local) = self.timezone.from_local_datetime(&candidate).earliest() {
+                let utc = local.with_timezone(&Utc);
+                if utc > after {
+                    return Some(utc);
+                }
+            }
+            candidate += Duration::minutes(1);
+        }
+
+        None
+    }
+
+    /// The next `count` fire times after `after`
+    pub fn upcoming(&self, after: DateTime<Utc>, count: usize) -> Vec<DateTime<Utc>> {
+        let mut times = Vec::with_capacity(count);
+        let mut cursor = after;
+        while times.len() < count {
+            match self.next_after(cursor) {
+                Some(next) => {
+                    times.push(next);
+                    cursor = next;
+                }
+                None => break,
+            }
+        }
+        times
+    }
+
+    fn day_matches(&self, date: NaiveDate) -> bool {
+        let dom = bit(self.days_of_month, date.day());
+        let dow = bit(self.days_of_week, date.weekday().num_days_from_sunday());
+        match (self.dom_restricted, self.dow_restricted) {
+            (true, true) => dom || dow,
+            _ => dom && dow,
+        }
+    }
+}
+
+/// Parse an IANA timezone name such as `Europe/London`
+pub fn parse_timezone(name: &str) -> Result<Tz, ScheduleError> {
+    name.parse::<Tz>()
+        .map_err(|_| ScheduleError(format!("unknown timezone '{}'", name)))
+}
+
+/// Parse the schedule of a DAG, checking both its expression and its timezone
+pub fn dag_schedule(dag: &DAG) -> Result<Schedule, ScheduleError> {
+    Schedule::parse(&dag.schedule_interval, parse_timezone(&dag.timezone)?)
+}
+
+/// The `next_run` a DAG should advertise: `None` while it is paused
+pub fn next_run_for(dag: &DAG, after: DateTime<Utc>) -> Result<Option<DateTime<Utc>>, ScheduleError> {
+    let schedule = dag_schedule(dag)?;
+    if dag.paused {
+        return Ok(None);
+    }
+    Ok(schedule.next_after(after))
+}
+
+const WEEKDAY_NAMES: [&str; 7] = ["sun", "mon", "tue", "wed", "thu", "fri", "sat"];
+const MONTH_NAMES: [&str; 12] = [
+    "jan", "feb", "mar", "apr", "may", "jun", "jul", "aug", "sep", "oct", "nov", "dec",
+];
+
+fn bit(mask: u64, value: u32) -> bool {
+    mask & (1 << value) != 0
+}
+
+fn first_of_next_month(dt: NaiveDateTime) -> Option<NaiveDateTime> {
+    let (year, month) = if dt.month() == 12 {
+        (dt.year() + 1, 1)
+    } else {
+        (dt.year(), dt.month() + 1)
+    };
+    NaiveDate::from_ymd_opt(year, month, 1)?.and_hms_opt(0, 0, 0)
+}
+
+/// Parse one cron field into a bitmask of allowed values. `names` maps symbolic values
+/// (`mon`, `jan`) onto numbers starting at `min`.
+fn parse_field(field: &str, min: u32, max: u32, names: &[&str], label: &str) -> Result<u64, ScheduleError> {
+    let invalid = |reason: &str| ScheduleError(format!("invalid {} field '{}': {}", label, field, reason));
+
+    let value = |token: &str| -> Result<u32, ScheduleError> {
+        let lower = token.to_ascii_lowercase();
+        if let Some(pos) = names.iter().position(|name| *name == lower) {
+            return Ok(min + pos as u32);
+        }
+        let number: u32 = token.parse().map_err(|_| invalid(&format!("'{}' is not a number", token)))?;
+        if number < min || number > max {
+            return Err(invalid(&format!("{} is outside {}-{}", number, min, max)));
+        }
+        Ok(number)
+    };
+
+    let mut mask = 0u64;
+    for part in field.split(',') {
+        let (range, step) = match part.split_once('/') {
+            Some((range, step)) => {
+                let step: u32 = step.parse().map_err(|_| invalid("step must be a number"))?;
+                if step == 0 {
+                    return Err(invalid("step must be greater than zero"));
+                }
+                (range, step)
+            }
+            None => (part, 1),
+        };
+
+        let (start, end) = if range == "*" {
+            (min, max)
+        } else if let Some((start, end)) = range.split_once('-') {
+            (value(start)?, value(end)?)
+        } else {
+            let start = value(range)?;
+            // `5/15` means "from 5 to the end in steps of 15"
+            (start, if part.contains('/') { max } else { start })
+        };
+
+        if start > end {
+            return Err(invalid(&format!("range {}-{} is reversed", start, end)));
+        }
+        for v in (start..=end).step_by(step as usize) {
+            mask |= 1 << v;
+        }
+    }
+
+    Ok(mask)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn utc(s: &str) -> DateTime<Utc> {
+        s.parse().unwrap()
+    }
+
+    fn upcoming(expression: &str, timezone: &str, after: &str, count: usize) -> Vec<DateTime<Utc>> {
+        Schedule::parse(expression, parse_timezone(timezone).unwrap())
+            .unwrap()
+            .upcoming(utc(after), count)
+    }
+
+    #[test]
+    fn day_of_month_and_day_of_week_are_ored_when_both_restricted() {
+        // 1 March 2026 is a Sunday
+        assert_eq!(
+            upcoming("0 0 1 * 1", "UTC", "2026-02-28T12:00:00Z", 4),
+            vec![
+                utc("2026-03-01T00:00:00Z"),
+                utc("2026-03-02T00:00:00Z"),
+                utc("2026-03-09T00:00:00Z"),
+                utc("2026-03-16T00:00:00Z"),
+            ]
+        );
+    }
+
+    #[test]
+    fn an_unrestricted_day_field_does_not_widen_the_other() {
+        assert_eq!(
+            upcoming("0 0 1 * *", "UTC", "2026-02-28T12:00:00Z", 2),
+            vec![utc("2026-03-01T00:00:00Z"), utc("2026-04-01T00:00:00Z")]
+        );
+        assert_eq!(
+            upcoming("0 0 * * mon", "UTC", "2026-02-28T12:00:00Z", 2),
+            vec![utc("2026-03-02T00:00:00Z"), utc("2026-03-09T00:00:00Z")]
+        );
+    }
+
+    #[test]
+    fn seven_means_sunday() {
+        assert_eq!(
+            upcoming("0 0 * * 7", "UTC", "2026-02-28T12:00:00Z", 1),
+            vec![utc("2026-03-01T00:00:00Z")]
+        );
+    }
+
+    #[test]
+    fn times_in_a_dst_gap_are_skipped() {
+        // London skips from 01:00 to 02:00 on 29 March 2026
+        assert_eq!(
+            upcoming("30 1 * * *", "Europe/London", "2026-03-28T12:00:00Z", 2),
+            vec![utc("2026-03-30T00:30:00Z"), utc("2026-03-31T00:30:00Z")]
+        );
+    }
+
+    #[test]
+    fn times_in_a_dst_overlap_fire_once() {
+        // London repeats 01:00 to 02:00 on 25 October 2026; the first (BST) 01:30 fires
+        assert_eq!(
+            upcoming("30 1 * * *", "Europe/London", "2026-10-24T12:00:00Z", 2),
+            vec![utc("2026-10-25T00:30:00Z"), utc("2026-10-26T01:30:00Z")]
+        );
+    }
+
+    #[test]
+    fn an_impossible_date_never_fires() {
+        assert!(upcoming("0 0 30 2 *", "UTC", "2026-01-01T00:00:00Z", 1).is_empty());
+    }
+
+    #[test]
+    fn invalid_expressions_are_rejected() {
+        for expression in ["* * * *", "@fortnightly", "60 * * * *", "0 0 * * fri-", "0 0 0 * *"] {
+            assert!(Schedule::parse(expression, Tz::UTC).is_err(), "{} parsed", expression);
+        }
+    }
+}
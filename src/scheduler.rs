@@ -0,0 +1,804 @@
+use std::collections::{HashMap, HashSet};
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use serde_json::Map;
+use tokio::task::JoinHandle;
+
+use crate::executor::{Executor, OnStart, TaskOutcome, TaskRequest};
+use crate::models::{
+    Backoff, ClearPayload, DAGGraph, DagRun, RetryPolicy, RunState, RunType, TaskAttempt, TaskInstance, TaskStatus,
+};
+use crate::registry::RegistryError;
+use crate::schedule;
+use crate::state::AppState;
+
+/// What to do when a DAG's schedule fires while an earlier run of it is still active
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OverlapPolicy {
+    /// Drop the new run
+    Skip,
+    /// Create the new run but hold it until the active one finishes
+    Queue,
+    /// Start the new run straight away
+    Allow,
+}
+
+impl FromStr for OverlapPolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "skip" => Ok(OverlapPolicy::Skip),
+            "queue" => Ok(OverlapPolicy::Queue),
+            "allow" => Ok(OverlapPolicy::Allow),
+            other => Err(format!("unknown overlap policy '{}', expected skip, queue or allow", other)),
+        }
+    }
+}
+
+/// Creates DAG runs when schedules fire, hands ready tasks to the executor and moves runs along
+/// as their tasks finish
+pub struct Scheduler {
+    state: AppState,
+    overlap: OverlapPolicy,
+    executor: Arc<dyn Executor>,
+    /// Upper bound on how long the loop sleeps, so new DAGs and task progress are noticed
+    max_sleep: Duration,
+}
+
+impl Scheduler {
+    pub fn new(state: AppState, overlap: OverlapPolicy, executor: Arc<dyn Executor>) -> Self {
+        Self {
+            state,
+            overlap,
+            executor,
+            max_sleep: Duration::from_secs(1),
+        }
+    }
+
+    pub fn spawn(self) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            tracing::info!("Scheduler started with overlap policy {:?}", self.overlap);
+            if let Err(err) = self.fail_orphaned_tasks(Utc::now()) {
+                tracing::error!("Failed to recover tasks from a previous process: {}", err);
+            }
+            loop {
+                let next_fire = match self.tick(Utc::now()) {
+                    Ok(next_fire) => next_fire,
+                    Err(err) => {
+                        tracing::error!("Scheduler tick failed: {}", err);
+                        None
+                    }
+                };
+                let sleep = next_fire
+                    .and_then(|at| (at - Utc::now()).to_std().ok())
+                    .unwrap_or(self.max_sleep)
+                    .min(self.max_sleep);
+                tokio::time::sleep(sleep).await;
+            }
+        })
+    }
+
+    /// Fire every due DAG, then advance active runs. Returns the earliest upcoming fire time.
+    pub fn tick(&self, now: DateTime<Utc>) -> Result<Option<DateTime<Utc>>, RegistryError> {
+        // Backfill runs have their own limit and do not hold up scheduled runs
+        let active_dags: HashSet<String> = self
+            .state
+            .runs
+            .active_runs()?
+            .into_iter()
+            .filter(|run| run.backfill.is_none())
+            .map(|run| run.dag_id)
+            .collect();
+
+        let mut earliest: Option<DateTime<Utc>> = None;
+        for dag in self.state.registry.list()? {
+            if dag.paused {
+                continue;
+            }
+            let Some(due) = dag.next_run else {
+                continue;
+            };
+
+            let next_run = if due <= now {
+                self.fire(&dag.dag_id, due, now, active_dags.contains(&dag.dag_id))?
+            } else {
+                Some(due)
+            };
+            if let Some(next_run) = next_run {
+                earliest = Some(earliest.map_or(next_run, |e| e.min(next_run)));
+            }
+        }
+
+        self.advance_runs(now)?;
+        Ok(earliest)
+    }
+
+    /// Handle a schedule slot that has come due and move the DAG on to its next slot
+    fn fire(
+        &self,
+        dag_id: &str,
+        logical_date: DateTime<Utc>,
+        now: DateTime<Utc>,
+        has_active_run: bool,
+    ) -> Result<Option<DateTime<Utc>>, RegistryError> {
+        // Re-read, since the DAG may have been paused or deleted since the listing
+        if self.state.registry.get(dag_id)?.is_none_or(|dag| dag.paused) {
+            return Ok(None);
+        }
+
+        let start = match (has_active_run, self.overlap) {
+            (true, OverlapPolicy::Skip) => {
+                tracing::info!("Skipping run of {} for {}: previous run still active", dag_id, logical_date);
+                None
+            }
+            (true, OverlapPolicy::Queue) => Some(false),
+            _ => Some(true),
+        };
+
+        let mut created = false;
+        if let Some(start) = start {
+            match self.state.registry.get_graph(dag_id)? {
+                Some(graph) => {
+                    let run_id = format!("scheduled__{}", logical_date.to_rfc3339());
+                    let mut run = new_run(&graph, run_id, RunType::SCHEDULED, logical_date, now);
+                    if start {
+                        start_run(&mut run, &graph, now);
+                    }
+                    match self.state.runs.create_run(run) {
+                        Ok(run) => {
+                            tracing::info!("Created run {} of {} ({:?})", run.run_id, dag_id, run.state);
+                            created = true;
+                        }
+                        Err(RegistryError::RunAlreadyExists { run_id, .. }) => {
+                            tracing::debug!("Run {} of {} already exists", run_id, dag_id);
+                        }
+                        Err(err) => return Err(err),
+                    }
+                }
+                None => tracing::warn!("DAG {} has no graph; not creating a run", dag_id),
+            }
+        }
+
+        // Only the schedule fields are written back, from the DAG as it is now, so a pause or
+        // edit that lands meanwhile is kept
+        let mut next_run = None;
+        let result = self.state.registry.modify(dag_id, &mut |dag| {
+            if created {
+                dag.last_run = Some(logical_date);
+            }
+            // Slots missed while the server was down collapse into this one run
+            next_run = match schedule::next_run_for(dag, now) {
+                Ok(next_run) => next_run,
+                Err(err) => {
+                    tracing::warn!("DAG {} has an invalid schedule: {}", dag.dag_id, err);
+                    None
+                }
+            };
+            dag.next_run = next_run;
+        });
+        match result {
+            Ok(_) => Ok(next_run),
+            Err(RegistryError::NotFound(_)) => Ok(None),
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Queue tasks whose upstreams are done, dispatch queued tasks, close finished runs and
+    /// start held-back ones
+    fn advance_runs(&self, now: DateTime<Utc>) -> Result<(), RegistryError> {
+        let mut graphs: HashMap<(String, u32), Option<DAGGraph>> = HashMap::new();
+        let mut running_dags: HashSet<String> = HashSet::new();
+        let mut running_backfills: HashMap<String, usize> = HashMap::new();
+        let mut queued: Vec<DagRun> = Vec::new();
+
+        for run in self.state.runs.active_runs()? {
+            if run.state == RunState::QUEUED {
+                queued.push(run);
+                continue;
+            }
+            let Some(graph) = self.graph_for(&mut graphs, &run)? else {
+                continue;
+            };
+
+            let mut claimed = Vec::new();
+            let run = self.state.runs.modify_run(&run.dag_id, &run.run_id, &mut |run| {
+                claimed.clear();
+                if run.state != RunState::RUNNING {
+                    return;
+                }
+                requeue_due_retries(run, now);
+                queue_ready_tasks(run, &graph);
+                claimed = claim_queued_tasks(run);
+                if let Some(outcome) = run_outcome(run, &graph) {
+                    run.state = outcome;
+                    run.end_date = Some(now);
+                }
+            })?;
+
+            if run.state.is_active() {
+                match &run.backfill {
+                    Some(info) => *running_backfills.entry(info.backfill_id.clone()).or_default() += 1,
+                    None => {
+                        running_dags.insert(run.dag_id.clone());
+                    }
+                }
+            } else {
+                tracing::info!("Run {} of {} finished: {:?}", run.run_id, run.dag_id, run.state);
+            }
+            for (task_id, try_number) in claimed {
+                self.dispatch(&run, &graph, task_id, try_number);
+            }
+        }
+
+        // Oldest first, so each DAG starts the run that has waited longest
+        for run in queued {
+            let blocked = match &run.backfill {
+                Some(info) => running_backfills.get(&info.backfill_id).copied().unwrap_or(0) >= info.max_active_runs,
+                None => self.overlap != OverlapPolicy::Allow && running_dags.contains(&run.dag_id),
+            };
+            if blocked {
+                continue;
+            }
+            let Some(graph) = self.graph_for(&mut graphs, &run)? else {
+                continue;
+            };
+            self.state.runs.modify_run(&run.dag_id, &run.run_id, &mut |run| {
+                if run.state == RunState::QUEUED {
+                    start_run(run, &graph, now);
+                }
+            })?;
+            match &run.backfill {
+                Some(info) => *running_backfills.entry(info.backfill_id.clone()).or_default() += 1,
+                None => {
+                    running_dags.insert(run.dag_id.clone());
+                }
+            }
+            tracing::info!("Started queued run {} of {}", run.run_id, run.dag_id);
+        }
+
+        Ok(())
+    }
+
+    /// The graph version a run was created from, cached for the duration of a tick
+    fn graph_for(
+        &self,
+        graphs: &mut HashMap<(String, u32), Option<DAGGraph>>,
+        run: &DagRun,
+    ) -> Result<Option<DAGGraph>, RegistryError> {
+        let key = (run.dag_id.clone(), run.graph_version);
+        if !graphs.contains_key(&key) {
+            let graph = self
+                .state
+                .registry
+                .get_graph_version(&run.dag_id, run.graph_version)?;
+            graphs.insert(key.clone(), graph);
+        }
+        Ok(graphs[&key].clone())
+    }
+
+    /// Hand a claimed task attempt to the executor and record its outcome when it finishes. A
+    /// failed attempt with retries left is scheduled for another try.
+    fn dispatch(&self, run: &DagRun, graph: &DAGGraph, task_id: String, try_number: usize) {
+        let Some(task) = graph.tasks.iter().find(|task| task.id == task_id) else {
+            return;
+        };
+        let request = TaskRequest {
+            dag_id: run.dag_id.clone(),
+            run_id: run.run_id.clone(),
+            task_id: task_id.clone(),
+            try_number,
+            operator: task.operator.clone(),
+            command: task.command.clone(),
+            conf: run.conf.clone(),
+            timeout: task.timeout_secs.map(Duration::from_secs),
+            log_path: self.state.logs.path(&run.dag_id, &run.run_id, &task_id, try_number),
+        };
+
+        let max_retries = task.max_retries;
+        let cleared_tries = run
+            .task_instances
+            .iter()
+            .find(|ti| ti.task_id == task_id)
+            .map_or(0, |ti| ti.cleared_tries);
+        let retry = task.retry.clone();
+        let executor = self.executor.clone();
+        let runs = self.state.runs.clone();
+        let on_start: OnStart = {
+            let runs = runs.clone();
+            let request = request.clone();
+            Box::new(move |started_at| {
+                tracing::info!(
+                    "Running task {} of {} / {} (try {})",
+                    request.task_id,
+                    request.dag_id,
+                    request.run_id,
+                    request.try_number
+                );
+                let result = runs.modify_run(&request.dag_id, &request.run_id, &mut |run| {
+                    if let Some(ti) = running_attempt(run, &request) {
+                        ti.start_time = Some(started_at);
+                    }
+                });
+                if let Err(err) = result {
+                    tracing::error!("Failed to record start of task {}: {}", request.task_id, err);
+                }
+            })
+        };
+        tokio::spawn(async move {
+            let outcome = executor.execute(request.clone(), on_start).await;
+
+            match &outcome.error {
+                Some(error) => tracing::warn!(
+                    "Task {} of {} / {} failed: {}",
+                    request.task_id,
+                    request.dag_id,
+                    request.run_id,
+                    error
+                ),
+                None => tracing::info!(
+                    "Task {} of {} / {} finished: {:?} (exit code {:?})",
+                    request.task_id,
+                    request.dag_id,
+                    request.run_id,
+                    outcome.status,
+                    outcome.exit_code
+                ),
+            }
+
+            let ended = Utc::now();
+            // A cleared task gets its full set of retries again
+            let attempt = request.try_number.saturating_sub(cleared_tries);
+            let retry_at = (outcome.status == TaskStatus::FAILED && attempt <= max_retries)
+                .then(|| ended + retry_delay(&retry, attempt, &mut rand::thread_rng()));
+            if let Some(retry_at) = retry_at {
+                tracing::info!(
+                    "Retrying task {} of {} / {} at {} ({} of {} retries)",
+                    request.task_id,
+                    request.dag_id,
+                    request.run_id,
+                    retry_at,
+                    attempt,
+                    max_retries
+                );
+            }
+
+            let result = runs.modify_run(&request.dag_id, &request.run_id, &mut |run| {
+                record_outcome(run, &request, &outcome, ended, retry_at);
+            });
+            if let Err(err) = result {
+                tracing::error!("Failed to record outcome of task {}: {}", request.task_id, err);
+            }
+        });
+    }
+
+    /// Tasks left RUNNING by a previous process have lost their executor; fail them so the run
+    /// can finish instead of waiting forever
+    fn fail_orphaned_tasks(&self, now: DateTime<Utc>) -> Result<(), RegistryError> {
+        for run in self.state.runs.active_runs()? {
+            if !run
+                .task_instances
+                .iter()
+                .any(|ti| ti.status == TaskStatus::RUNNING)
+            {
+                continue;
+            }
+            self.state.runs.modify_run(&run.dag_id, &run.run_id, &mut |run| {
+                for ti in run.task_instances.iter_mut() {
+                    if ti.status == TaskStatus::RUNNING {
+                        tracing::warn!("Task {} of {} / {} was orphaned", ti.task_id, run.dag_id, run.run_id);
+                        let duration = ti.start_time.map(|start| (now - start).num_milliseconds() as f64 / 1000.0);
+                        finish_attempt(
+                            ti,
+                            TaskStatus::FAILED,
+                            None,
+                            Some("lost when the server restarted".to_string()),
+                            now,
+                            duration,
+                        );
+                    }
+                }
+            })?;
+        }
+        Ok(())
+    }
+}
+
+/// A run with every task PENDING, not yet started
+pub fn new_run(
+    graph: &DAGGraph,
+    run_id: String,
+    run_type: RunType,
+    logical_date: DateTime<Utc>,
+    now: DateTime<Utc>,
+) -> DagRun {
+    DagRun {
+        run_id,
+        dag_id: graph.dag_id.clone(),
+        logical_date,
+        run_type,
+        state: RunState::QUEUED,
+        graph_version: graph.version,
+        queued_at: now,
+        start_date: None,
+        end_date: None,
+        conf: Map::new(),
+        backfill: None,
+        triggered_by: None,
+        task_instances: graph
+            .tasks
+            .iter()
+            .map(|task| TaskInstance {
+                task_id: task.id.clone(),
+                status: TaskStatus::PENDING,
+                try_number: 0,
+                start_time: None,
+                end_time: None,
+                duration: None,
+                exit_code: None,
+                next_retry_at: None,
+                attempts: Vec::new(),
+                cleared_tries: 0,
+            })
+            .collect(),
+    }
+}
+
+/// Mark a run as running and queue its root tasks
+pub fn start_run(run: &mut DagRun, graph: &DAGGraph, now: DateTime<Utc>) {
+    run.state = RunState::RUNNING;
+    run.start_date = Some(now);
+    queue_ready_tasks(run, graph);
+}
+
+/// Upstream task ids of every task, from the graph's edge list
+pub fn upstream_map(graph: &DAGGraph) -> HashMap<&str, Vec<&str>> {
+    let mut upstream: HashMap<&str, Vec<&str>> = HashMap::new();
+    for edge in &graph.edges {
+        upstream
+            .entry(edge.target.as_str())
+            .or_default()
+            .push(edge.source.as_str());
+    }
+    upstream
+}
+
+fn is_done(status: &TaskStatus) -> bool {
+    matches!(status, TaskStatus::SUCCEEDED | TaskStatus::SKIPPED)
+}
+
+/// Move PENDING tasks whose upstream tasks have all succeeded or been skipped to QUEUED.
+/// Returns the number of tasks queued.
+pub fn queue_ready_tasks(run: &mut DagRun, graph: &DAGGraph) -> usize {
+    let upstream = upstream_map(graph);
+    let statuses: HashMap<String, TaskStatus> = run
+        .task_instances
+        .iter()
+        .map(|ti| (ti.task_id.clone(), ti.status.clone()))
+        .collect();
+
+    let mut queued = 0;
+    for ti in run.task_instances.iter_mut() {
+        if ti.status != TaskStatus::PENDING {
+            continue;
+        }
+        let ready = upstream
+            .get(ti.task_id.as_str())
+            .into_iter()
+            .flatten()
+            .all(|up| statuses.get(*up).is_some_and(is_done));
+        if ready {
+            ti.status = TaskStatus::QUEUED;
+            queued += 1;
+        }
+    }
+    queued
+}
+
+/// Move QUEUED tasks to RUNNING as a new attempt. Returns the claimed task ids with their
+/// try numbers. The start time is left empty until the executor actually starts the attempt.
+pub fn claim_queued_tasks(run: &mut DagRun) -> Vec<(String, usize)> {
+    let mut claimed = Vec::new();
+    for ti in run.task_instances.iter_mut() {
+        if ti.status != TaskStatus::QUEUED {
+            continue;
+        }
+        ti.status = TaskStatus::RUNNING;
+        ti.try_number += 1;
+        ti.start_time = None;
+        ti.end_time = None;
+        ti.duration = None;
+        ti.exit_code = None;
+        claimed.push((ti.task_id.clone(), ti.try_number));
+    }
+    claimed
+}
+
+/// The task instance still running the attempt `request` describes, unless the task has since
+/// moved on (for example because it was cleared and started again)
+fn running_attempt<'a>(run: &'a mut DagRun, request: &TaskRequest) -> Option<&'a mut TaskInstance> {
+    run.task_instances.iter_mut().find(|ti| {
+        ti.task_id == request.task_id && ti.try_number == request.try_number && ti.status == TaskStatus::RUNNING
+    })
+}
+
+/// Store the outcome of an attempt, unless the task has since moved on. With `retry_at` set, a
+/// failure leaves the task UP_FOR_RETRY. The duration counts from when the executor started
+/// the attempt, not from when it was claimed.
+fn record_outcome(
+    run: &mut DagRun,
+    request: &TaskRequest,
+    outcome: &TaskOutcome,
+    ended: DateTime<Utc>,
+    retry_at: Option<DateTime<Utc>>,
+) {
+    let Some(ti) = running_attempt(run, request) else {
+        return;
+    };
+    if outcome.started_at.is_some() {
+        ti.start_time = outcome.started_at;
+    }
+    let duration = ti.start_time.map(|start| (ended - start).num_milliseconds() as f64 / 1000.0);
+    let status = match retry_at {
+        Some(_) => TaskStatus::UP_FOR_RETRY,
+        None => outcome.status.clone(),
+    };
+    finish_attempt(ti, status, outcome.exit_code, outcome.error.clone(), ended, duration);
+    ti.next_retry_at = retry_at;
+}
+
+/// Close the running attempt of a task instance and append it to the attempt history
+fn finish_attempt(
+    ti: &mut TaskInstance,
+    status: TaskStatus,
+    exit_code: Option<i32>,
+    error: Option<String>,
+    ended: DateTime<Utc>,
+    duration: Option<f64>,
+) {
+    ti.end_time = Some(ended);
+    ti.duration = duration;
+    ti.exit_code = exit_code;
+    ti.attempts.push(TaskAttempt {
+        try_number: ti.try_number,
+        // The attempt itself failed even if the task will be retried
+        status: match status {
+            TaskStatus::UP_FOR_RETRY => TaskStatus::FAILED,
+            ref other => other.clone(),
+        },
+        start_time: ti.start_time,
+        end_time: ti.end_time,
+        duration,
+        exit_code,
+        error,
+    });
+    ti.status = status;
+}
+
+/// Move a task instance, and optionally everything upstream or downstream of it, to `target`
+/// by hand. The task itself must be allowed to make the transition; related tasks already in
+/// `target` are left alone and any other illegal transition rejects the whole change. Returns
+/// the ids of the changed tasks.
+pub fn set_task_status(
+    run: &mut DagRun,
+    graph: &DAGGraph,
+    task_id: &str,
+    target: &TaskStatus,
+    upstream: bool,
+    downstream: bool,
+    now: DateTime<Utc>,
+) -> Result<Vec<String>, String> {
+    let mut related = Vec::new();
+    if upstream {
+        related.extend(graph.upstream_of(task_id));
+    }
+    if downstream {
+        related.extend(graph.downstream_of(task_id));
+    }
+
+    let status_of = |id: &str| {
+        run.task_instances
+            .iter()
+            .find(|ti| ti.task_id == id)
+            .map(|ti| ti.status.clone())
+    };
+    let Some(current) = status_of(task_id) else {
+        return Err(format!("task '{}' is not part of run '{}'", task_id, run.run_id));
+    };
+    current
+        .check_transition(target)
+        .map_err(|reason| format!("task '{}': {}", task_id, reason))?;
+
+    let mut changed = vec![task_id.to_string()];
+    for id in related {
+        match status_of(&id) {
+            Some(status) if &status == target => {}
+            Some(status) => {
+                status
+                    .check_transition(target)
+                    .map_err(|reason| format!("task '{}': {}", id, reason))?;
+                changed.push(id);
+            }
+            None => {}
+        }
+    }
+
+    for ti in run.task_instances.iter_mut() {
+        if !changed.contains(&ti.task_id) {
+            continue;
+        }
+        ti.status = target.clone();
+        ti.next_retry_at = None;
+        if matches!(target, TaskStatus::SUCCEEDED | TaskStatus::FAILED | TaskStatus::SKIPPED) {
+            ti.end_time.get_or_insert(now);
+        }
+    }
+    settle_run_state(run, graph, now);
+    Ok(changed)
+}
+
+/// Which tasks clearing `task_id` resets: the task itself first, then its upstream and/or
+/// downstream tasks, direct or transitive, optionally only those that failed
+pub fn tasks_to_clear(run: &DagRun, graph: &DAGGraph, task_id: &str, scope: &ClearPayload) -> Vec<String> {
+    let mut related = Vec::new();
+    if scope.upstream {
+        related.extend(match scope.recursive {
+            true => graph.upstream_of(task_id),
+            false => graph
+                .edges
+                .iter()
+                .filter(|edge| edge.target == task_id)
+                .map(|edge| edge.source.clone())
+                .collect(),
+        });
+    }
+    if scope.downstream {
+        related.extend(match scope.recursive {
+            true => graph.downstream_of(task_id),
+            false => graph
+                .edges
+                .iter()
+                .filter(|edge| edge.source == task_id)
+                .map(|edge| edge.target.clone())
+                .collect(),
+        });
+    }
+
+    let mut cleared = vec![task_id.to_string()];
+    for id in related {
+        let Some(ti) = run.task_instances.iter().find(|ti| ti.task_id == id) else {
+            continue;
+        };
+        if scope.failed_only && !matches!(ti.status, TaskStatus::FAILED | TaskStatus::UP_FOR_RETRY) {
+            continue;
+        }
+        if !cleared.contains(&id) {
+            cleared.push(id);
+        }
+    }
+    cleared
+}
+
+/// Reset the given tasks to PENDING so the scheduler runs them again, keeping their attempt
+/// history. An attempt still running is left to finish; its outcome is ignored.
+pub fn clear_tasks(run: &mut DagRun, graph: &DAGGraph, task_ids: &[String], now: DateTime<Utc>) {
+    for ti in run.task_instances.iter_mut() {
+        if !task_ids.contains(&ti.task_id) {
+            continue;
+        }
+        ti.status = TaskStatus::PENDING;
+        ti.cleared_tries = ti.try_number;
+        ti.start_time = None;
+        ti.end_time = None;
+        ti.duration = None;
+        ti.exit_code = None;
+        ti.next_retry_at = None;
+    }
+    settle_run_state(run, graph, now);
+}
+
+/// Bring a finished run in line with task states changed by hand: it runs again if tasks can
+/// make progress, otherwise its final state is recomputed. Active runs are left to the
+/// scheduler loop.
+pub fn settle_run_state(run: &mut DagRun, graph: &DAGGraph, now: DateTime<Utc>) {
+    if run.state.is_active() {
+        return;
+    }
+    match run_outcome(run, graph) {
+        Some(outcome) => run.state = outcome,
+        None => {
+            run.state = RunState::RUNNING;
+            run.start_date.get_or_insert(now);
+            run.end_date = None;
+        }
+    }
+}
+
+/// Queue UP_FOR_RETRY tasks whose retry delay has passed
+pub fn requeue_due_retries(run: &mut DagRun, now: DateTime<Utc>) -> usize {
+    let mut requeued = 0;
+    for ti in run.task_instances.iter_mut() {
+        if ti.status == TaskStatus::UP_FOR_RETRY && ti.next_retry_at.is_none_or(|at| at <= now) {
+            ti.status = TaskStatus::QUEUED;
+            ti.next_retry_at = None;
+            requeued += 1;
+        }
+    }
+    requeued
+}
+
+/// How long to wait before retrying a task whose attempt `try_number` just failed
+pub fn retry_delay(policy: &RetryPolicy, try_number: usize, rng: &mut impl Rng) -> chrono::Duration {
+    let delay = match policy.backoff {
+        Backoff::Fixed => policy.delay_secs,
+        Backoff::Exponential => {
+            let doublings = try_number.saturating_sub(1).min(32) as u32;
+            policy.delay_secs.saturating_mul(1 << doublings)
+        }
+    }
+    .min(policy.max_delay_secs) as f64;
+
+    let jitter = policy.jitter.clamp(0.0, 1.0);
+    let delay = delay * (1.0 - jitter * rng.gen::<f64>());
+    chrono::Duration::milliseconds((delay * 1000.0) as i64)
+}
+
+/// The final state of a run once no task can make further progress, or `None` while it can
+pub fn run_outcome(run: &DagRun, graph: &DAGGraph) -> Option<RunState> {
+    if run.task_instances.iter().any(|ti| {
+        matches!(
+            ti.status,
+            TaskStatus::QUEUED | TaskStatus::RUNNING | TaskStatus::PAUSED | TaskStatus::UP_FOR_RETRY
+        )
+    }) {
+        return None;
+    }
+
+    // Pending tasks downstream of a failure can never become ready
+    let upstream = upstream_map(graph);
+    let mut blocked: HashSet<&str> = run
+        .task_instances
+        .iter()
+        .filter(|ti| ti.status == TaskStatus::FAILED)
+        .map(|ti| ti.task_id.as_str())
+        .collect();
+    loop {
+        let newly_blocked: Vec<&str> = run
+            .task_instances
+            .iter()
+            .filter(|ti| ti.status == TaskStatus::PENDING && !blocked.contains(ti.task_id.as_str()))
+            .filter(|ti| {
+                upstream
+                    .get(ti.task_id.as_str())
+                    .into_iter()
+                    .flatten()
+                    .any(|up| blocked.contains(up))
+            })
+            .map(|ti| ti.task_id.as_str())
+            .collect();
+        if newly_blocked.is_empty() {
+            break;
+        }
+        blocked.extend(newly_blocked);
+    }
+
+    let can_progress = run
+        .task_instances
+        .iter()
+        .any(|ti| ti.status == TaskStatus::PENDING && !blocked.contains(ti.task_id.as_str()));
+    if can_progress {
+        return None;
+    }
+
+    if run.task_instances.iter().all(|ti| is_done(&ti.status)) {
+        Some(RunState::SUCCEEDED)
+    } else {
+        Some(RunState::FAILED)
+    }
+}
@@ -0,0 +1,580 @@
+//! The DAG search language, e.g. `owner:data_engineer tag:production status:failed "sales"`.
+//!
+//! Terms are ANDed; `OR` joins alternatives and binds looser than the implicit AND; parentheses
+//! group; `-` or `NOT` negates a term or group. A bare word matches DAG id, owner and description
+//! as a substring, or the whole DAG id when it has `*` or `?` wildcards. Fields:
+//!
+//! - `id:`, `owner:`, `tag:` compare the whole value, case-insensitively, with wildcards
+//! - `schedule:` compares the whole schedule interval, case-insensitively, so cron `*` is literal
+//! - `status:` is one of active, paused, success, failed or running
+
+use std::fmt;
+use std::str::FromStr;
+
+use serde::Serialize;
+
+use crate::models::DAG;
+
+pub const FIELDS: [&str; 5] = ["id", "owner", "tag", "status", "schedule"];
+
+/// Why a search query could not be parsed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum QueryErrorKind {
+    UnterminatedQuote,
+    UnbalancedParenthesis,
+    UnknownField,
+    UnknownStatus,
+    EmptyValue,
+    /// `OR`, `NOT` or `-` with nothing to apply to, or `()`
+    MissingTerm,
+}
+
+/// A malformed search query, with the span of the offending text in characters
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct QueryError {
+    pub kind: QueryErrorKind,
+    pub message: String,
+    pub position: usize,
+    pub length: usize,
+}
+
+impl QueryError {
+    fn new(kind: QueryErrorKind, position: usize, length: usize, message: impl Into<String>) -> Self {
+        QueryError {
+            kind,
+            message: message.into(),
+            position,
+            length: length.max(1),
+        }
+    }
+}
+
+impl fmt::Display for QueryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid search at character {}: {}", self.position + 1, self.message)
+    }
+}
+
+impl std::error::Error for QueryError {}
+
+/// What the `status` filter and `status:` term select
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DagStatus {
+    Active,
+    Paused,
+    /// Has succeeded runs and no failed ones
+    Success,
+    Failed,
+    Running,
+}
+
+impl DagStatus {
+    pub const ALL: [DagStatus; 5] = [
+        DagStatus::Active,
+        DagStatus::Paused,
+        DagStatus::Success,
+        DagStatus::Failed,
+        DagStatus::Running,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DagStatus::Active => "active",
+            DagStatus::Paused => "paused",
+            DagStatus::Success => "success",
+            DagStatus::Failed => "failed",
+            DagStatus::Running => "running",
+        }
+    }
+
+    pub fn matches(&self, dag: &DAG) -> bool {
+        match self {
+            DagStatus::Active => !dag.paused,
+            DagStatus::Paused => dag.paused,
+            DagStatus::Success => dag.success_count > 0 && dag.failed_count == 0,
+            DagStatus::Failed => dag.failed_count > 0,
+            DagStatus::Running => dag.running_count > 0,
+        }
+    }
+}
+
+impl FromStr for DagStatus {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        DagStatus::ALL
+            .into_iter()
+            .find(|status| status.as_str().eq_ignore_ascii_case(s))
+            .ok_or_else(|| format!("unknown status '{}'", s))
+    }
+}
+
+/// What an empty query or group parses to
+const EVERYTHING: Filter = Filter::And(Vec::new());
+
+/// A parsed search query
+#[derive(Debug, Clone, PartialEq)]
+pub enum Filter {
+    /// Bare word without wildcards or quoted text, lower-cased
+    Text(String),
+    Id(Pattern),
+    Owner(Pattern),
+    Tag(Pattern),
+    Schedule(String),
+    Status(DagStatus),
+    Not(Box<Filter>),
+    And(Vec<Filter>),
+    Or(Vec<Filter>),
+}
+
+impl Filter {
+    /// Parse a search query; blank queries match every DAG
+    pub fn parse(query: &str) -> Result<Filter, QueryError> {
+        let tokens = tokenize(query)?;
+        let mut parser = Parser { tokens, next: 0 };
+        let filter = parser.or_group()?;
+        match parser.tokens.get(parser.next) {
+            None => Ok(filter),
+            Some(token) => Err(QueryError::new(
+                QueryErrorKind::UnbalancedParenthesis,
+                token.position,
+                token.length,
+                "')' without a matching '('",
+            )),
+        }
+    }
+
+    pub fn matches(&self, dag: &DAG) -> bool {
+        match self {
+            Filter::Text(text) => {
+                dag.dag_id.to_lowercase().contains(text)
+                    || dag.owner.to_lowercase().contains(text)
+                    || dag.description.as_ref().is_some_and(|desc| desc.to_lowercase().contains(text))
+            }
+            Filter::Id(pattern) => pattern.matches(&dag.dag_id),
+            Filter::Owner(pattern) => pattern.matches(&dag.owner),
+            Filter::Tag(pattern) => dag.tags.iter().any(|tag| pattern.matches(tag)),
+            Filter::Schedule(schedule) => dag.schedule_interval.eq_ignore_ascii_case(schedule),
+            Filter::Status(status) => status.matches(dag),
+            Filter::Not(filter) => !filter.matches(dag),
+            Filter::And(filters) => filters.iter().all(|filter| filter.matches(dag)),
+            Filter::Or(filters) => filters.iter().any(|filter| filter.matches(dag)),
+        }
+    }
+}
+
+/// Case-insensitive text, where `*` matches any run of characters and `?` any one character
+#[derive(Debug, Clone, PartialEq)]
+pub struct Pattern(Vec<char>);
+
+impl Pattern {
+    fn new(text: &str) -> Pattern {
+        Pattern(text.to_lowercase().chars().collect())
+    }
+
+    fn is_glob(&self) -> bool {
+        self.0.iter().any(|c| matches!(c, '*' | '?'))
+    }
+
+    /// Whether the whole of `text` matches
+    fn matches(&self, text: &str) -> bool {
+        let text: Vec<char> = text.to_lowercase().chars().collect();
+        let (mut p, mut t) = (0, 0);
+        // Where the last `*` was seen in the pattern, and the text position it currently covers up to
+        let mut star: Option<(usize, usize)> = None;
+        while t < text.len() {
+            match self.0.get(p) {
+                Some('*') => {
+                    star = Some((p, t));
+                    p += 1;
+                }
+                Some(c) if *c == '?' || *c == text[t] => {
+                    p += 1;
+                    t += 1;
+                }
+                _ => match star {
+                    Some((star_p, star_t)) => {
+                        p = star_p + 1;
+                        t = star_t + 1;
+                        star = Some((star_p, star_t + 1));
+                    }
+                    None => return false,
+                },
+            }
+        }
+        self.0[p..].iter().all(|c| *c == '*')
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum TokenKind {
+    Open,
+    Close,
+    Or,
+    Not,
+    Term {
+        field: Option<String>,
+        value: String,
+        quoted: bool,
+    },
+}
+
+#[derive(Debug, Clone)]
+struct Token {
+    kind: TokenKind,
+    position: usize,
+    length: usize,
+}
+
+fn tokenize(query: &str) -> Result<Vec<Token>, QueryError> {
+    let chars: Vec<char> = query.chars().collect();
+    let mut tokens = Vec::new();
+    let mut at = 0;
+    while at < chars.len() {
+        let start = at;
+        let kind = match chars[at] {
+            c if c.is_whitespace() => {
+                at += 1;
+                continue;
+            }
+            '(' => {
+                at += 1;
+                TokenKind::Open
+            }
+            ')' => {
+                at += 1;
+                TokenKind::Close
+            }
+            '-' => {
+                at += 1;
+                TokenKind::Not
+            }
+            '"' => {
+                let value = quoted(&chars, &mut at)?;
+                TokenKind::Term {
+                    field: None,
+                    value,
+                    quoted: true,
+                }
+            }
+            _ => {
+                while at < chars.len() && !is_delimiter(chars[at]) {
+                    at += 1;
+                }
+                let word: String = chars[start..at].iter().collect();
+                match word.as_str() {
+                    "OR" => TokenKind::Or,
+                    "NOT" => TokenKind::Not,
+                    // Terms are ANDed anyway
+                    "AND" => continue,
+                    _ => match word.split_once(':') {
+                        Some((field, value)) => {
+                            // `field:"quoted value"`
+                            let value = if value.is_empty() && chars.get(at) == Some(&'"') {
+                                quoted(&chars, &mut at)?
+                            } else {
+                                value.to_string()
+                            };
+                            TokenKind::Term {
+                                field: Some(field.to_lowercase()),
+                                value,
+                                quoted: false,
+                            }
+                        }
+                        None => TokenKind::Term {
+                            field: None,
+                            value: word,
+                            quoted: false,
+                        },
+                    },
+                }
+            }
+        };
+        tokens.push(Token {
+            kind,
+            position: start,
+            length: at - start,
+        });
+    }
+    Ok(tokens)
+}
+
+fn is_delimiter(c: char) -> bool {
+    c.is_whitespace() || matches!(c, '(' | ')' | '"')
+}
+
+/// Read a `"..."` string starting at `at`, leaving `at` after the closing quote
+fn quoted(chars: &[char], at: &mut usize) -> Result<String, QueryError> {
+    let start = *at;
+    let Some(end) = chars[start + 1..].iter().position(|c| *c == '"') else {
+        return Err(QueryError::new(
+            QueryErrorKind::UnterminatedQuote,
+            start,
+            chars.len() - start,
+            "missing closing '\"'",
+        ));
+    };
+    *at = start + 1 + end + 1;
+    Ok(chars[start + 1..start + 1 + end].iter().collect())
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    next: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.next)
+    }
+
+    /// `and_group (OR and_group)*`
+    fn or_group(&mut self) -> Result<Filter, QueryError> {
+        let mut alternatives = vec![self.and_group()?];
+        while let Some(token) = self.peek().filter(|token| token.kind == TokenKind::Or).cloned() {
+            self.next += 1;
+            let alternative = self.and_group()?;
+            if alternatives.last() == Some(&EVERYTHING) || alternative == EVERYTHING {
+                return Err(QueryError::new(
+                    QueryErrorKind::MissingTerm,
+                    token.position,
+                    token.length,
+                    "OR needs a term on both sides",
+                ));
+            }
+            alternatives.push(alternative);
+        }
+        Ok(if alternatives.len() == 1 { alternatives.remove(0) } else { Filter::Or(alternatives) })
+    }
+
+    /// Terms up to the next `OR`, `)` or the end
+    fn and_group(&mut self) -> Result<Filter, QueryError> {
+        let mut terms = Vec::new();
+        while let Some(token) = self.peek() {
+            if matches!(token.kind, TokenKind::Or | TokenKind::Close) {
+                break;
+            }
+            terms.push(self.unary()?);
+        }
+        Ok(if terms.len() == 1 { terms.remove(0) } else { Filter::And(terms) })
+    }
+
+    fn unary(&mut self) -> Result<Filter, QueryError> {
+        let token = self.tokens[self.next].clone();
+        self.next += 1;
+        match &token.kind {
+            TokenKind::Not => match self.peek() {
+                Some(next) if !matches!(next.kind, TokenKind::Or | TokenKind::Close) => {
+                    Ok(Filter::Not(Box::new(self.unary()?)))
+                }
+                _ => Err(QueryError::new(
+                    QueryErrorKind::MissingTerm,
+                    token.position,
+                    token.length,
+                    "nothing to negate",
+                )),
+            },
+            TokenKind::Open => {
+                let group = self.or_group()?;
+                match self.peek().cloned() {
+                    Some(close) if close.kind == TokenKind::Close => {
+                        self.next += 1;
+                        if group == EVERYTHING {
+                            return Err(QueryError::new(
+                                QueryErrorKind::MissingTerm,
+                                token.position,
+                                close.position + 1 - token.position,
+                                "empty parentheses",
+                            ));
+                        }
+                        Ok(group)
+                    }
+                    _ => Err(QueryError::new(
+                        QueryErrorKind::UnbalancedParenthesis,
+                        token.position,
+                        token.length,
+                        "'(' without a matching ')'",
+                    )),
+                }
+            }
+            TokenKind::Term { field, value, quoted } => term(field.as_deref(), value, *quoted, &token),
+            TokenKind::Or | TokenKind::Close => unreachable!("and_group stops at OR and ')'"),
+        }
+    }
+}
+
+fn term(field: Option<&str>, value: &str, quoted: bool, token: &Token) -> Result<Filter, QueryError> {
+    let Some(field) = field else {
+        let pattern = Pattern::new(value);
+        return Ok(if !quoted && pattern.is_glob() {
+            Filter::Id(pattern)
+        } else {
+            Filter::Text(value.to_lowercase())
+        });
+    };
+    if value.is_empty() {
+        return Err(QueryError::new(
+            QueryErrorKind::EmptyValue,
+            token.position,
+            token.length,
+            format!("'{}:' needs a value", field),
+        ));
+    }
+    match field {
+        "id" => Ok(Filter::Id(Pattern::new(value))),
+        "owner" => Ok(Filter::Owner(Pattern::new(value))),
+        "tag" => Ok(Filter::Tag(Pattern::new(value))),
+        "schedule" => Ok(Filter::Schedule(value.to_string())),
+        "status" => value.parse().map(Filter::Status).map_err(|message: String| {
+            let known: Vec<&str> = DagStatus::ALL.iter().map(DagStatus::as_str).collect();
+            QueryError::new(
+                QueryErrorKind::UnknownStatus,
+                token.position + field.chars().count() + 1,
+                value.chars().count(),
+                format!("{}, expected one of {}", message, known.join(", ")),
+            )
+        }),
+        other => Err(QueryError::new(
+            QueryErrorKind::UnknownField,
+            token.position,
+            other.chars().count(),
+            format!("unknown field '{}', expected one of {}", other, FIELDS.join(", ")),
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Utc;
+    use uuid::Uuid;
+
+    use super::*;
+    use crate::models::default_timezone;
+
+    fn text(word: &str) -> Filter {
+        Filter::Text(word.to_string())
+    }
+
+    fn parse_error(query: &str) -> (QueryErrorKind, usize, usize) {
+        let err = Filter::parse(query).unwrap_err();
+        (err.kind, err.position, err.length)
+    }
+
+    fn dag(schedule_interval: &str) -> DAG {
+        let now = Utc::now();
+        DAG {
+            id: Uuid::new_v4(),
+            dag_id: "pipeline".to_string(),
+            description: None,
+            file_path: String::new(),
+            owner: "me".to_string(),
+            paused: false,
+            last_run: None,
+            next_run: None,
+            runs_count: 0,
+            success_count: 0,
+            failed_count: 0,
+            running_count: 0,
+            schedule_interval: schedule_interval.to_string(),
+            timezone: default_timezone(),
+            tags: Vec::new(),
+            params_schema: None,
+            created_at: now,
+            updated_at: now,
+        }
+    }
+
+    #[test]
+    fn or_binds_looser_than_the_implicit_and() {
+        assert_eq!(
+            Filter::parse("a b OR c").unwrap(),
+            Filter::Or(vec![Filter::And(vec![text("a"), text("b")]), text("c")])
+        );
+        assert_eq!(Filter::parse("a AND b").unwrap(), Filter::parse("a b").unwrap());
+    }
+
+    #[test]
+    fn not_applies_to_the_next_term_only() {
+        assert_eq!(
+            Filter::parse("NOT a OR b").unwrap(),
+            Filter::Or(vec![Filter::Not(Box::new(text("a"))), text("b")])
+        );
+        assert_eq!(
+            Filter::parse("-a b").unwrap(),
+            Filter::And(vec![Filter::Not(Box::new(text("a"))), text("b")])
+        );
+    }
+
+    #[test]
+    fn parentheses_group() {
+        assert_eq!(
+            Filter::parse("a (b OR c)").unwrap(),
+            Filter::And(vec![text("a"), Filter::Or(vec![text("b"), text("c")])])
+        );
+        assert_eq!(
+            Filter::parse("-(a OR b)").unwrap(),
+            Filter::Not(Box::new(Filter::Or(vec![text("a"), text("b")])))
+        );
+    }
+
+    #[test]
+    fn fields_and_wildcards() {
+        assert_eq!(
+            Filter::parse("owner:\"data team\" tag:etl_* status:FAILED").unwrap(),
+            Filter::And(vec![
+                Filter::Owner(Pattern::new("data team")),
+                Filter::Tag(Pattern::new("etl_*")),
+                Filter::Status(DagStatus::Failed),
+            ])
+        );
+        assert_eq!(Filter::parse("etl_*").unwrap(), Filter::Id(Pattern::new("etl_*")));
+        assert_eq!(Filter::parse("\"etl_*\"").unwrap(), text("etl_*"));
+        assert_eq!(Filter::parse("  ").unwrap(), EVERYTHING);
+    }
+
+    #[test]
+    fn patterns_match_the_whole_value() {
+        let pattern = Pattern::new("etl_*_d?ily");
+        assert!(pattern.matches("ETL_sales_daily"));
+        assert!(pattern.matches("etl__daily"));
+        assert!(!pattern.matches("etl_sales_daily_backup"));
+        assert!(!pattern.matches("etl_sales_dily"));
+    }
+
+    #[test]
+    fn schedules_match_whole_and_ignore_case() {
+        let daily = Filter::parse("schedule:@daily").unwrap();
+        assert!(daily.matches(&dag("@daily")));
+        assert!(daily.matches(&dag("@DAILY")));
+        assert!(!daily.matches(&dag("@daily_at_noon")));
+        let cron = Filter::parse("schedule:\"0 * * * MON\"").unwrap();
+        assert!(cron.matches(&dag("0 * * * mon")));
+        // `*` is part of the cron expression, not a wildcard
+        assert!(!cron.matches(&dag("0 6 * * MON")));
+    }
+
+    #[test]
+    fn errors_point_at_the_offending_text() {
+        assert_eq!(parse_error("tag:x status:bogus"), (QueryErrorKind::UnknownStatus, 13, 5));
+        // Positions count characters, not bytes
+        assert_eq!(parse_error("café status:bogus"), (QueryErrorKind::UnknownStatus, 12, 5));
+        assert_eq!(parse_error("foo:bar"), (QueryErrorKind::UnknownField, 0, 3));
+        assert_eq!(parse_error("a owner:"), (QueryErrorKind::EmptyValue, 2, 6));
+        assert_eq!(parse_error("a \"abc"), (QueryErrorKind::UnterminatedQuote, 2, 4));
+        assert_eq!(parse_error("(a OR b"), (QueryErrorKind::UnbalancedParenthesis, 0, 1));
+        assert_eq!(parse_error("a b)"), (QueryErrorKind::UnbalancedParenthesis, 3, 1));
+        assert_eq!(parse_error("a OR"), (QueryErrorKind::MissingTerm, 2, 2));
+        assert_eq!(parse_error("OR a"), (QueryErrorKind::MissingTerm, 0, 2));
+        assert_eq!(parse_error("a -"), (QueryErrorKind::MissingTerm, 2, 1));
+        assert_eq!(parse_error("a ( )"), (QueryErrorKind::MissingTerm, 2, 3));
+    }
+
+    #[test]
+    fn error_display_counts_from_one() {
+        assert_eq!(
+            Filter::parse("foo:bar").unwrap_err().to_string(),
+            "invalid search at character 1: unknown field 'foo', expected one of id, owner, tag, status, schedule"
+        );
+    }
+}
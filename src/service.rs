@@ -0,0 +1,418 @@
+use std::collections::BTreeMap;
+use std::fmt;
+
+use chrono::{DateTime, Utc};
+use serde_json::json;
+
+use crate::audit;
+use crate::events::ServerEvent;
+use crate::models::{
+    AuditAction, AuditEntry, ClearPayload, ClearResponse, DAGGraph, DAGsQuery, DAGsResponse, DagRun, DagRunsQuery,
+    DagRunsResponse, RunState, RunType, TagCount, TagsMode, TagsQuery, TagsResponse, TaskStatusPayload,
+    TriggerRunPayload, DAG,
+};
+use crate::listing::{ListingError, SortSpec};
+use crate::params::{self, ParamsError};
+use crate::registry::{RegistryError, RunCounts};
+use crate::schedule::{self, ScheduleError};
+use crate::scheduler;
+use crate::search::{DagStatus, Filter, QueryError};
+use crate::state::AppState;
+
+/// Error from an operation shared by the HTTP handlers and the command line
+#[derive(Debug)]
+pub enum ServiceError {
+    /// The request itself is unusable: bad range, schedule, conf, ...
+    Invalid(String),
+    NotFound(String),
+    /// The request is valid but not allowed in the current state
+    Conflict(String),
+    /// Wrong or missing credentials
+    Unauthorized(String),
+    /// The DAG search query is malformed
+    InvalidQuery(QueryError),
+    Registry(RegistryError),
+}
+
+impl fmt::Display for ServiceError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ServiceError::Invalid(message)
+            | ServiceError::NotFound(message)
+            | ServiceError::Conflict(message)
+            | ServiceError::Unauthorized(message) => write!(f, "{}", message),
+            ServiceError::InvalidQuery(err) => write!(f, "{}", err),
+            ServiceError::Registry(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl std::error::Error for ServiceError {}
+
+impl From<RegistryError> for ServiceError {
+    fn from(err: RegistryError) -> Self {
+        ServiceError::Registry(err)
+    }
+}
+
+impl From<ScheduleError> for ServiceError {
+    fn from(err: ScheduleError) -> Self {
+        ServiceError::Invalid(err.to_string())
+    }
+}
+
+impl From<ListingError> for ServiceError {
+    fn from(err: ListingError) -> Self {
+        ServiceError::Invalid(err.to_string())
+    }
+}
+
+impl From<QueryError> for ServiceError {
+    fn from(err: QueryError) -> Self {
+        ServiceError::InvalidQuery(err)
+    }
+}
+
+impl From<ParamsError> for ServiceError {
+    fn from(err: ParamsError) -> Self {
+        ServiceError::Invalid(err.to_string())
+    }
+}
+
+/// Most items one page of a listing holds
+pub const MAX_PAGE_SIZE: usize = 1000;
+
+/// Offset and size of a page of a listing. Pages count from 1; the size is capped at
+/// `MAX_PAGE_SIZE` and a far-off page saturates instead of overflowing.
+pub fn page_window(page: Option<usize>, limit: Option<usize>, default_limit: usize) -> (usize, usize) {
+    let limit = limit.unwrap_or(default_limit).min(MAX_PAGE_SIZE);
+    let offset = page.unwrap_or(1).saturating_sub(1).saturating_mul(limit);
+    (offset, limit)
+}
+
+/// One page of DAGs, with run counters, after search, status and tag filters and sorting. The
+/// total counts the DAGs left after filtering.
+pub fn list_dags(state: &AppState, query: &DAGsQuery) -> Result<DAGsResponse, ServiceError> {
+    let (offset, limit) = page_window(query.page, query.limit, 25);
+
+    let mut dags = search_dags(state, query.search.as_deref(), query.status.as_deref())?;
+
+    // Apply tag filter if provided
+    if let Some(tags) = &query.tags {
+        let tag_list: Vec<&str> = tags.split(',').map(str::trim).filter(|tag| !tag.is_empty()).collect();
+        let has_tag = |dag: &DAG, tag: &&str| dag.tags.iter().any(|t| t.eq_ignore_ascii_case(tag));
+        match query.tags_mode.unwrap_or_default() {
+            TagsMode::Any if !tag_list.is_empty() => dags.retain(|dag| tag_list.iter().any(|tag| has_tag(dag, tag))),
+            TagsMode::Any => {}
+            TagsMode::All => dags.retain(|dag| tag_list.iter().all(|tag| has_tag(dag, tag))),
+        }
+    }
+
+    let total_count = dags.len();
+
+    let sort = SortSpec::parse(query.sort_by.as_deref(), query.sort_order.as_deref())?;
+    sort.sort(&mut dags);
+
+    let start = match &query.cursor {
+        Some(cursor) => sort.position_after(&dags, cursor)?,
+        None => offset,
+    };
+    let end = start.saturating_add(limit).min(dags.len());
+    let next_cursor = (end < dags.len() && end > 0).then(|| sort.cursor_after(&dags[end - 1]));
+    let dags = dags.into_iter().skip(start).take(limit).collect::<Vec<_>>();
+
+    Ok(DAGsResponse {
+        dags,
+        total_count,
+        next_cursor,
+    })
+}
+
+/// Every tag with the number of DAGs carrying it, among the DAGs the search and status filters
+/// leave, most used first
+pub fn tag_counts(state: &AppState, query: &TagsQuery) -> Result<TagsResponse, ServiceError> {
+    let dags = search_dags(state, query.search.as_deref(), query.status.as_deref())?;
+    // Tags match case-insensitively, so spellings are counted together under the one used most
+    let mut counts: BTreeMap<String, (usize, BTreeMap<&str, usize>)> = BTreeMap::new();
+    for dag in &dags {
+        let tags: BTreeMap<String, &str> = dag.tags.iter().map(|tag| (tag.to_ascii_lowercase(), tag.as_str())).collect();
+        for (key, tag) in tags {
+            let (count, spellings) = counts.entry(key).or_default();
+            *count += 1;
+            *spellings.entry(tag).or_default() += 1;
+        }
+    }
+    let mut tags: Vec<TagCount> = counts
+        .into_values()
+        .map(|(count, spellings)| TagCount {
+            tag: spellings
+                .into_iter()
+                .max_by(|a, b| a.1.cmp(&b.1).then_with(|| b.0.cmp(a.0)))
+                .map(|(tag, _)| tag.to_string())
+                .unwrap_or_default(),
+            count,
+        })
+        .collect();
+    tags.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.tag.cmp(&b.tag)));
+    Ok(TagsResponse { tags })
+}
+
+/// The DAGs, with run counters, that a search query and status filter leave
+fn search_dags(state: &AppState, search: Option<&str>, status: Option<&str>) -> Result<Vec<DAG>, ServiceError> {
+    let mut dags = state.registry.list()?;
+    let counts = state.runs.run_counts()?;
+    for dag in dags.iter_mut() {
+        apply_run_counts(dag, counts.get(&dag.dag_id));
+    }
+
+    if let Some(search) = search {
+        let filter = Filter::parse(search)?;
+        dags.retain(|dag| filter.matches(dag));
+    }
+
+    // Unknown statuses filter nothing
+    if let Some(Ok(status)) = status.map(str::parse::<DagStatus>) {
+        dags.retain(|dag| status.matches(dag));
+    }
+    Ok(dags)
+}
+
+/// A DAG with its run counters
+pub fn get_dag(state: &AppState, dag_id: &str) -> Result<DAG, ServiceError> {
+    let mut dag = state
+        .registry
+        .get(dag_id)?
+        .ok_or_else(|| RegistryError::NotFound(dag_id.to_string()))?;
+    apply_run_counts(&mut dag, state.runs.run_counts()?.get(dag_id));
+    Ok(dag)
+}
+
+/// Overwrite the run counters of a DAG with totals taken from its stored runs
+pub fn apply_run_counts(dag: &mut DAG, counts: Option<&RunCounts>) {
+    let counts = counts.cloned().unwrap_or_default();
+    dag.runs_count = counts.total;
+    dag.success_count = counts.succeeded;
+    dag.failed_count = counts.failed;
+    dag.running_count = counts.active;
+    if counts.last_logical_date.is_some() {
+        dag.last_run = counts.last_logical_date;
+    }
+}
+
+/// Pause or unpause a DAG. Pausing drops the pending `next_run`; unpausing schedules the next
+/// run from now rather than catching up on the ticks missed while paused.
+pub fn set_paused(
+    state: &AppState,
+    dag_id: &str,
+    paused: bool,
+    comment: Option<&str>,
+    actor: &str,
+    now: DateTime<Utc>,
+) -> Result<DAG, ServiceError> {
+    let mut dag = state
+        .registry
+        .get(dag_id)?
+        .ok_or_else(|| RegistryError::NotFound(dag_id.to_string()))?;
+
+    let changed = dag.paused != paused;
+    dag.paused = paused;
+    dag.updated_at = now;
+    dag.next_run = schedule::next_run_for(&dag, now)?;
+
+    let mut dag = state.registry.update(dag)?;
+    if changed {
+        tracing::info!("{} {} for {}", if paused { "Paused" } else { "Unpaused" }, dag_id, actor);
+        let action = if paused { AuditAction::Pause } else { AuditAction::Unpause };
+        let mut entry = AuditEntry::new(now, actor, action, dag_id);
+        entry.before = Some(json!({ "paused": !paused }));
+        entry.after = Some(json!({ "paused": paused }));
+        audit::record(state, entry, comment);
+        state.events.publish(ServerEvent::DagPaused {
+            dag_id: dag.dag_id.clone(),
+            paused,
+        });
+    }
+    apply_run_counts(&mut dag, state.runs.run_counts()?.get(dag_id));
+    Ok(dag)
+}
+
+/// One page of a DAG's runs, newest first, optionally only those in one state
+pub fn list_runs(state: &AppState, dag_id: &str, query: &DagRunsQuery) -> Result<DagRunsResponse, ServiceError> {
+    if state.registry.get(dag_id)?.is_none() {
+        return Err(RegistryError::NotFound(dag_id.to_string()).into());
+    }
+
+    let (offset, limit) = page_window(query.page, query.limit, 25);
+    let state_filter = match &query.state {
+        Some(name) => Some(parse_run_state(name)?),
+        None => None,
+    };
+
+    let mut runs = state.runs.list_runs(dag_id)?;
+    if let Some(wanted) = state_filter {
+        runs.retain(|run| run.state == wanted);
+    }
+    let total_count = runs.len();
+    let runs = runs
+        .into_iter()
+        .skip(offset)
+        .take(limit)
+        .collect();
+
+    Ok(DagRunsResponse { runs, total_count })
+}
+
+/// Create a manual run with the given `conf`. The run is queued and started by the scheduler
+/// like any other, so it respects the overlap policy.
+pub fn trigger_run(
+    state: &AppState,
+    dag_id: &str,
+    payload: TriggerRunPayload,
+    actor: &str,
+    now: DateTime<Utc>,
+) -> Result<DagRun, ServiceError> {
+    let dag = state
+        .registry
+        .get(dag_id)?
+        .ok_or_else(|| RegistryError::NotFound(dag_id.to_string()))?;
+    let graph = state
+        .registry
+        .get_graph(dag_id)?
+        .ok_or_else(|| ServiceError::Invalid(format!("DAG '{}' has no graph to run", dag_id)))?;
+    let conf = params::resolve_conf(dag.params_schema.as_ref(), payload.conf)?;
+
+    let logical_date = payload.logical_date.unwrap_or(now);
+    let run_id = format!("manual__{}", logical_date.to_rfc3339());
+    let mut run = scheduler::new_run(&graph, run_id, RunType::MANUAL, logical_date, now);
+    run.conf = conf;
+    run.triggered_by = Some(actor.to_string());
+
+    let run = state.runs.create_run(run)?;
+    tracing::info!("Triggered run {} of {} for {}", run.run_id, dag_id, actor);
+    let mut entry = AuditEntry::new(now, actor, AuditAction::Trigger, dag_id);
+    entry.run_id = Some(run.run_id.clone());
+    entry.after = Some(json!({ "logical_date": run.logical_date, "conf": run.conf }));
+    audit::record(state, entry, payload.comment.as_deref());
+    Ok(run)
+}
+
+/// Change the state of a task instance by hand (mark success, skip, retry, ...), optionally
+/// together with its upstream or downstream tasks. Illegal transitions are a conflict.
+pub fn set_task_status(
+    state: &AppState,
+    dag_id: &str,
+    run_id: &str,
+    task_id: &str,
+    payload: &TaskStatusPayload,
+    actor: &str,
+    now: DateTime<Utc>,
+) -> Result<DagRun, ServiceError> {
+    let (_, graph) = run_with_task(state, dag_id, run_id, task_id)?;
+
+    // Taken inside the update, so the audit entry shows the states this change replaced
+    let mut before = None;
+    let mut result = Ok(Vec::new());
+    let run = state.runs.modify_run(dag_id, run_id, &mut |run| {
+        before = Some(run.clone());
+        result = scheduler::set_task_status(
+            run,
+            &graph,
+            task_id,
+            &payload.status,
+            payload.upstream,
+            payload.downstream,
+            now,
+        );
+    })?;
+
+    let changed = result.map_err(ServiceError::Conflict)?;
+    tracing::info!(
+        "Set {} of {} / {} to {:?} for {}",
+        changed.join(", "),
+        dag_id,
+        run_id,
+        payload.status,
+        actor
+    );
+    let mut entry = AuditEntry::new(now, actor, AuditAction::for_task_status(&payload.status), dag_id);
+    entry.run_id = Some(run_id.to_string());
+    entry.task_id = Some(task_id.to_string());
+    entry.before = before.map(|before| audit::task_states(&before, &changed));
+    entry.after = Some(audit::task_states(&run, &changed));
+    audit::record(state, entry, payload.comment.as_deref());
+    Ok(run)
+}
+
+/// Reset a task instance, and optionally related ones, to PENDING so it runs again. With
+/// `dry_run` set only the ids that would be cleared are returned.
+pub fn clear_task(
+    state: &AppState,
+    dag_id: &str,
+    run_id: &str,
+    task_id: &str,
+    payload: &ClearPayload,
+    actor: &str,
+    now: DateTime<Utc>,
+) -> Result<ClearResponse, ServiceError> {
+    let (run, graph) = run_with_task(state, dag_id, run_id, task_id)?;
+
+    if payload.dry_run {
+        let task_ids = scheduler::tasks_to_clear(&run, &graph, task_id, payload);
+        return Ok(ClearResponse {
+            task_ids,
+            dry_run: true,
+            run,
+        });
+    }
+
+    // Taken inside the update, so the audit entry shows the states this change replaced
+    let mut before = None;
+    let mut task_ids = Vec::new();
+    let run = state.runs.modify_run(dag_id, run_id, &mut |run| {
+        before = Some(run.clone());
+        task_ids = scheduler::tasks_to_clear(run, &graph, task_id, payload);
+        scheduler::clear_tasks(run, &graph, &task_ids, now);
+    })?;
+    tracing::info!("Cleared {} of {} / {} for {}", task_ids.join(", "), dag_id, run_id, actor);
+    let mut entry = AuditEntry::new(now, actor, AuditAction::Clear, dag_id);
+    entry.run_id = Some(run_id.to_string());
+    entry.task_id = Some(task_id.to_string());
+    entry.before = before.map(|before| audit::task_states(&before, &task_ids));
+    entry.after = Some(audit::task_states(&run, &task_ids));
+    audit::record(state, entry, payload.comment.as_deref());
+
+    Ok(ClearResponse {
+        task_ids,
+        dry_run: false,
+        run,
+    })
+}
+
+/// A run that has the given task, and the graph version it was created from
+fn run_with_task(
+    state: &AppState,
+    dag_id: &str,
+    run_id: &str,
+    task_id: &str,
+) -> Result<(DagRun, DAGGraph), ServiceError> {
+    let run = state
+        .runs
+        .get_run(dag_id, run_id)?
+        .ok_or_else(|| RegistryError::RunNotFound {
+            dag_id: dag_id.to_string(),
+            run_id: run_id.to_string(),
+        })?;
+    if !run.task_instances.iter().any(|ti| ti.task_id == task_id) {
+        return Err(ServiceError::NotFound(format!("task '{}' not found in run '{}'", task_id, run_id)));
+    }
+    let graph = state
+        .registry
+        .get_graph_version(dag_id, run.graph_version)?
+        .ok_or_else(|| RegistryError::NotFound(dag_id.to_string()))?;
+    Ok((run, graph))
+}
+
+fn parse_run_state(name: &str) -> Result<RunState, ServiceError> {
+    serde_json::from_value(serde_json::Value::String(name.to_ascii_uppercase()))
+        .map_err(|_| ServiceError::Invalid(format!("unknown run state '{}'", name)))
+}
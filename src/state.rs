@@ -0,0 +1,38 @@
+use std::sync::{Arc, RwLock};
+
+use crate::config::AuthConfig;
+use crate::events::{EventBus, PublishingRunStore};
+use crate::logs::LogStore;
+use crate::models::ImportError;
+use crate::registry::{AuditStore, DagRegistry, RunStore, Stores, UserStore};
+
+/// Shared state handed to every axum handler
+#[derive(Clone)]
+pub struct AppState {
+    pub registry: Arc<dyn DagRegistry>,
+    /// Publishes every run and task instance change to `events`
+    pub runs: Arc<dyn RunStore>,
+    pub users: Arc<dyn UserStore>,
+    pub audit: Arc<dyn AuditStore>,
+    pub logs: Arc<LogStore>,
+    pub events: Arc<EventBus>,
+    pub auth: Arc<AuthConfig>,
+    /// Parse failures from the most recent scan of the DAG folder
+    pub import_errors: Arc<RwLock<Vec<ImportError>>>,
+}
+
+impl AppState {
+    pub fn new(stores: Stores, logs: LogStore, auth: AuthConfig) -> Self {
+        let events = Arc::new(EventBus::new());
+        Self {
+            registry: stores.registry,
+            runs: Arc::new(PublishingRunStore::new(stores.runs, events.clone())),
+            users: stores.users,
+            audit: stores.audit,
+            logs: Arc::new(logs),
+            events,
+            auth: Arc::new(auth),
+            import_errors: Arc::new(RwLock::new(Vec::new())),
+        }
+    }
+}
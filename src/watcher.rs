@@ -0,0 +1,147 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+use tokio::task::JoinHandle;
+
+use crate::loader;
+use crate::state::AppState;
+
+/// Keeps the registry in sync with the DAG folder by re-parsing files whose modification time
+/// changed since the previous scan. A file that stops parsing leaves its last good version
+/// registered and shows up in the import errors until it is fixed.
+pub struct DagFolderWatcher {
+    dir: PathBuf,
+    state: AppState,
+    /// Modification time of every DAG file seen on the previous scan
+    seen: HashMap<PathBuf, SystemTime>,
+    /// Which file currently defines each dag_id
+    owners: HashMap<String, PathBuf>,
+}
+
+impl DagFolderWatcher {
+    pub fn new(dir: impl Into<PathBuf>, state: AppState) -> Self {
+        Self {
+            dir: dir.into(),
+            state,
+            seen: HashMap::new(),
+            owners: HashMap::new(),
+        }
+    }
+
+    /// Pick up new, changed and removed files. Returns the number of DAGs (re)registered.
+    pub fn scan(&mut self) -> usize {
+        let current = match list_dag_files(&self.dir) {
+            Ok(files) => files,
+            Err(err) => {
+                tracing::warn!("Cannot read DAG folder {}: {}", self.dir.display(), err);
+                return 0;
+            }
+        };
+
+        let removed: Vec<PathBuf> = self
+            .seen
+            .keys()
+            .filter(|path| !current.contains_key(*path))
+            .cloned()
+            .collect();
+        for path in removed {
+            tracing::info!("DAG file removed: {} (DAG stays registered)", path.display());
+            self.seen.remove(&path);
+            self.owners.retain(|_, owner| *owner != path);
+            self.set_error(&path, None);
+        }
+
+        let mut changed: Vec<(&PathBuf, &SystemTime)> = current
+            .iter()
+            .filter(|(path, modified)| self.seen.get(*path) != Some(*modified))
+            .collect();
+        changed.sort();
+
+        let mut registered = 0;
+        for (path, modified) in changed {
+            self.seen.insert(path.clone(), *modified);
+            if self.reload(path) {
+                registered += 1;
+            }
+        }
+        registered
+    }
+
+    /// Poll the folder on a fixed interval for the lifetime of the process
+    pub fn spawn(mut self, interval: Duration) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            ticker.tick().await;
+            loop {
+                ticker.tick().await;
+                let registered = self.scan();
+                if registered > 0 {
+                    tracing::info!("Reloaded {} DAG definitions from {}", registered, self.dir.display());
+                }
+            }
+        })
+    }
+
+    fn reload(&mut self, path: &Path) -> bool {
+        let loaded = match loader::parse_dag_file(path) {
+            Ok(loaded) => loaded,
+            Err(err) => {
+                tracing::warn!("Import error in {}: {}", path.display(), err);
+                self.set_error(path, Some(err));
+                return false;
+            }
+        };
+
+        let dag_id = loaded.dag.dag_id.clone();
+        if let Some(owner) = self.owners.get(&dag_id) {
+            if owner != path && self.seen.contains_key(owner) {
+                let err = format!("dag_id '{}' is already defined in {}", dag_id, owner.display());
+                tracing::warn!("Import error in {}: {}", path.display(), err);
+                self.set_error(path, Some(err));
+                return false;
+            }
+        }
+
+        // A file that is renamed to a different dag_id releases its old one
+        self.owners.retain(|_, owner| owner != path);
+
+        match loader::register_loaded_dag(self.state.registry.as_ref(), loaded) {
+            Ok(_) => {
+                self.owners.insert(dag_id, path.to_path_buf());
+                self.set_error(path, None);
+                true
+            }
+            Err(err) => {
+                tracing::error!("Failed to register DAG {}: {}", dag_id, err);
+                self.set_error(path, Some(err.to_string()));
+                false
+            }
+        }
+    }
+
+    /// Replace the recorded import error for a file, or clear it when `error` is `None`
+    fn set_error(&self, path: &Path, error: Option<String>) {
+        let file_path = path.display().to_string();
+        let mut errors = self.state.import_errors.write().unwrap();
+        errors.retain(|e| e.file_path != file_path);
+        if let Some(error) = error {
+            errors.push(loader::import_error(path, error));
+        }
+    }
+}
+
+fn list_dag_files(dir: &Path) -> std::io::Result<HashMap<PathBuf, SystemTime>> {
+    let mut files = HashMap::new();
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if !path.is_file() || !loader::is_dag_file(&path) {
+            continue;
+        }
+        if let Ok(modified) = fs::metadata(&path).and_then(|meta| meta.modified()) {
+            files.insert(path, modified);
+        }
+    }
+    Ok(files)
+}